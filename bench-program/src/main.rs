@@ -0,0 +1,32 @@
+//! Decodes proof nodes through the owned or the borrowed decoder so the bench script can
+//! compare their cycles. Built as its own ELF, never part of the program proofs settle against.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+mod legacy_decoder;
+
+use obsidian_lib::{verifier::MPTVerifier, DecodeBenchInput, DecodePath};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<DecodeBenchInput>();
+
+    let mut decoded: u32 = 0;
+    for proof in &input.proofs {
+        match input.path {
+            DecodePath::Owned => {
+                for node in proof {
+                    let node = legacy_decoder::NodeDecoder::decode_mpt_node(node);
+                    core::hint::black_box(node);
+                    decoded += 1;
+                }
+            }
+            DecodePath::Borrowed => {
+                let nodes = MPTVerifier::decode_proof(proof).unwrap();
+                decoded += nodes.len() as u32;
+                core::hint::black_box(nodes);
+            }
+        }
+    }
+
+    sp1_zkvm::io::commit(&decoded);
+}
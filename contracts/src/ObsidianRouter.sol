@@ -18,10 +18,26 @@ struct ObsidianOrder {
     address sell_token;
 }
 
+// order priced at an old block, block_hash is reached from the anchor through the header chain
+struct AnchoredObsidianOrder {
+    ObsidianOrder order;
+    bytes32 anchor_hash;
+    uint256 anchor_number;
+}
+
 contract ObsidianRouter is Ownable, EIP712 {
+    // program modes share one vkey, the first public value word names the mode that proved them
+    uint256 public constant MODE_ORDER = 0;
+    uint256 public constant MODE_ANCHORED_ORDER = 1;
+    uint256 public constant MODE_RAW_ORDER = 5;
+
     address public verifier;
     bytes32 public obsidianProgramVKey;
     bool public verifyBlock;
+    // how many blocks an anchored order's pricing block may trail its anchor
+    uint256 public maxAnchoredAge = 8191;
+    // signed orders already settled, keyed on the seller and the order hash they signed
+    mapping(bytes32 => bool) public settled;
 
     using ECDSA for bytes32;
 
@@ -55,6 +71,10 @@ contract ObsidianRouter is Ownable, EIP712 {
         verifyBlock = state;
     }
 
+    function setMaxAnchoredAge(uint256 age) public onlyOwner {
+        maxAnchoredAge = age;
+    }
+
     /**
      * @dev creates a hash of the order signed by seller
      * @param blockNumber agreed block number of the order used for pricing oracle
@@ -104,7 +124,14 @@ contract ObsidianRouter is Ownable, EIP712 {
         bytes calldata _proofBytes,
         bytes calldata _orderSignature
     ) external {
-        ObsidianOrder memory order = abi.decode(_publicValues, (ObsidianOrder));
+        (uint256 mode, ObsidianOrder memory order) = abi.decode(
+            _publicValues,
+            (uint256, ObsidianOrder)
+        );
+        require(
+            mode == MODE_ORDER || mode == MODE_RAW_ORDER,
+            "public values are not an order"
+        );
 
         require(
             verifySignature(order, _orderSignature),
@@ -118,14 +145,78 @@ contract ObsidianRouter is Ownable, EIP712 {
         );
 
         if (verifyBlock) {
-            bytes32 actualBlockHash = blockhash(order.block_number);
-            require(actualBlockHash != bytes32(0), "block is too old");
-            require(
-                actualBlockHash == order.block_hash,
-                "block hash does not match"
-            );
+            checkBlockHash(order.block_number, order.block_hash);
+        }
+
+        settle(order);
+    }
+
+    /**
+     * @dev settles an order older than the blockhash window, the proof links its block to a recent anchor
+     */
+    function solveAnchored(
+        bytes calldata _publicValues,
+        bytes calldata _proofBytes,
+        bytes calldata _orderSignature
+    ) external {
+        (uint256 mode, AnchoredObsidianOrder memory anchored) = abi.decode(
+            _publicValues,
+            (uint256, AnchoredObsidianOrder)
+        );
+        require(
+            mode == MODE_ANCHORED_ORDER,
+            "public values are not an anchored order"
+        );
+
+        require(
+            verifySignature(anchored.order, _orderSignature),
+            "invalid order signature"
+        );
+
+        ISP1Verifier(verifier).verifyProof(
+            obsidianProgramVKey,
+            _publicValues,
+            _proofBytes
+        );
+
+        require(
+            anchored.anchor_number >= anchored.order.block_number &&
+                anchored.anchor_number - anchored.order.block_number <=
+                maxAnchoredAge,
+            "order block too far behind anchor"
+        );
+
+        if (verifyBlock) {
+            checkBlockHash(anchored.anchor_number, anchored.anchor_hash);
         }
 
+        settle(anchored.order);
+    }
+
+    function checkBlockHash(
+        uint256 blockNumber,
+        bytes32 blockHash
+    ) internal view {
+        bytes32 actualBlockHash = blockhash(blockNumber);
+        require(actualBlockHash != bytes32(0), "block is too old");
+        require(actualBlockHash == blockHash, "block hash does not match");
+    }
+
+    function settle(ObsidianOrder memory order) internal {
+        bytes32 key = keccak256(
+            abi.encode(
+                order.seller,
+                getOrderHash(
+                    order.block_number,
+                    order.sold_amount,
+                    order.buy_token,
+                    order.sell_token
+                )
+            )
+        );
+        require(!settled[key], "order already settled");
+        settled[key] = true;
+
         require(
             IERC20(order.buy_token).transferFrom(
                 msg.sender,
@@ -3,6 +3,7 @@ pragma solidity ^0.8.20;
 
 import "forge-std/Test.sol";
 import "src/ObsidianRouter.sol";
+import {ERC20} from "@openzeppelin/token/ERC20/ERC20.sol";
 
 contract MockSP1Verifier is ISP1Verifier {
     mapping(bytes32 => mapping(bytes => mapping(bytes => bool)))
@@ -46,22 +47,25 @@ contract ObsidianRouterTest is Test {
         vm.selectFork(mainnetFork);
 
         bytes
-            memory publicValues = hex"000000000000000000000000befe5e6df5f3e4cba02b11ba233f6584f295b96d0e653461bc96391383c7ed60c6aca044ec9533eb3332e7a1dfbfcdd887d2b9500000000000000000000000000000000000000000000000000000000001aa35fc0000000000000000000000000000000000000000000000000001c8df78cc77c200000000000000000000000000000000000000000000000000000000000f42400000000000000000000000004200000000000000000000000000000000000006000000000000000000000000833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+            memory publicValues = hex"0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000befe5e6df5f3e4cba02b11ba233f6584f295b96d0e653461bc96391383c7ed60c6aca044ec9533eb3332e7a1dfbfcdd887d2b9500000000000000000000000000000000000000000000000000000000001aa35fc0000000000000000000000000000000000000000000000000001c8df78cc77c200000000000000000000000000000000000000000000000000000000000f42400000000000000000000000004200000000000000000000000000000000000006000000000000000000000000833589fcd6edb6e08f4c7c32d4f71b54bda02913";
         bytes
             memory proofBytes = hex"11b6a09d107bce4a042087be1bd27441006bc5ce3fb670654357fa7060e5ed2f6324c75d1047db20bee01862b1bd21eec259085af25313d2d0aff2d32ef727f4ebe3bede1314b4197c902662ff1d399c447538caf56515a505f797ec4c6d876d430af93116bda1aff1006a3cb7cd5dbe4a8b740cb51ca4e60c71190c0caefaebe8d6fb43011869007658d4795a3cad5a1bad7984ca7efa4788a30022bdc159ff265b690d19c203e5346780ca8ee020ccf9cd2836868cee110991ddeb1eb5e4b03e1b8cc606a9f3d9af567b68252da2b339c347c1cfe15b697ece45b9ebbc26043b2fb7b20a5f30ef551089cc0cc3a36800d2557297da9cb00a20a2ffa29ef436be292fd0";
 
-        ObsidianOrder memory order = abi.decode(publicValues, (ObsidianOrder));
+        (, ObsidianOrder memory order) = abi.decode(
+            publicValues,
+            (uint256, ObsidianOrder)
+        );
 
         bytes32 vkey = hex"0087e1ea649d141c1b62fc8155f7df892a3ef29b9744fe46ded393fab786638b";
 
         uint256 sellerPrivateKey = vm.envUint("SELLER_PRIVATE_KEY");
         address sellerAddress = order.seller;
 
-        router = new ObsidianRouter(
-            address(0x397A5f7f3dBd538f23DE225B51f532c34448dA9B),
-            vkey,
-            sellerAddress
-        );
+        // the recorded proof was made before orders carried the mode word, so it no longer
+        // verifies against these public values and the gateway verifier is mocked
+        verifier = new MockSP1Verifier();
+        verifier.setVerifyProofResult(vkey, publicValues, proofBytes, true);
+        router = new ObsidianRouter(address(verifier), vkey, sellerAddress);
 
         address derivedAddress = vm.addr(sellerPrivateKey);
         require(
@@ -114,12 +118,9 @@ contract ObsidianRouterTest is Test {
         uint256 mainnetFork = vm.createFork(vm.envString("MAINNET_RPC_URL"));
         vm.selectFork(mainnetFork);
 
-        // Connect to pre-deployed router
-        router = ObsidianRouter(ROUTER_ADDRESS);
-
         // Public values and proof from the original test
         bytes
-            memory publicValues = hex"000000000000000000000000befe5e6df5f3e4cba02b11ba233f6584f295b96d0e653461bc96391383c7ed60c6aca044ec9533eb3332e7a1dfbfcdd887d2b9500000000000000000000000000000000000000000000000000000000001aa35fc0000000000000000000000000000000000000000000000000001c8df78cc77c200000000000000000000000000000000000000000000000000000000000f42400000000000000000000000004200000000000000000000000000000000000006000000000000000000000000833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+            memory publicValues = hex"0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000befe5e6df5f3e4cba02b11ba233f6584f295b96d0e653461bc96391383c7ed60c6aca044ec9533eb3332e7a1dfbfcdd887d2b9500000000000000000000000000000000000000000000000000000000001aa35fc0000000000000000000000000000000000000000000000000001c8df78cc77c200000000000000000000000000000000000000000000000000000000000f42400000000000000000000000004200000000000000000000000000000000000006000000000000000000000000833589fcd6edb6e08f4c7c32d4f71b54bda02913";
         bytes
             memory proofBytes = hex"11b6a09d107bce4a042087be1bd27441006bc5ce3fb670654357fa7060e5ed2f6324c75d1047db20bee01862b1bd21eec259085af25313d2d0aff2d32ef727f4ebe3bede1314b4197c902662ff1d399c447538caf56515a505f797ec4c6d876d430af93116bda1aff1006a3cb7cd5dbe4a8b740cb51ca4e60c71190c0caefaebe8d6fb43011869007658d4795a3cad5a1bad7984ca7efa4788a30022bdc159ff265b690d19c203e5346780ca8ee020ccf9cd2836868cee110991ddeb1eb5e4b03e1b8cc606a9f3d9af567b68252da2b339c347c1cfe15b697ece45b9ebbc26043b2fb7b20a5f30ef551089cc0cc3a36800d2557297da9cb00a20a2ffa29ef436be292fd0";
 
@@ -129,7 +130,27 @@ contract ObsidianRouterTest is Test {
 
         address sender = 0xd4f23AfEAcfc05399E58e122B9a23cD04FA02C3B;
 
-        ObsidianOrder memory order = abi.decode(publicValues, (ObsidianOrder));
+        // the deployed router reads orders without the mode word, put the current router at its
+        // address so the fork's approvals and the signed domain still apply, and mock the verifier
+        // since the recorded proof predates the mode word
+        verifier = new MockSP1Verifier();
+        verifier.setVerifyProofResult(
+            PROGRAM_VKEY,
+            publicValues,
+            proofBytes,
+            true
+        );
+        deployCodeTo(
+            "ObsidianRouter.sol:ObsidianRouter",
+            abi.encode(address(verifier), PROGRAM_VKEY, sender),
+            ROUTER_ADDRESS
+        );
+        router = ObsidianRouter(ROUTER_ADDRESS);
+
+        (, ObsidianOrder memory order) = abi.decode(
+            publicValues,
+            (uint256, ObsidianOrder)
+        );
 
         // Log initial balances
         // console.log("========== Initial Balances ==========");
@@ -166,3 +187,201 @@ contract ObsidianRouterTest is Test {
         // console.log("Seller USDC:", IERC20(USDC).balanceOf(order.seller));
     }
 }
+
+contract MockERC20 is ERC20 {
+    constructor(string memory symbol) ERC20(symbol, symbol) {}
+
+    function mint(address to, uint256 amount) external {
+        _mint(to, amount);
+    }
+}
+
+contract ObsidianRouterUnitTest is Test {
+    uint256 internal constant SELLER_KEY = 0xA11CE;
+    bytes32 internal constant VKEY = bytes32(uint256(0x123));
+    bytes internal constant PROOF = hex"01";
+
+    ObsidianRouter internal router;
+    MockSP1Verifier internal verifier;
+    MockERC20 internal buyToken;
+    MockERC20 internal sellToken;
+    address internal seller;
+    address internal buyer = address(0xBEEF);
+
+    function setUp() public {
+        seller = vm.addr(SELLER_KEY);
+        verifier = new MockSP1Verifier();
+        router = new ObsidianRouter(address(verifier), VKEY, address(this));
+        buyToken = new MockERC20("BUY");
+        sellToken = new MockERC20("SELL");
+
+        buyToken.mint(buyer, 10e18);
+        sellToken.mint(seller, 10e18);
+        vm.prank(buyer);
+        buyToken.approve(address(router), type(uint256).max);
+        vm.prank(seller);
+        sellToken.approve(address(router), type(uint256).max);
+    }
+
+    function _order(
+        uint256 blockNumber
+    ) internal view returns (ObsidianOrder memory) {
+        return
+            ObsidianOrder({
+                seller: seller,
+                block_hash: keccak256(abi.encode(blockNumber)),
+                block_number: blockNumber,
+                bought_amount: 2e18,
+                sold_amount: 1e18,
+                buy_token: address(buyToken),
+                sell_token: address(sellToken)
+            });
+    }
+
+    function _sign(
+        ObsidianOrder memory order
+    ) internal view returns (bytes memory) {
+        bytes32 digest = router.getOrderHash(
+            order.block_number,
+            order.sold_amount,
+            order.buy_token,
+            order.sell_token
+        );
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(SELLER_KEY, digest);
+        return abi.encodePacked(r, s, v);
+    }
+
+    /// proven public values of an order priced at `blockNumber` and anchored at `anchorNumber`
+    function _anchored(
+        uint256 blockNumber,
+        uint256 anchorNumber
+    ) internal returns (ObsidianOrder memory order, bytes memory publicValues) {
+        order = _order(blockNumber);
+        publicValues = abi.encode(
+            router.MODE_ANCHORED_ORDER(),
+            AnchoredObsidianOrder(
+                order,
+                keccak256(abi.encode(anchorNumber)),
+                anchorNumber
+            )
+        );
+        verifier.setVerifyProofResult(VKEY, publicValues, PROOF, true);
+    }
+
+    function testSolveRejectsOtherModes() public {
+        ObsidianOrder memory order = _order(1);
+        // an account state proof, mode 3, under the same vkey
+        bytes memory publicValues = abi.encode(uint256(3), order);
+        verifier.setVerifyProofResult(VKEY, publicValues, PROOF, true);
+
+        vm.expectRevert("public values are not an order");
+        router.solve(publicValues, PROOF, _sign(order));
+    }
+
+    function testSolveAnchoredRejectsPlainOrder() public {
+        ObsidianOrder memory order = _order(1);
+        bytes memory publicValues = abi.encode(
+            router.MODE_ORDER(),
+            AnchoredObsidianOrder(order, keccak256(abi.encode(1)), 1)
+        );
+        verifier.setVerifyProofResult(VKEY, publicValues, PROOF, true);
+
+        vm.expectRevert("public values are not an anchored order");
+        router.solveAnchored(publicValues, PROOF, _sign(order));
+    }
+
+    function testSolveAnchoredSettles() public {
+        vm.roll(10_000);
+        router.setVerifyBlock(true);
+        (
+            ObsidianOrder memory order,
+            bytes memory publicValues
+        ) = _anchored(9_000, 9_990);
+        vm.setBlockhash(9_990, keccak256(abi.encode(uint256(9_990))));
+
+        vm.prank(buyer);
+        router.solveAnchored(publicValues, PROOF, _sign(order));
+
+        assertEq(buyToken.balanceOf(seller), order.bought_amount);
+        assertEq(sellToken.balanceOf(buyer), order.sold_amount);
+    }
+
+    function testSolveAnchoredRejectsReplay() public {
+        vm.roll(10_000);
+        router.setVerifyBlock(true);
+        (
+            ObsidianOrder memory order,
+            bytes memory publicValues
+        ) = _anchored(9_000, 9_990);
+        vm.setBlockhash(9_990, keccak256(abi.encode(uint256(9_990))));
+        bytes memory signature = _sign(order);
+
+        vm.prank(buyer);
+        router.solveAnchored(publicValues, PROOF, signature);
+
+        vm.prank(buyer);
+        vm.expectRevert("order already settled");
+        router.solveAnchored(publicValues, PROOF, signature);
+    }
+
+    function testSolveRejectsReplay() public {
+        ObsidianOrder memory order = _order(1);
+        bytes memory publicValues = abi.encode(router.MODE_ORDER(), order);
+        verifier.setVerifyProofResult(VKEY, publicValues, PROOF, true);
+        bytes memory signature = _sign(order);
+
+        vm.prank(buyer);
+        router.solve(publicValues, PROOF, signature);
+
+        vm.prank(buyer);
+        vm.expectRevert("order already settled");
+        router.solve(publicValues, PROOF, signature);
+    }
+
+    function testSolveAnchoredRejectsOrderSettledBySolve() public {
+        vm.roll(10_000);
+        (
+            ObsidianOrder memory order,
+            bytes memory publicValues
+        ) = _anchored(9_000, 9_990);
+        bytes memory plain = abi.encode(router.MODE_ORDER(), order);
+        verifier.setVerifyProofResult(VKEY, plain, PROOF, true);
+        bytes memory signature = _sign(order);
+
+        vm.prank(buyer);
+        router.solve(plain, PROOF, signature);
+
+        // both entry points share the settled set
+        vm.prank(buyer);
+        vm.expectRevert("order already settled");
+        router.solveAnchored(publicValues, PROOF, signature);
+    }
+
+    function testSolveAnchoredRejectsAnchorOutsideBlockhashWindow() public {
+        vm.roll(10_000);
+        router.setVerifyBlock(true);
+        (
+            ObsidianOrder memory order,
+            bytes memory publicValues
+        ) = _anchored(9_000, 9_990);
+        vm.setBlockhash(9_990, keccak256(abi.encode(uint256(9_990))));
+        // the anchor is 257 blocks back, blockhash no longer returns it
+        vm.roll(9_990 + 257);
+
+        vm.prank(buyer);
+        vm.expectRevert("block is too old");
+        router.solveAnchored(publicValues, PROOF, _sign(order));
+    }
+
+    function testSolveAnchoredRejectsOrderTooFarBehindAnchor() public {
+        vm.roll(20_000);
+        (
+            ObsidianOrder memory order,
+            bytes memory publicValues
+        ) = _anchored(1_000, 1_000 + router.maxAnchoredAge() + 1);
+
+        vm.prank(buyer);
+        vm.expectRevert("order block too far behind anchor");
+        router.solveAnchored(publicValues, PROOF, _sign(order));
+    }
+}
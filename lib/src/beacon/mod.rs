@@ -0,0 +1,447 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::chain_spec::{ChainSpec, Fork};
+use crate::header::LeanHeader;
+
+/// seconds between beacon slots on ethereum networks
+pub const SECONDS_PER_SLOT: u64 = 12;
+
+/// `state_root` in a `BeaconBlockHeader`, whose root is the beacon block root
+pub const BLOCK_STATE_ROOT_GINDEX: u64 = 11;
+/// `body_root` in a `BeaconBlockHeader`
+pub const BLOCK_BODY_ROOT_GINDEX: u64 = 12;
+/// `execution_payload` in a `BeaconBlockBody` (bellatrix onward)
+pub const BODY_EXECUTION_PAYLOAD_GINDEX: u64 = 25;
+/// `block_hash` in an `ExecutionPayload` or `ExecutionPayloadHeader`
+pub const PAYLOAD_BLOCK_HASH_FIELD: u64 = 12;
+
+pub const STATE_VALIDATORS_FIELD: u64 = 11;
+pub const STATE_BALANCES_FIELD: u64 = 12;
+pub const STATE_LATEST_EXECUTION_PAYLOAD_HEADER_FIELD: u64 = 24;
+/// `finalized_checkpoint`, its root is the second field of the checkpoint
+pub const STATE_FINALIZED_CHECKPOINT_FIELD: u64 = 20;
+pub const STATE_CURRENT_SYNC_COMMITTEE_FIELD: u64 = 22;
+pub const STATE_NEXT_SYNC_COMMITTEE_FIELD: u64 = 23;
+
+/// `effective_balance` in a `Validator`
+const VALIDATOR_EFFECTIVE_BALANCE_GINDEX: u64 = 10;
+/// validator and balance lists are limited to 2^40 entries
+const VALIDATOR_REGISTRY_DEPTH: u32 = 40;
+/// four balances share a chunk
+const BALANCES_DEPTH: u32 = VALIDATOR_REGISTRY_DEPTH - 2;
+
+/// Beacon state layout, the number of fields sets the depth of the state tree.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum BeaconFork {
+    /// 24 state fields, no execution payload
+    Altair,
+    /// 25 state fields, payloads without withdrawals
+    Bellatrix,
+    /// 28 state fields, payloads without blob gas
+    Capella,
+    /// 28 state fields
+    Deneb,
+    /// 37 state fields, 38 from fulu
+    Electra,
+}
+
+impl BeaconFork {
+    /// Fork of the beacon block behind the `parent_beacon_block_root` of a header at `timestamp`.
+    ///
+    /// The parent beacon block is taken one slot before the header, so the first block of a fork
+    /// proves against a block of the fork before. After missed slots right across a fork the
+    /// parent is older still and this gives the newer layout. OP-stack headers carry the beacon
+    /// root of L1 and take the L1 spec.
+    pub fn at(spec: &ChainSpec, timestamp: u64) -> BeaconFork {
+        let parent = timestamp.saturating_sub(SECONDS_PER_SLOT);
+        if spec.is_active(Fork::Prague, 0, parent) {
+            BeaconFork::Electra
+        } else if spec.is_active(Fork::Cancun, 0, parent) {
+            BeaconFork::Deneb
+        } else if spec.is_active(Fork::Shanghai, 0, parent) {
+            BeaconFork::Capella
+        } else {
+            BeaconFork::Bellatrix
+        }
+    }
+
+    fn state_depth(&self) -> u32 {
+        match self {
+            BeaconFork::Electra => 6,
+            _ => 5,
+        }
+    }
+
+    /// Generalized index of a beacon state field, from the state root.
+    pub fn state_gindex(&self, field: u64) -> u64 {
+        (1 << self.state_depth()) + field
+    }
+
+    /// Generalized index of a beacon state field, from the beacon block root.
+    pub fn state_field_gindex(&self, field: u64) -> u64 {
+        BeaconVerifier::concat_gindices(&[BLOCK_STATE_ROOT_GINDEX, self.state_gindex(field)])
+    }
+
+    /// Generalized index of an execution payload field, from the payload root.
+    pub fn payload_gindex(&self, field: u64) -> BeaconResult<u64> {
+        let fields: u64 = match self {
+            BeaconFork::Altair => return Err(BeaconError::PayloadLayout { fork: *self }),
+            BeaconFork::Bellatrix => 14,
+            BeaconFork::Capella => 15,
+            BeaconFork::Deneb | BeaconFork::Electra => 17,
+        };
+        Ok(fields.next_power_of_two() + field)
+    }
+}
+
+/// A leaf and its sibling hashes from the bottom up, at a generalized index under a root.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SszProof {
+    pub leaf: Vec<u8>,
+    pub branch: Vec<Vec<u8>>,
+    pub gindex: u64,
+}
+
+/// `ExecutionPayloadHeader` as served by the beacon API, capella onward.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ExecutionPayloadHeader {
+    pub parent_hash: [u8; 32],
+    pub fee_recipient: [u8; 20],
+    pub state_root: [u8; 32],
+    pub receipts_root: [u8; 32],
+    pub logs_bloom: Vec<u8>,
+    pub prev_randao: [u8; 32],
+    pub block_number: u64,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+    pub timestamp: u64,
+    pub extra_data: Vec<u8>,
+    /// big endian, as in the execution header
+    pub base_fee_per_gas: [u8; 32],
+    pub block_hash: [u8; 32],
+    pub transactions_root: [u8; 32],
+    pub withdrawals_root: [u8; 32],
+    /// deneb onward
+    pub blob_gas_used: Option<u64>,
+    pub excess_blob_gas: Option<u64>,
+}
+
+impl ExecutionPayloadHeader {
+    /// Root of the header in the layout of `fork`, which is the root of the payload it summarizes.
+    pub fn hash_tree_root(&self, fork: BeaconFork) -> BeaconResult<[u8; 32]> {
+        Ok(BeaconVerifier::merkleize(self.leaves(fork)?))
+    }
+
+    /// Field roots in the layout of `fork`, the payload root merkleizes them.
+    pub fn leaves(&self, fork: BeaconFork) -> BeaconResult<Vec<[u8; 32]>> {
+        let blob_gas = match (fork, self.blob_gas_used, self.excess_blob_gas) {
+            (BeaconFork::Capella, None, None) => None,
+            (BeaconFork::Deneb | BeaconFork::Electra, Some(used), Some(excess)) => {
+                Some((used, excess))
+            }
+            _ => return Err(BeaconError::PayloadLayout { fork }),
+        };
+        if self.logs_bloom.len() != 256 || self.extra_data.len() > 32 {
+            return Err(BeaconError::PayloadLayout { fork });
+        }
+
+        let mut fee_recipient = [0u8; 32];
+        fee_recipient[..20].copy_from_slice(&self.fee_recipient);
+        let bloom = BeaconVerifier::merkleize(
+            self.logs_bloom
+                .chunks(32)
+                .map(|chunk| chunk.try_into().unwrap())
+                .collect(),
+        );
+        // byte list limited to one chunk, mixed in with its length
+        let mut extra_data = [0u8; 32];
+        extra_data[..self.extra_data.len()].copy_from_slice(&self.extra_data);
+        let extra_data =
+            BeaconVerifier::sha256_pair(&extra_data, &uint_chunk(self.extra_data.len() as u64));
+        let mut base_fee_per_gas = self.base_fee_per_gas;
+        base_fee_per_gas.reverse();
+
+        let mut leaves = vec![
+            self.parent_hash,
+            fee_recipient,
+            self.state_root,
+            self.receipts_root,
+            bloom,
+            self.prev_randao,
+            uint_chunk(self.block_number),
+            uint_chunk(self.gas_limit),
+            uint_chunk(self.gas_used),
+            uint_chunk(self.timestamp),
+            extra_data,
+            base_fee_per_gas,
+            self.block_hash,
+            self.transactions_root,
+            self.withdrawals_root,
+        ];
+        if let Some((used, excess)) = blob_gas {
+            leaves.push(uint_chunk(used));
+            leaves.push(uint_chunk(excess));
+        }
+        Ok(leaves)
+    }
+}
+
+pub(crate) fn uint_chunk(value: u64) -> [u8; 32] {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&value.to_le_bytes());
+    chunk
+}
+
+/// Fields proven against the `parent_beacon_block_root` of an execution header.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BeaconInputs {
+    pub header: LeanHeader,
+    pub validator_index: u64,
+    /// chunk of the balances list holding the validator's balance
+    pub balance_proof: Vec<Vec<u8>>,
+    pub balance_chunk: Vec<u8>,
+    /// effective balance leaf of the validator
+    pub effective_balance_proof: Vec<Vec<u8>>,
+    pub effective_balance_leaf: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct BeaconOutput {
+    pub block_hash: Vec<u8>,
+    pub beacon_block_root: Vec<u8>,
+    pub validator_index: u64,
+    /// gwei
+    pub balance: u64,
+    /// gwei
+    pub effective_balance: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BeaconError {
+    /// the header predates dencun and commits to no beacon block
+    MissingBeaconRoot,
+    /// generalized indices start at 1 for the root
+    InvalidGindex { gindex: u64 },
+    /// the branch length does not match the depth of the generalized index
+    BranchLength { expected: usize, found: usize },
+    /// leaf or sibling that is not a 32 byte chunk
+    InvalidChunk { length: usize },
+    /// the branch does not hash up to the root
+    RootMismatch { gindex: u64 },
+    /// payload header fields that do not fit the fork's layout, or a fork without payloads
+    PayloadLayout { fork: BeaconFork },
+}
+
+impl fmt::Display for BeaconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeaconError::MissingBeaconRoot => write!(f, "header has no parent beacon block root"),
+            BeaconError::InvalidGindex { gindex } => {
+                write!(f, "invalid generalized index {}", gindex)
+            }
+            BeaconError::BranchLength { expected, found } => {
+                write!(f, "branch of {} nodes, expected {}", found, expected)
+            }
+            BeaconError::InvalidChunk { length } => write!(f, "chunk of {} bytes", length),
+            BeaconError::RootMismatch { gindex } => {
+                write!(
+                    f,
+                    "proof for generalized index {} does not match the root",
+                    gindex
+                )
+            }
+            BeaconError::PayloadLayout { fork } => {
+                write!(f, "payload header does not match the {:?} layout", fork)
+            }
+        }
+    }
+}
+
+pub type BeaconResult<T> = Result<T, BeaconError>;
+
+pub struct BeaconVerifier;
+
+impl BeaconVerifier {
+    pub(crate) fn sha256_pair(left: &[u8], right: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+
+    /// Root of the chunks padded with zero chunks to the next power of two.
+    pub(crate) fn merkleize(mut chunks: Vec<[u8; 32]>) -> [u8; 32] {
+        let width = chunks.len().next_power_of_two();
+        chunks.resize(width, [0u8; 32]);
+        while chunks.len() > 1 {
+            chunks = chunks
+                .chunks(2)
+                .map(|pair| Self::sha256_pair(&pair[0], &pair[1]))
+                .collect();
+        }
+        chunks[0]
+    }
+
+    fn depth(gindex: u64) -> u32 {
+        63 - gindex.leading_zeros()
+    }
+
+    /// Generalized index of a path through nested trees, each index relative to the one before.
+    pub fn concat_gindices(gindices: &[u64]) -> u64 {
+        gindices.iter().fold(1, |acc, &gindex| {
+            let depth = Self::depth(gindex);
+            (acc << depth) | (gindex ^ (1 << depth))
+        })
+    }
+
+    /// Checks that `leaf` sits at `gindex` under `root`.
+    pub fn verify_branch(
+        root: &[u8],
+        leaf: &[u8],
+        branch: &[Vec<u8>],
+        gindex: u64,
+    ) -> BeaconResult<()> {
+        if gindex == 0 {
+            return Err(BeaconError::InvalidGindex { gindex });
+        }
+        let depth = Self::depth(gindex) as usize;
+        if branch.len() != depth {
+            return Err(BeaconError::BranchLength {
+                expected: depth,
+                found: branch.len(),
+            });
+        }
+        if leaf.len() != 32 {
+            return Err(BeaconError::InvalidChunk { length: leaf.len() });
+        }
+
+        let mut node: [u8; 32] = leaf.try_into().unwrap();
+        for (level, sibling) in branch.iter().enumerate() {
+            if sibling.len() != 32 {
+                return Err(BeaconError::InvalidChunk {
+                    length: sibling.len(),
+                });
+            }
+            // the bit of the index at this level tells which side the node is on
+            node = if (gindex >> level) & 1 == 1 {
+                Self::sha256_pair(sibling, &node)
+            } else {
+                Self::sha256_pair(&node, sibling)
+            };
+        }
+
+        if node != root {
+            return Err(BeaconError::RootMismatch { gindex });
+        }
+        Ok(())
+    }
+
+    pub fn verify_proof(root: &[u8], proof: &SszProof) -> BeaconResult<()> {
+        Self::verify_branch(root, &proof.leaf, &proof.branch, proof.gindex)
+    }
+
+    fn read_u64_le(bytes: &[u8]) -> u64 {
+        let mut out = [0u8; 8];
+        out.copy_from_slice(&bytes[..8]);
+        u64::from_le_bytes(out)
+    }
+
+    /// Generalized index of the balances chunk holding `validator_index`, from the block root.
+    pub fn balance_gindex(fork: BeaconFork, validator_index: u64) -> u64 {
+        // the list root mixes in the length, the data root is its left child
+        Self::concat_gindices(&[
+            fork.state_field_gindex(STATE_BALANCES_FIELD),
+            2,
+            (1 << BALANCES_DEPTH) + validator_index / 4,
+        ])
+    }
+
+    /// Generalized index of a validator's effective balance, from the block root.
+    pub fn effective_balance_gindex(fork: BeaconFork, validator_index: u64) -> u64 {
+        Self::concat_gindices(&[
+            fork.state_field_gindex(STATE_VALIDATORS_FIELD),
+            2,
+            (1 << VALIDATOR_REGISTRY_DEPTH) + validator_index,
+            VALIDATOR_EFFECTIVE_BALANCE_GINDEX,
+        ])
+    }
+
+    /// Proves a validator's balance in gwei from the chunk of four balances holding it.
+    pub fn verify_balance(
+        block_root: &[u8],
+        fork: BeaconFork,
+        validator_index: u64,
+        chunk: &[u8],
+        branch: &[Vec<u8>],
+    ) -> BeaconResult<u64> {
+        let gindex = Self::balance_gindex(fork, validator_index);
+        Self::verify_branch(block_root, chunk, branch, gindex)?;
+        let offset = (validator_index % 4) as usize * 8;
+        Ok(Self::read_u64_le(&chunk[offset..]))
+    }
+
+    pub fn verify_effective_balance(
+        block_root: &[u8],
+        fork: BeaconFork,
+        validator_index: u64,
+        leaf: &[u8],
+        branch: &[Vec<u8>],
+    ) -> BeaconResult<u64> {
+        let gindex = Self::effective_balance_gindex(fork, validator_index);
+        Self::verify_branch(block_root, leaf, branch, gindex)?;
+        Ok(Self::read_u64_le(leaf))
+    }
+
+    /// Proves the execution block hash carried in the payload of a beacon block of `fork`.
+    ///
+    /// Under an execution header's `parent_beacon_block_root` this is the previous block.
+    pub fn verify_payload_block_hash(
+        block_root: &[u8],
+        fork: BeaconFork,
+        block_hash: &[u8],
+        branch: &[Vec<u8>],
+    ) -> BeaconResult<()> {
+        let gindex = Self::concat_gindices(&[
+            BLOCK_BODY_ROOT_GINDEX,
+            BODY_EXECUTION_PAYLOAD_GINDEX,
+            fork.payload_gindex(PAYLOAD_BLOCK_HASH_FIELD)?,
+        ]);
+        Self::verify_branch(block_root, block_hash, branch, gindex)
+    }
+
+    /// Proves a validator's balance and effective balance at the header's parent beacon block,
+    /// laid out by the fork `spec` has active at the header.
+    pub fn verify_validator(spec: &ChainSpec, input: BeaconInputs) -> BeaconResult<BeaconOutput> {
+        let block_root = input
+            .header
+            .parent_beacon_block_root
+            .ok_or(BeaconError::MissingBeaconRoot)?;
+        let fork = BeaconFork::at(spec, input.header.timestamp);
+
+        let balance = Self::verify_balance(
+            &block_root,
+            fork,
+            input.validator_index,
+            &input.balance_chunk,
+            &input.balance_proof,
+        )?;
+        let effective_balance = Self::verify_effective_balance(
+            &block_root,
+            fork,
+            input.validator_index,
+            &input.effective_balance_leaf,
+            &input.effective_balance_proof,
+        )?;
+
+        Ok(BeaconOutput {
+            block_hash: input.header.hash().to_vec(),
+            beacon_block_root: block_root.to_vec(),
+            validator_index: input.validator_index,
+            balance,
+            effective_balance,
+        })
+    }
+}
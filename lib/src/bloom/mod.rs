@@ -0,0 +1,161 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::header::{HeaderError, HeaderVerifier, LeanHeader};
+use crate::verifier::MPTVerifier;
+
+/// 2048 bit logs bloom
+pub const BLOOM_BYTES: usize = 256;
+
+/// Logs from `address` carrying every one of `topics`, at any position.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LogQuery {
+    pub address: Vec<u8>,
+    pub topics: Vec<Vec<u8>>,
+}
+
+/// A header run, ordered oldest to newest, in which no block can hold a log matching the query.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AbsenceInputs {
+    pub headers: Vec<LeanHeader>,
+    pub query: LogQuery,
+}
+
+#[derive(Debug)]
+pub struct AbsenceOutput {
+    pub first_hash: Vec<u8>,
+    pub first_number: u64,
+    pub last_hash: Vec<u8>,
+    pub last_number: u64,
+    pub query: LogQuery,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BloomError {
+    Header(HeaderError),
+    InvalidBloom {
+        length: usize,
+    },
+    InvalidAddress {
+        length: usize,
+    },
+    InvalidTopic {
+        length: usize,
+    },
+    /// the bloom of block `number` has every bit of the query set, so a matching log may exist
+    PossiblyPresent {
+        number: u64,
+    },
+}
+
+impl fmt::Display for BloomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BloomError::Header(e) => write!(f, "header run failed: {}", e),
+            BloomError::InvalidBloom { length } => write!(f, "logs bloom of {} bytes", length),
+            BloomError::InvalidAddress { length } => write!(f, "address of {} bytes", length),
+            BloomError::InvalidTopic { length } => write!(f, "topic of {} bytes", length),
+            BloomError::PossiblyPresent { number } => {
+                write!(f, "bloom of block {} may contain the log", number)
+            }
+        }
+    }
+}
+
+impl From<HeaderError> for BloomError {
+    fn from(e: HeaderError) -> Self {
+        BloomError::Header(e)
+    }
+}
+
+pub type BloomResult<T> = Result<T, BloomError>;
+
+pub struct BloomVerifier;
+
+impl BloomVerifier {
+    /// Byte index and mask of the three bits `item` sets: the low 11 bits of each of the
+    /// first three byte pairs of its keccak (m3:2048).
+    pub fn bits(item: &[u8]) -> [(usize, u8); 3] {
+        let hash = MPTVerifier::keccak(item);
+        let mut bits = [(0, 0); 3];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            let index = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & 2047;
+            // bit 0 is the lowest bit of the last byte
+            *bit = (BLOOM_BYTES - 1 - index / 8, 1 << (index % 8));
+        }
+        bits
+    }
+
+    /// Whether all three bits of `item` are set, a miss proves no log added it.
+    pub fn contains(bloom: &[u8], item: &[u8]) -> BloomResult<bool> {
+        if bloom.len() != BLOOM_BYTES {
+            return Err(BloomError::InvalidBloom {
+                length: bloom.len(),
+            });
+        }
+        Ok(Self::bits(item)
+            .iter()
+            .all(|&(byte, mask)| bloom[byte] & mask != 0))
+    }
+
+    fn check_query(query: &LogQuery) -> BloomResult<()> {
+        if query.address.len() != 20 {
+            return Err(BloomError::InvalidAddress {
+                length: query.address.len(),
+            });
+        }
+        if let Some(topic) = query.topics.iter().find(|topic| topic.len() != 32) {
+            return Err(BloomError::InvalidTopic {
+                length: topic.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the bloom leaves room for a log matching the query. A matching log sets the
+    /// bits of its address and of each of its topics.
+    pub fn may_contain(bloom: &[u8], query: &LogQuery) -> BloomResult<bool> {
+        Self::check_query(query)?;
+        if !Self::contains(bloom, &query.address)? {
+            return Ok(false);
+        }
+        for topic in &query.topics {
+            if !Self::contains(bloom, topic)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Links the header run through parent hashes and checks no bloom in it admits the log.
+    pub fn verify_absence(input: AbsenceInputs) -> BloomResult<AbsenceOutput> {
+        // hashing re-encodes the bloom as a fixed 256 bytes, so sizes are checked first
+        if let Some(header) = input
+            .headers
+            .iter()
+            .find(|header| header.logs_bloom.len() != BLOOM_BYTES)
+        {
+            return Err(BloomError::InvalidBloom {
+                length: header.logs_bloom.len(),
+            });
+        }
+        let ancestry = HeaderVerifier::verify_ancestry(&input.headers)?;
+
+        for header in &input.headers {
+            if Self::may_contain(&header.logs_bloom, &input.query)? {
+                return Err(BloomError::PossiblyPresent {
+                    number: header.number,
+                });
+            }
+        }
+
+        Ok(AbsenceOutput {
+            first_hash: ancestry.target_hash.to_vec(),
+            first_number: ancestry.target_number,
+            last_hash: ancestry.anchor_hash.to_vec(),
+            last_number: ancestry.anchor_number,
+            query: input.query,
+        })
+    }
+}
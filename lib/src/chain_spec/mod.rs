@@ -0,0 +1,260 @@
+use std::fmt;
+
+/// Forks that add fields to the block header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fork {
+    /// `base_fee_per_gas`
+    London,
+    /// `withdrawals_root`, canyon on OP-stack chains
+    Shanghai,
+    /// `blob_gas_used`, `excess_blob_gas` and `parent_beacon_block_root`, ecotone on OP-stack chains
+    Cancun,
+    /// `requests_hash`, isthmus on OP-stack chains
+    Prague,
+}
+
+impl fmt::Display for Fork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fork::London => write!(f, "london"),
+            Fork::Shanghai => write!(f, "shanghai"),
+            Fork::Cancun => write!(f, "cancun"),
+            Fork::Prague => write!(f, "prague"),
+        }
+    }
+}
+
+/// gas per blob under EIP-4844
+pub const GAS_PER_BLOB: u64 = 131_072;
+/// EIP-7918, execution gas a blob is priced at least at
+pub const BLOB_BASE_COST: u64 = 8192;
+/// base fee of the london fork block
+pub const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+/// the gas limit may move by less than 1/1024 of the parent's each block
+pub const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+pub const MIN_GAS_LIMIT: u64 = 5000;
+
+/// EIP-1559 parameters, the gas target is the gas limit over the elasticity multiplier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseFeeParams {
+    pub max_change_denominator: u64,
+    pub elasticity_multiplier: u64,
+}
+
+pub const ETHEREUM_BASE_FEE: BaseFeeParams = BaseFeeParams {
+    max_change_denominator: 8,
+    elasticity_multiplier: 2,
+};
+
+/// Blob gas target and limit per block, and how fast the blob base fee moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobParams {
+    pub target_blob_gas: u64,
+    pub max_blob_gas: u64,
+    pub base_fee_update_fraction: u64,
+    /// EIP-7918 from osaka, the blob base fee follows the execution base fee from below
+    pub reserve_price: bool,
+}
+
+/// Blob parameters from a fork's activation timestamp on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobSchedule {
+    pub timestamp: u64,
+    pub params: BlobParams,
+}
+
+/// three blobs targeted, six at most
+pub const CANCUN_BLOBS: BlobParams = BlobParams {
+    target_blob_gas: 3 * GAS_PER_BLOB,
+    max_blob_gas: 6 * GAS_PER_BLOB,
+    base_fee_update_fraction: 3_338_477,
+    reserve_price: false,
+};
+
+/// EIP-7691, six blobs targeted, nine at most
+pub const PRAGUE_BLOBS: BlobParams = BlobParams {
+    target_blob_gas: 6 * GAS_PER_BLOB,
+    max_blob_gas: 9 * GAS_PER_BLOB,
+    base_fee_update_fraction: 5_007_716,
+    reserve_price: false,
+};
+
+/// prague's blob counts under the EIP-7918 reserve price
+pub const OSAKA_BLOBS: BlobParams = BlobParams {
+    reserve_price: true,
+    ..PRAGUE_BLOBS
+};
+
+/// first blob parameter only fork, ten blobs targeted, fifteen at most
+pub const BPO1_BLOBS: BlobParams = BlobParams {
+    target_blob_gas: 10 * GAS_PER_BLOB,
+    max_blob_gas: 15 * GAS_PER_BLOB,
+    base_fee_update_fraction: 8_346_193,
+    reserve_price: true,
+};
+
+/// second blob parameter only fork, fourteen blobs targeted, twenty one at most
+pub const BPO2_BLOBS: BlobParams = BlobParams {
+    target_blob_gas: 14 * GAS_PER_BLOB,
+    max_blob_gas: 21 * GAS_PER_BLOB,
+    base_fee_update_fraction: 11_684_671,
+    reserve_price: true,
+};
+
+/// Base fee rules of OP-stack chains, which leave the gas limit to the system config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpStackParams {
+    /// from canyon, which activates with shanghai
+    pub canyon_base_fee: BaseFeeParams,
+    /// from holocene the parent's extra data may carry the parameters
+    pub holocene_timestamp: u64,
+    /// from jovian the parent's extra data also carries a minimum base fee, and blob gas used
+    /// holds the block's data availability footprint
+    pub jovian_timestamp: u64,
+}
+
+/// Fork activations of a chain, london by block and the later forks by timestamp.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChainSpec {
+    pub name: &'static str,
+    pub chain_id: u64,
+    pub london_block: u64,
+    pub shanghai_timestamp: u64,
+    pub cancun_timestamp: u64,
+    pub prague_timestamp: u64,
+    pub base_fee: BaseFeeParams,
+    /// ordered by timestamp from cancun on, empty on chains without blobs
+    pub blob_schedule: &'static [BlobSchedule],
+    pub op_stack: Option<OpStackParams>,
+}
+
+const OP_BASE_FEE: BaseFeeParams = BaseFeeParams {
+    max_change_denominator: 50,
+    elasticity_multiplier: 6,
+};
+
+const OP_STACK: OpStackParams = OpStackParams {
+    canyon_base_fee: BaseFeeParams {
+        max_change_denominator: 250,
+        elasticity_multiplier: 6,
+    },
+    holocene_timestamp: 1_736_445_601,
+    jovian_timestamp: 1_764_691_201,
+};
+
+const fn blobs(timestamp: u64, params: BlobParams) -> BlobSchedule {
+    BlobSchedule { timestamp, params }
+}
+
+pub const MAINNET: ChainSpec = ChainSpec {
+    name: "mainnet",
+    chain_id: 1,
+    london_block: 12_965_000,
+    shanghai_timestamp: 1_681_338_455,
+    cancun_timestamp: 1_710_338_135,
+    prague_timestamp: 1_746_612_311,
+    base_fee: ETHEREUM_BASE_FEE,
+    blob_schedule: &[
+        blobs(1_710_338_135, CANCUN_BLOBS),
+        blobs(1_746_612_311, PRAGUE_BLOBS),
+        blobs(1_764_798_551, OSAKA_BLOBS),
+        blobs(1_765_290_071, BPO1_BLOBS),
+        blobs(1_767_747_671, BPO2_BLOBS),
+    ],
+    op_stack: None,
+};
+
+pub const SEPOLIA: ChainSpec = ChainSpec {
+    name: "sepolia",
+    chain_id: 11_155_111,
+    london_block: 0,
+    shanghai_timestamp: 1_677_557_088,
+    cancun_timestamp: 1_706_655_072,
+    prague_timestamp: 1_741_159_776,
+    base_fee: ETHEREUM_BASE_FEE,
+    blob_schedule: &[
+        blobs(1_706_655_072, CANCUN_BLOBS),
+        blobs(1_741_159_776, PRAGUE_BLOBS),
+        blobs(1_760_427_360, OSAKA_BLOBS),
+        blobs(1_761_017_184, BPO1_BLOBS),
+        blobs(1_761_607_008, BPO2_BLOBS),
+    ],
+    op_stack: None,
+};
+
+pub const HOLESKY: ChainSpec = ChainSpec {
+    name: "holesky",
+    chain_id: 17_000,
+    london_block: 0,
+    shanghai_timestamp: 1_696_000_704,
+    cancun_timestamp: 1_707_305_664,
+    prague_timestamp: 1_740_434_112,
+    base_fee: ETHEREUM_BASE_FEE,
+    blob_schedule: &[
+        blobs(1_707_305_664, CANCUN_BLOBS),
+        blobs(1_740_434_112, PRAGUE_BLOBS),
+        blobs(1_759_308_480, OSAKA_BLOBS),
+        blobs(1_759_800_000, BPO1_BLOBS),
+        blobs(1_760_389_824, BPO2_BLOBS),
+    ],
+    op_stack: None,
+};
+
+pub const BASE: ChainSpec = ChainSpec {
+    name: "base",
+    chain_id: 8_453,
+    london_block: 0,
+    shanghai_timestamp: 1_704_992_401,
+    cancun_timestamp: 1_710_374_401,
+    prague_timestamp: 1_746_806_401,
+    base_fee: OP_BASE_FEE,
+    blob_schedule: &[],
+    op_stack: Some(OP_STACK),
+};
+
+/// london arrived with bedrock, legacy blocks before it carry no base fee
+pub const OP_MAINNET: ChainSpec = ChainSpec {
+    name: "op-mainnet",
+    chain_id: 10,
+    london_block: 105_235_063,
+    shanghai_timestamp: 1_704_992_401,
+    cancun_timestamp: 1_710_374_401,
+    prague_timestamp: 1_746_806_401,
+    base_fee: OP_BASE_FEE,
+    blob_schedule: &[],
+    op_stack: Some(OP_STACK),
+};
+
+pub static CHAIN_SPECS: [ChainSpec; 5] = [MAINNET, SEPOLIA, HOLESKY, BASE, OP_MAINNET];
+
+impl ChainSpec {
+    pub fn from_chain_id(chain_id: u64) -> Option<&'static ChainSpec> {
+        CHAIN_SPECS.iter().find(|spec| spec.chain_id == chain_id)
+    }
+
+    /// Base fee parameters of a block at `timestamp`, before any holocene override.
+    pub fn base_fee_params(&self, timestamp: u64) -> BaseFeeParams {
+        match self.op_stack {
+            Some(op) if timestamp >= self.shanghai_timestamp => op.canyon_base_fee,
+            _ => self.base_fee,
+        }
+    }
+
+    /// Blob parameters of a block at `timestamp`, none before cancun or on chains without blobs.
+    pub fn blob_params(&self, timestamp: u64) -> Option<BlobParams> {
+        self.blob_schedule
+            .iter()
+            .rev()
+            .find(|entry| entry.timestamp <= timestamp)
+            .map(|entry| entry.params)
+    }
+
+    pub fn is_active(&self, fork: Fork, number: u64, timestamp: u64) -> bool {
+        match fork {
+            Fork::London => number >= self.london_block,
+            Fork::Shanghai => timestamp >= self.shanghai_timestamp,
+            Fork::Cancun => timestamp >= self.cancun_timestamp,
+            Fork::Prague => timestamp >= self.prague_timestamp,
+        }
+    }
+}
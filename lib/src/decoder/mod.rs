@@ -1,118 +1,488 @@
-use crate::verifier::{Node, NodeType};
+use std::fmt;
+
+use alloy_primitives::U256;
+
+use crate::verifier::{Node, NodeChild, NodeType};
+
+/// A decoded RLP item, either a byte string or a (possibly nested) list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RlpItem {
+    String(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    pub fn as_bytes(&self) -> Result<&[u8], RlpError> {
+        match self {
+            RlpItem::String(bytes) => Ok(bytes),
+            RlpItem::List(_) => Err(RlpError::UnexpectedList),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[RlpItem], RlpError> {
+        match self {
+            RlpItem::List(items) => Ok(items),
+            RlpItem::String(_) => Err(RlpError::UnexpectedString),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RlpError {
+    /// the input (or a list payload) ended before the item did
+    UnexpectedEnd {
+        offset: usize,
+        needed: usize,
+    },
+    /// bytes left over after the top level item
+    TrailingBytes {
+        consumed: usize,
+        length: usize,
+    },
+    /// long form length starting with a zero byte
+    LeadingZeroLength {
+        offset: usize,
+    },
+    /// long form length used for a payload shorter than 56 bytes
+    NonCanonicalLength {
+        offset: usize,
+    },
+    /// single byte below 0x80 wrapped as a one byte string
+    NonCanonicalSingleByte {
+        offset: usize,
+    },
+    /// length prefix does not fit in a usize
+    LengthOverflow {
+        offset: usize,
+    },
+    /// list items do not add up to the list payload length
+    ListLengthMismatch {
+        offset: usize,
+    },
+    /// lists nested deeper than [`MAX_DEPTH`]
+    DepthExceeded {
+        offset: usize,
+    },
+    UnexpectedList,
+    UnexpectedString,
+    /// item count is neither a branch (17) nor a leaf/extension (2)
+    InvalidNode {
+        items: usize,
+    },
+    /// leaf or extension node with an empty path
+    EmptyNodePath,
+    /// leaf or extension path with a flag nibble above 3
+    InvalidPathPrefix {
+        prefix: u8,
+    },
+    /// child reference that is neither empty, a 32 byte hash nor a short embedded node
+    InvalidChild {
+        length: usize,
+    },
+    /// integer with leading zero bytes
+    LeadingZeroInteger,
+    /// integer wider than the type it is read into
+    IntegerOverflow {
+        length: usize,
+    },
+}
+
+impl fmt::Display for RlpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RlpError::UnexpectedEnd { offset, needed } => write!(
+                f,
+                "unexpected end of input at {} needed {} more bytes",
+                offset, needed
+            ),
+            RlpError::TrailingBytes { consumed, length } => write!(
+                f,
+                "could not decode full input expected {} found {}",
+                consumed, length
+            ),
+            RlpError::LeadingZeroLength { offset } => {
+                write!(f, "length with leading zero at {}", offset)
+            }
+            RlpError::NonCanonicalLength { offset } => {
+                write!(f, "long form length for short payload at {}", offset)
+            }
+            RlpError::NonCanonicalSingleByte { offset } => {
+                write!(f, "single byte encoded as string at {}", offset)
+            }
+            RlpError::LengthOverflow { offset } => write!(f, "length overflow at {}", offset),
+            RlpError::ListLengthMismatch { offset } => {
+                write!(f, "list payload length mismatch at {}", offset)
+            }
+            RlpError::DepthExceeded { offset } => {
+                write!(f, "list nested deeper than {} at {}", MAX_DEPTH, offset)
+            }
+            RlpError::UnexpectedList => write!(f, "expected string found list"),
+            RlpError::UnexpectedString => write!(f, "expected list found string"),
+            RlpError::InvalidNode { items } => write!(f, "invalid mpt node with {} items", items),
+            RlpError::EmptyNodePath => write!(f, "empty path in leaf or extension node"),
+            RlpError::InvalidPathPrefix { prefix } => {
+                write!(f, "invalid path prefix {:#x}", prefix)
+            }
+            RlpError::InvalidChild { length } => {
+                write!(f, "invalid child reference of length {}", length)
+            }
+            RlpError::LeadingZeroInteger => write!(f, "integer with leading zero"),
+            RlpError::IntegerOverflow { length } => {
+                write!(f, "integer of {} bytes overflows", length)
+            }
+        }
+    }
+}
+
+/// Deepest list nesting [`NodeDecoder::decode_inner`] follows, far above anything a trie node,
+/// header, receipt or transaction uses.
+pub const MAX_DEPTH: usize = 64;
+
+/// Prefix of an RLP item: whether it is a list, where its payload starts and how long it is.
+#[derive(Clone, Copy, Debug)]
+pub struct RlpHeader {
+    pub list: bool,
+    pub payload_offset: usize,
+    pub payload_length: usize,
+}
+
+impl RlpHeader {
+    /// offset right after the item
+    pub fn end(&self) -> usize {
+        self.payload_offset + self.payload_length
+    }
+}
+
 pub struct NodeDecoder;
 
 impl NodeDecoder {
-    pub fn decode_inner(input: &[u8], input_offset: usize) -> (Vec<u8>, usize) {
-        let mut offset = input_offset;
-        let mut out: Vec<u8> = Vec::new();
-
-        // always assume the encoded value will be a string
-        // hence would be between 0x80 and 0xbf
-        let item_type = input[offset];
-        if item_type < 0x80 {
-            out = vec![item_type];
-            offset += 1;
-        } else if item_type <= 0xb7 {
-            // read length encoded in 1 byte
-            let actual_length = (item_type - 0x80) as usize;
-            // read length
-            offset += 1;
-            out = input[offset..offset + actual_length].to_vec();
-            // shift the len
-            offset += actual_length;
-        } else if item_type <= 0xbf {
-            let length_bytes = (item_type - 0xb7) as usize;
-            offset += 1;
-            // read in the first value and set it in a usize
-            let mut actual_length = (input[offset]) as usize;
-
-            // read the whole length if needed now
-            if length_bytes > 1 {
-                for i in 1..=length_bytes - 1 {
-                    actual_length = (actual_length << 8) | (input[offset + i]) as usize;
-                }
-            }
+    fn read_length(input: &[u8], offset: usize, length_bytes: usize) -> Result<usize, RlpError> {
+        if length_bytes > std::mem::size_of::<usize>() {
+            return Err(RlpError::LengthOverflow { offset });
+        }
+        let bytes = Self::take(input, offset, length_bytes)?;
+        if bytes[0] == 0 {
+            return Err(RlpError::LeadingZeroLength { offset });
+        }
 
-            // shift the read length
-            offset += length_bytes;
+        let mut length = 0usize;
+        for &byte in bytes {
+            length = (length << 8) | byte as usize;
+        }
 
-            out = input[offset..offset + actual_length].to_vec();
+        // anything shorter must use the short form
+        if length < 56 {
+            return Err(RlpError::NonCanonicalLength { offset });
+        }
+        Ok(length)
+    }
 
-            offset += actual_length;
-        } else {
-            // do not handle anything else
-            panic!("too much work, use a lib at this point bro");
+    fn take(input: &[u8], offset: usize, length: usize) -> Result<&[u8], RlpError> {
+        let end = offset
+            .checked_add(length)
+            .ok_or(RlpError::LengthOverflow { offset })?;
+        if end > input.len() {
+            return Err(RlpError::UnexpectedEnd {
+                offset,
+                needed: end - input.len(),
+            });
         }
-        (out, offset)
+        Ok(&input[offset..end])
     }
 
-    pub fn decode_rlp(input: &[u8]) -> Vec<Vec<u8>> {
-        // only handle lists
-        if input[0] < 0xf7 {
-            let (out, offset) = Self::decode_inner(input, 0);
-            if offset < input.len() {
-                panic!(
-                    "could not decode full expected {} found {}",
-                    offset,
-                    input.len()
-                )
+    /// Reads the prefix of the item at `offset` and checks its payload fits in the input.
+    pub fn decode_header(input: &[u8], offset: usize) -> Result<RlpHeader, RlpError> {
+        let prefix = *input
+            .get(offset)
+            .ok_or(RlpError::UnexpectedEnd { offset, needed: 1 })?;
+
+        let header = match prefix {
+            // single byte is its own encoding
+            0x00..=0x7f => RlpHeader {
+                list: false,
+                payload_offset: offset,
+                payload_length: 1,
+            },
+            0x80..=0xb7 => {
+                let payload_length = (prefix - 0x80) as usize;
+                if payload_length == 1 && Self::take(input, offset + 1, 1)?[0] < 0x80 {
+                    return Err(RlpError::NonCanonicalSingleByte { offset });
+                }
+                RlpHeader {
+                    list: false,
+                    payload_offset: offset + 1,
+                    payload_length,
+                }
+            }
+            0xb8..=0xbf => {
+                let length_bytes = (prefix - 0xb7) as usize;
+                RlpHeader {
+                    list: false,
+                    payload_offset: offset + 1 + length_bytes,
+                    payload_length: Self::read_length(input, offset + 1, length_bytes)?,
+                }
+            }
+            0xc0..=0xf7 => RlpHeader {
+                list: true,
+                payload_offset: offset + 1,
+                payload_length: (prefix - 0xc0) as usize,
+            },
+            0xf8..=0xff => {
+                let length_bytes = (prefix - 0xf7) as usize;
+                RlpHeader {
+                    list: true,
+                    payload_offset: offset + 1 + length_bytes,
+                    payload_length: Self::read_length(input, offset + 1, length_bytes)?,
+                }
             }
-            return vec![out];
+        };
+
+        Self::take(input, header.payload_offset, header.payload_length)?;
+        Ok(header)
+    }
+
+    /// Decodes the item at `offset`, returning it with the offset right after it.
+    pub fn decode_inner(input: &[u8], input_offset: usize) -> Result<(RlpItem, usize), RlpError> {
+        Self::decode_nested(input, input_offset, 0)
+    }
+
+    fn decode_nested(
+        input: &[u8],
+        input_offset: usize,
+        depth: usize,
+    ) -> Result<(RlpItem, usize), RlpError> {
+        let header = Self::decode_header(input, input_offset)?;
+        let payload = &input[header.payload_offset..header.end()];
+
+        if !header.list {
+            return Ok((RlpItem::String(payload.to_vec()), header.end()));
         }
-        // we skip reading length and directly shift offset to data as the given node is always a complete list
-        let mut offset: usize = 1usize + (input[0] - 0xf7) as usize;
 
-        let mut out: Vec<Vec<u8>> = Vec::new();
-        while offset < input.len() {
-            let (parsed, new_offset) = Self::decode_inner(input, offset);
+        // every level recurses, so untrusted input must not choose how deep
+        if depth == MAX_DEPTH {
+            return Err(RlpError::DepthExceeded {
+                offset: input_offset,
+            });
+        }
 
-            out.push(parsed);
+        let mut items = Vec::new();
+        let mut offset = header.payload_offset;
+        while offset < header.end() {
+            let (item, new_offset) = Self::decode_nested(input, offset, depth + 1)?;
+            // a nested item must not run past its parent
+            if new_offset > header.end() {
+                return Err(RlpError::ListLengthMismatch {
+                    offset: input_offset,
+                });
+            }
+            items.push(item);
             offset = new_offset;
         }
-        out
+
+        Ok((RlpItem::List(items), header.end()))
     }
 
-    pub fn decode_mpt_node(input: &[u8]) -> Node {
-        let decoded = NodeDecoder::decode_rlp(input);
-        match decoded.len() {
-            17 => Node {
-                original: input.to_vec(),
-                node: NodeType::Branch(decoded),
-            },
+    /// Decodes a single complete item, rejecting trailing bytes.
+    pub fn decode(input: &[u8]) -> Result<RlpItem, RlpError> {
+        let (item, offset) = Self::decode_inner(input, 0)?;
+        if offset != input.len() {
+            return Err(RlpError::TrailingBytes {
+                consumed: offset,
+                length: input.len(),
+            });
+        }
+        Ok(item)
+    }
+
+    /// Decodes a flat list of strings, or a single string as a one element list.
+    pub fn decode_rlp(input: &[u8]) -> Result<Vec<Vec<u8>>, RlpError> {
+        match Self::decode(input)? {
+            RlpItem::String(bytes) => Ok(vec![bytes]),
+            RlpItem::List(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    RlpItem::String(bytes) => Ok(bytes),
+                    RlpItem::List(_) => Err(RlpError::UnexpectedList),
+                })
+                .collect(),
+        }
+    }
+
+    /// Iterates over the raw encodings of the items of a complete list without copying them.
+    pub fn list_items(input: &[u8]) -> Result<RlpListIter<'_>, RlpError> {
+        let header = Self::decode_header(input, 0)?;
+        if !header.list {
+            return Err(RlpError::UnexpectedString);
+        }
+        if header.end() != input.len() {
+            return Err(RlpError::TrailingBytes {
+                consumed: header.end(),
+                length: input.len(),
+            });
+        }
+
+        Ok(RlpListIter {
+            input,
+            offset: header.payload_offset,
+        })
+    }
+
+    /// Splits a complete list into the raw encodings of its items without decoding them.
+    pub fn decode_list_raw(input: &[u8]) -> Result<Vec<&[u8]>, RlpError> {
+        Self::list_items(input)?.collect()
+    }
+
+    /// Payload of a complete string item, borrowed from the input.
+    pub fn decode_bytes(raw: &[u8]) -> Result<&[u8], RlpError> {
+        let header = Self::decode_header(raw, 0)?;
+        if header.list {
+            return Err(RlpError::UnexpectedList);
+        }
+        if header.end() != raw.len() {
+            return Err(RlpError::TrailingBytes {
+                consumed: header.end(),
+                length: raw.len(),
+            });
+        }
+        Ok(&raw[header.payload_offset..header.end()])
+    }
+
+    /// Canonical big endian integer of at most `width` bytes.
+    fn decode_integer(raw: &[u8], width: usize) -> Result<&[u8], RlpError> {
+        let bytes = Self::decode_bytes(raw)?;
+        if bytes.first() == Some(&0) {
+            return Err(RlpError::LeadingZeroInteger);
+        }
+        if bytes.len() > width {
+            return Err(RlpError::IntegerOverflow {
+                length: bytes.len(),
+            });
+        }
+        Ok(bytes)
+    }
+
+    pub fn decode_u64(raw: &[u8]) -> Result<u64, RlpError> {
+        let bytes = Self::decode_integer(raw, 8)?;
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    pub fn decode_u256(raw: &[u8]) -> Result<U256, RlpError> {
+        Ok(U256::from_be_slice(Self::decode_integer(raw, 32)?))
+    }
+
+    fn decode_child(raw: &[u8]) -> Result<NodeChild<'_>, RlpError> {
+        let header = Self::decode_header(raw, 0)?;
+        if header.list {
+            // nodes shorter than 32 bytes are embedded in their parent instead of hashed,
+            // they are decoded when the walk reaches them
+            if raw.len() >= 32 {
+                return Err(RlpError::InvalidChild { length: raw.len() });
+            }
+            return Ok(NodeChild::Inline(raw));
+        }
+
+        let bytes = Self::decode_bytes(raw)?;
+        match bytes.len() {
+            0 => Ok(NodeChild::Empty),
+            32 => Ok(NodeChild::Hash(bytes)),
+            length => Err(RlpError::InvalidChild { length }),
+        }
+    }
+
+    /// Decodes a trie node, every field borrows from `input`.
+    pub fn decode_mpt_node(input: &[u8]) -> Result<Node<'_>, RlpError> {
+        // a branch has 17 items, anything longer is rejected without collecting it
+        let mut items: [&[u8]; 17] = [&[]; 17];
+        let mut count = 0;
+        for item in Self::list_items(input)? {
+            if count == items.len() {
+                return Err(RlpError::InvalidNode { items: count + 1 });
+            }
+            items[count] = item?;
+            count += 1;
+        }
+
+        match count {
+            17 => {
+                let mut children = [NodeChild::Empty; 16];
+                for (child, raw) in children.iter_mut().zip(items.iter()) {
+                    *child = Self::decode_child(raw)?;
+                }
+                Ok(Node {
+                    original: input,
+                    node: NodeType::Branch(children, Self::decode_bytes(items[16])?),
+                })
+            }
             2 => {
                 // 0000 Extension Even
                 // 0001 Extension OddE
                 // 0010 Leaf Even
                 // 0011 Leaf Odd
 
+                let path = Self::decode_bytes(items[0])?;
                 // find the first nibble
-                let prefix = decoded[0][0] >> 4;
+                let prefix = path.first().ok_or(RlpError::EmptyNodePath)? >> 4;
+                // even paths pad the flag nibble with a zero nibble
+                if prefix > 0x3 || (prefix & 0x1 == 0 && path[0] & 0x0f != 0) {
+                    return Err(RlpError::InvalidPathPrefix { prefix });
+                }
 
                 if (prefix & 0x2) != 0 {
-                    Node {
-                        original: input.to_vec(),
+                    Ok(Node {
+                        original: input,
                         node: NodeType::Leaf(
                             // true if odd
                             prefix & 0x1 != 0,
-                            decoded[0].clone(),
-                            decoded[1].clone(),
+                            path,
+                            Self::decode_bytes(items[1])?,
                         ),
-                    }
+                    })
                 } else {
-                    Node {
-                        original: input.to_vec(),
+                    // an extension always points at a node
+                    let child = Self::decode_child(items[1])?;
+                    if child == NodeChild::Empty {
+                        return Err(RlpError::InvalidChild { length: 0 });
+                    }
+                    Ok(Node {
+                        original: input,
                         node: NodeType::Extension(
                             // true if odd
                             prefix & 0x1 != 0,
-                            decoded[0].clone(),
-                            decoded[1].clone(),
+                            path,
+                            child,
                         ),
-                    }
+                    })
                 }
             }
-            _ => {
-                panic!("we don't support this node");
-            }
+            items => Err(RlpError::InvalidNode { items }),
         }
     }
 }
+
+/// Raw encodings of the items of a list, see [`NodeDecoder::list_items`].
+pub struct RlpListIter<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for RlpListIter<'a> {
+    type Item = Result<&'a [u8], RlpError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.input.len() {
+            return None;
+        }
+
+        // the list header already checked the payload ends with the input
+        let item = match NodeDecoder::decode_header(self.input, self.offset) {
+            Ok(item) => item,
+            Err(e) => {
+                self.offset = self.input.len();
+                return Some(Err(e));
+            }
+        };
+        let raw = &self.input[self.offset..item.end()];
+        self.offset = item.end();
+        Some(Ok(raw))
+    }
+}
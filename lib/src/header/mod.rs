@@ -1,8 +1,16 @@
+use std::fmt;
+
 use alloy_primitives::{Address, Bloom, Bytes, B256, B64, U256};
 use alloy_rlp::{BufMut, Encodable};
 use serde::{Deserialize, Serialize};
 use tiny_keccak::{self, Hasher};
 
+use crate::chain_spec::{
+    BaseFeeParams, BlobParams, ChainSpec, Fork, BLOB_BASE_COST, GAS_LIMIT_BOUND_DIVISOR,
+    GAS_PER_BLOB, INITIAL_BASE_FEE, MIN_GAS_LIMIT,
+};
+use crate::decoder::{NodeDecoder, RlpError};
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LeanHeader {
     pub parent_hash: [u8; 32],
@@ -95,6 +103,97 @@ impl LeanHeader {
         }
     }
 
+    /// Decodes a header from its RLP encoding, the optional fields of later forks included.
+    pub fn decode(raw: &[u8]) -> Result<LeanHeader, HeaderError> {
+        let fields = NodeDecoder::decode_list_raw(raw)?;
+        if fields.len() < 15 || fields.len() > 21 {
+            return Err(HeaderError::InvalidHeader {
+                items: fields.len(),
+            });
+        }
+
+        let optional_u64 = |index: usize| -> Result<Option<u64>, HeaderError> {
+            fields
+                .get(index)
+                .map(|field| NodeDecoder::decode_u64(field))
+                .transpose()
+                .map_err(HeaderError::from)
+        };
+        let optional_hash = |index: usize| -> Result<Option<[u8; 32]>, HeaderError> {
+            fields
+                .get(index)
+                .map(|field| fixed_field(field, index))
+                .transpose()
+        };
+
+        Ok(LeanHeader {
+            parent_hash: fixed_field(fields[0], 0)?,
+            ommers_hash: fixed_field(fields[1], 1)?,
+            beneficiary: fixed_field(fields[2], 2)?,
+            state_root: fixed_field(fields[3], 3)?,
+            transactions_root: fixed_field(fields[4], 4)?,
+            receipts_root: fixed_field(fields[5], 5)?,
+            logs_bloom: fixed_field::<256>(fields[6], 6)?.to_vec(),
+            difficulty: NodeDecoder::decode_u256(fields[7])?.to_be_bytes(),
+            number: NodeDecoder::decode_u64(fields[8])?,
+            gas_limit: NodeDecoder::decode_u64(fields[9])?,
+            gas_used: NodeDecoder::decode_u64(fields[10])?,
+            timestamp: NodeDecoder::decode_u64(fields[11])?,
+            extra_data: NodeDecoder::decode_bytes(fields[12])?.to_vec(),
+            mix_hash: fixed_field(fields[13], 13)?,
+            nonce: fixed_field(fields[14], 14)?,
+            base_fee_per_gas: optional_u64(15)?,
+            withdrawals_root: optional_hash(16)?,
+            blob_gas_used: optional_u64(17)?,
+            excess_blob_gas: optional_u64(18)?,
+            parent_beacon_block_root: optional_hash(19)?,
+            requests_hash: optional_hash(20)?,
+        })
+    }
+
+    /// Checks that the fields each fork adds are present exactly when the fork is active
+    /// at this block on `spec`.
+    pub fn validate(&self, spec: &ChainSpec) -> Result<(), HeaderError> {
+        let fields = [
+            (
+                Fork::London,
+                "base_fee_per_gas",
+                self.base_fee_per_gas.is_some(),
+            ),
+            (
+                Fork::Shanghai,
+                "withdrawals_root",
+                self.withdrawals_root.is_some(),
+            ),
+            (Fork::Cancun, "blob_gas_used", self.blob_gas_used.is_some()),
+            (
+                Fork::Cancun,
+                "excess_blob_gas",
+                self.excess_blob_gas.is_some(),
+            ),
+            (
+                Fork::Cancun,
+                "parent_beacon_block_root",
+                self.parent_beacon_block_root.is_some(),
+            ),
+            (Fork::Prague, "requests_hash", self.requests_hash.is_some()),
+        ];
+
+        for (fork, field, present) in fields {
+            let active = spec.is_active(fork, self.number, self.timestamp);
+            if active != present {
+                return Err(HeaderError::ForkField {
+                    chain: spec.name,
+                    fork,
+                    field,
+                    number: self.number,
+                    expected: active,
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn header_payload_length(&self) -> usize {
         let mut length = 0;
 
@@ -142,6 +241,458 @@ impl LeanHeader {
     }
 }
 
+/// Payload of a string item that must be exactly `N` bytes.
+fn fixed_field<const N: usize>(raw: &[u8], index: usize) -> Result<[u8; N], HeaderError> {
+    let bytes = NodeDecoder::decode_bytes(raw)?;
+    bytes
+        .try_into()
+        .map_err(|_| HeaderError::InvalidFieldLength {
+            index,
+            length: bytes.len(),
+        })
+}
+
+/// The fields most proofs need, read from a header's RLP encoding without decoding the rest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderFields {
+    /// keccak of the encoding as given
+    pub hash: [u8; 32],
+    pub parent_hash: [u8; 32],
+    pub state_root: [u8; 32],
+    pub number: u64,
+    pub timestamp: u64,
+}
+
+impl HeaderFields {
+    pub fn decode(raw: &[u8]) -> Result<HeaderFields, HeaderError> {
+        // splits the list without decoding past the timestamp, the count still has to fit a fork
+        let fields = NodeDecoder::decode_list_raw(raw)?;
+        if fields.len() < 15 || fields.len() > 21 {
+            return Err(HeaderError::InvalidHeader {
+                items: fields.len(),
+            });
+        }
+        let parent_hash = fixed_field(fields[0], 0)?;
+        let state_root = fixed_field(fields[3], 3)?;
+        let number = NodeDecoder::decode_u64(fields[8])?;
+        let timestamp = NodeDecoder::decode_u64(fields[11])?;
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&LeanHeader::keccak(raw));
+        Ok(HeaderFields {
+            hash,
+            parent_hash,
+            state_root,
+            number,
+            timestamp,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    Decode(RlpError),
+    /// header list with a number of fields no fork uses
+    InvalidHeader {
+        items: usize,
+    },
+    /// fixed size field of the wrong length, by position in the header list
+    InvalidFieldLength {
+        index: usize,
+        length: usize,
+    },
+    /// a fork's field is missing after its activation or present before it
+    ForkField {
+        chain: &'static str,
+        fork: Fork,
+        field: &'static str,
+        number: u64,
+        expected: bool,
+    },
+    /// holocene or jovian extra data that does not encode the base fee parameters
+    InvalidExtraData {
+        number: u64,
+    },
+    GasUsedExceedsLimit {
+        number: u64,
+    },
+    /// the gas limit moved by more than the parent's limit allows
+    GasLimitDrift {
+        number: u64,
+    },
+    BaseFeeMismatch {
+        number: u64,
+        expected: u64,
+        found: u64,
+    },
+    /// blob gas over the block limit or not a whole number of blobs
+    InvalidBlobGasUsed {
+        number: u64,
+    },
+    ExcessBlobGasMismatch {
+        number: u64,
+        expected: u64,
+        found: u64,
+    },
+    /// the timestamp does not increase over the parent's
+    InvalidTimestamp {
+        number: u64,
+    },
+    /// no headers to link
+    EmptyChain,
+    /// the header at `number` does not name the header before it as its parent
+    BrokenLink {
+        number: u64,
+    },
+    /// the header at `number` does not follow the header before it
+    NonConsecutive {
+        number: u64,
+    },
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::Decode(e) => write!(f, "invalid header rlp: {}", e),
+            HeaderError::InvalidHeader { items } => write!(f, "header with {} fields", items),
+            HeaderError::InvalidFieldLength { index, length } => {
+                write!(f, "header field {} of {} bytes", index, length)
+            }
+            HeaderError::ForkField {
+                chain,
+                fork,
+                field,
+                number,
+                expected,
+            } => {
+                if *expected {
+                    write!(
+                        f,
+                        "block {} on {} is missing {}, required from {}",
+                        number, chain, field, fork
+                    )
+                } else {
+                    write!(
+                        f,
+                        "block {} on {} has {}, which {} has not activated",
+                        number, chain, field, fork
+                    )
+                }
+            }
+            HeaderError::InvalidExtraData { number } => {
+                write!(f, "block {} has invalid base fee parameters", number)
+            }
+            HeaderError::GasUsedExceedsLimit { number } => {
+                write!(f, "block {} uses more gas than its limit", number)
+            }
+            HeaderError::GasLimitDrift { number } => {
+                write!(f, "gas limit of block {} moved too far", number)
+            }
+            HeaderError::BaseFeeMismatch {
+                number,
+                expected,
+                found,
+            } => write!(
+                f,
+                "base fee of block {} is {}, expected {}",
+                number, found, expected
+            ),
+            HeaderError::InvalidBlobGasUsed { number } => {
+                write!(f, "invalid blob gas used in block {}", number)
+            }
+            HeaderError::ExcessBlobGasMismatch {
+                number,
+                expected,
+                found,
+            } => write!(
+                f,
+                "excess blob gas of block {} is {}, expected {}",
+                number, found, expected
+            ),
+            HeaderError::InvalidTimestamp { number } => {
+                write!(f, "timestamp of block {} does not increase", number)
+            }
+            HeaderError::EmptyChain => write!(f, "empty header chain"),
+            HeaderError::BrokenLink { number } => {
+                write!(f, "parent hash of block {} does not link", number)
+            }
+            HeaderError::NonConsecutive { number } => {
+                write!(f, "block {} does not follow its parent", number)
+            }
+        }
+    }
+}
+
+impl From<RlpError> for HeaderError {
+    fn from(e: RlpError) -> Self {
+        HeaderError::Decode(e)
+    }
+}
+
+/// Both ends of a verified header chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ancestry {
+    pub target_hash: [u8; 32],
+    pub target_number: u64,
+    pub anchor_hash: [u8; 32],
+    pub anchor_number: u64,
+}
+
+pub struct HeaderVerifier;
+
+impl HeaderVerifier {
+    fn is_jovian(spec: &ChainSpec, header: &LeanHeader) -> bool {
+        spec.op_stack
+            .is_some_and(|op| header.timestamp >= op.jovian_timestamp)
+    }
+
+    /// Base fee parameters and minimum base fee for the child of `parent`. Holocene chains
+    /// read the parameters from the parent's extra data, jovian chains the minimum as well.
+    fn base_fee_params(
+        spec: &ChainSpec,
+        parent: &LeanHeader,
+        timestamp: u64,
+    ) -> Result<(BaseFeeParams, u64), HeaderError> {
+        let params = spec.base_fee_params(timestamp);
+        let holocene = spec
+            .op_stack
+            .filter(|op| parent.timestamp >= op.holocene_timestamp);
+        if holocene.is_none() {
+            return Ok((params, 0));
+        }
+
+        // version byte, the denominator and elasticity as big endian u32s, then from jovian
+        // the minimum base fee as a big endian u64 under version 1
+        let jovian = Self::is_jovian(spec, parent);
+        let (version, length) = if jovian { (1, 17) } else { (0, 9) };
+        let extra = &parent.extra_data;
+        if extra.len() != length || extra[0] != version {
+            return Err(HeaderError::InvalidExtraData {
+                number: parent.number,
+            });
+        }
+        let denominator = u32::from_be_bytes(extra[1..5].try_into().unwrap()) as u64;
+        let elasticity = u32::from_be_bytes(extra[5..9].try_into().unwrap()) as u64;
+        let min_base_fee = if jovian {
+            u64::from_be_bytes(extra[9..17].try_into().unwrap())
+        } else {
+            0
+        };
+        match (denominator, elasticity) {
+            // zero parameters keep the canyon defaults
+            (0, 0) => Ok((params, min_base_fee)),
+            (0, _) | (_, 0) => Err(HeaderError::InvalidExtraData {
+                number: parent.number,
+            }),
+            _ => Ok((
+                BaseFeeParams {
+                    max_change_denominator: denominator,
+                    elasticity_multiplier: elasticity,
+                },
+                min_base_fee,
+            )),
+        }
+    }
+
+    /// EIP-1559 base fee of the block after `parent`.
+    pub fn next_base_fee(
+        spec: &ChainSpec,
+        parent: &LeanHeader,
+        timestamp: u64,
+    ) -> Result<u64, HeaderError> {
+        let (params, min_base_fee) = Self::base_fee_params(spec, parent, timestamp)?;
+        let parent_base_fee = match parent.base_fee_per_gas {
+            Some(base_fee) => base_fee as u128,
+            None => return Ok(INITIAL_BASE_FEE),
+        };
+
+        let target = (parent.gas_limit / params.elasticity_multiplier) as u128;
+        let mut used = parent.gas_used as u128;
+        if Self::is_jovian(spec, parent) {
+            // the data availability footprint counts when it is over the gas used
+            used = used.max(parent.blob_gas_used.unwrap_or(0) as u128);
+        }
+        let denominator = params.max_change_denominator as u128;
+        let base_fee = if used == target || target == 0 {
+            parent_base_fee
+        } else if used > target {
+            let delta = (parent_base_fee * (used - target) / target / denominator).max(1);
+            parent_base_fee + delta
+        } else {
+            let delta = parent_base_fee * (target - used) / target / denominator;
+            parent_base_fee.saturating_sub(delta)
+        };
+        Ok((base_fee.min(u64::MAX as u128) as u64).max(min_base_fee))
+    }
+
+    /// `factor * e^(numerator / denominator)` by its taylor series, as EIP-4844 prices blobs.
+    fn fake_exponential(factor: u128, numerator: u128, denominator: u128) -> u128 {
+        let mut output = 0u128;
+        let mut accumulator = factor * denominator;
+        let mut i = 1u128;
+        while accumulator > 0 {
+            output = output.saturating_add(accumulator);
+            accumulator = accumulator.saturating_mul(numerator) / (denominator * i);
+            i += 1;
+        }
+        output / denominator
+    }
+
+    /// Excess blob gas of the block after `parent` under the child's `blobs`.
+    pub fn next_excess_blob_gas(spec: &ChainSpec, parent: &LeanHeader, blobs: BlobParams) -> u64 {
+        // the cancun block starts from zero
+        let excess = parent.excess_blob_gas.unwrap_or(0);
+        let used = parent.blob_gas_used.unwrap_or(0);
+        if excess + used < blobs.target_blob_gas {
+            return 0;
+        }
+
+        if blobs.reserve_price {
+            // EIP-7918: while blobs cost less than BLOB_BASE_COST execution gas, the excess
+            // only grows. The parent's blob base fee is priced under the parent's own fork.
+            let fraction = spec
+                .blob_params(parent.timestamp)
+                .unwrap_or(blobs)
+                .base_fee_update_fraction;
+            let blob_base_fee = Self::fake_exponential(1, excess as u128, fraction as u128);
+            let reserve = BLOB_BASE_COST as u128 * parent.base_fee_per_gas.unwrap_or(0) as u128;
+            if reserve > (GAS_PER_BLOB as u128).saturating_mul(blob_base_fee) {
+                return excess
+                    + used * (blobs.max_blob_gas - blobs.target_blob_gas) / blobs.max_blob_gas;
+            }
+        }
+        excess + used - blobs.target_blob_gas
+    }
+
+    /// Checks the rules of a header on its own: its fork fields and gas used.
+    pub fn verify_header(spec: &ChainSpec, header: &LeanHeader) -> Result<(), HeaderError> {
+        header.validate(spec)?;
+        if header.gas_used > header.gas_limit {
+            return Err(HeaderError::GasUsedExceedsLimit {
+                number: header.number,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the rules that tie `child` to `parent`, apart from the parent hash, along
+    /// with the child's fork fields.
+    pub fn verify_child(
+        spec: &ChainSpec,
+        parent: &LeanHeader,
+        child: &LeanHeader,
+    ) -> Result<(), HeaderError> {
+        let number = child.number;
+        Self::verify_header(spec, child)?;
+        if parent.number.checked_add(1) != Some(number) {
+            return Err(HeaderError::NonConsecutive { number });
+        }
+        if child.timestamp <= parent.timestamp {
+            return Err(HeaderError::InvalidTimestamp { number });
+        }
+
+        // OP-stack gas limits come from the system config instead
+        if spec.op_stack.is_none() {
+            let mut parent_gas_limit = parent.gas_limit;
+            if parent.base_fee_per_gas.is_none() && child.base_fee_per_gas.is_some() {
+                // the london block doubles the limit to keep the old limit as its target
+                parent_gas_limit *= spec.base_fee.elasticity_multiplier;
+            }
+            let drift = child.gas_limit.abs_diff(parent_gas_limit);
+            if drift >= parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR
+                || child.gas_limit < MIN_GAS_LIMIT
+            {
+                return Err(HeaderError::GasLimitDrift { number });
+            }
+        }
+
+        if let Some(found) = child.base_fee_per_gas {
+            // OP-stack chains did not start london from the initial base fee
+            if parent.base_fee_per_gas.is_some() || spec.op_stack.is_none() {
+                let expected = Self::next_base_fee(spec, parent, child.timestamp)?;
+                if found != expected {
+                    return Err(HeaderError::BaseFeeMismatch {
+                        number,
+                        expected,
+                        found,
+                    });
+                }
+            }
+        }
+
+        if let (Some(blob_gas_used), Some(found)) = (child.blob_gas_used, child.excess_blob_gas) {
+            let expected = match spec.blob_params(child.timestamp) {
+                Some(blobs) => {
+                    if blob_gas_used > blobs.max_blob_gas || blob_gas_used % GAS_PER_BLOB != 0 {
+                        return Err(HeaderError::InvalidBlobGasUsed { number });
+                    }
+                    Self::next_excess_blob_gas(spec, parent, blobs)
+                }
+                // OP-stack blocks carry no blobs, from jovian blob gas used is the data
+                // availability footprint instead
+                None => {
+                    if blob_gas_used != 0 && !Self::is_jovian(spec, child) {
+                        return Err(HeaderError::InvalidBlobGasUsed { number });
+                    }
+                    0
+                }
+            };
+            if found != expected {
+                return Err(HeaderError::ExcessBlobGasMismatch {
+                    number,
+                    expected,
+                    found,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a header chain like `verify_ancestry`, every header on its own and the consensus
+    /// rules between every pair of headers in it.
+    pub fn verify_chain(spec: &ChainSpec, headers: &[LeanHeader]) -> Result<Ancestry, HeaderError> {
+        // later headers are checked as children of the one before
+        let first = headers.first().ok_or(HeaderError::EmptyChain)?;
+        Self::verify_header(spec, first)?;
+        for pair in headers.windows(2) {
+            Self::verify_child(spec, &pair[0], &pair[1])?;
+        }
+        Self::verify_ancestry(headers)
+    }
+
+    /// Checks that `headers`, ordered from the old target to the recent anchor, are linked
+    /// by their parent hashes. Every header is hashed once.
+    pub fn verify_ancestry(headers: &[LeanHeader]) -> Result<Ancestry, HeaderError> {
+        let target = headers.first().ok_or(HeaderError::EmptyChain)?;
+        let target_hash = target.hash();
+
+        let mut hash = target_hash;
+        let mut number = target.number;
+        for header in &headers[1..] {
+            if number.checked_add(1) != Some(header.number) {
+                return Err(HeaderError::NonConsecutive {
+                    number: header.number,
+                });
+            }
+            if header.parent_hash != hash {
+                return Err(HeaderError::BrokenLink {
+                    number: header.number,
+                });
+            }
+            hash = header.hash();
+            number = header.number;
+        }
+
+        Ok(Ancestry {
+            target_hash,
+            target_number: target.number,
+            anchor_hash: hash,
+            anchor_number: number,
+        })
+    }
+}
+
 impl From<alloy_consensus::Header> for LeanHeader {
     fn from(header: alloy_consensus::Header) -> Self {
         let mut parent_hash = [0u8; 32];
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::header::{Ancestry, LeanHeader};
+use crate::verifier::{MPTVerifier, VerifierError};
+
+/// EIP-2935 history storage contract, keeps recent block hashes in a ring buffer.
+pub const HISTORY_STORAGE_ADDRESS: [u8; 20] = [
+    0x00, 0x00, 0xf9, 0x08, 0x27, 0xf1, 0xc5, 0x3a, 0x10, 0xcb, 0x7a, 0x02, 0x33, 0x5b, 0x17, 0x53,
+    0x20, 0x00, 0x29, 0x35,
+];
+
+/// number of block hashes the ring buffer holds
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+
+/// History contract state at a recent anchor block, proving one of its stored hashes.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct HistoryProof {
+    pub anchor: LeanHeader,
+    pub account_proof: Vec<Vec<u8>>,
+    pub storage_proof: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HistoryError {
+    Verifier(VerifierError),
+    /// the target is not among the hashes the anchor's ring buffer holds
+    OutOfWindow {
+        target: u64,
+        anchor: u64,
+    },
+    /// the stored hash is not the target header's hash
+    HashMismatch {
+        number: u64,
+    },
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Verifier(e) => write!(f, "history proof failed: {}", e),
+            HistoryError::OutOfWindow { target, anchor } => write!(
+                f,
+                "block {} is outside the history window of block {}",
+                target, anchor
+            ),
+            HistoryError::HashMismatch { number } => {
+                write!(
+                    f,
+                    "stored hash of block {} does not match its header",
+                    number
+                )
+            }
+        }
+    }
+}
+
+impl From<VerifierError> for HistoryError {
+    fn from(e: VerifierError) -> Self {
+        HistoryError::Verifier(e)
+    }
+}
+
+pub type HistoryResult<T> = Result<T, HistoryError>;
+
+pub struct HistoryVerifier;
+
+impl HistoryVerifier {
+    /// Ring buffer slot holding the hash of block `number`.
+    pub fn slot(number: u64) -> Vec<u8> {
+        let mut slot = vec![0u8; 32];
+        slot[24..].copy_from_slice(&(number % HISTORY_SERVE_WINDOW).to_be_bytes());
+        slot
+    }
+
+    /// Proves `target` is an ancestor of the anchor by reading its hash from the history
+    /// contract's storage at the anchor.
+    ///
+    /// Processing block `n` stores the hash of block `n - 1`, so the anchor holds the hashes of
+    /// the `HISTORY_SERVE_WINDOW` blocks before it.
+    pub fn verify(target: &LeanHeader, proof: HistoryProof) -> HistoryResult<Ancestry> {
+        let anchor_number = proof.anchor.number;
+        if target.number >= anchor_number || anchor_number - target.number > HISTORY_SERVE_WINDOW {
+            return Err(HistoryError::OutOfWindow {
+                target: target.number,
+                anchor: anchor_number,
+            });
+        }
+
+        let account_state = MPTVerifier::verify_and_get_account_state(
+            &proof.anchor.state_root,
+            HISTORY_STORAGE_ADDRESS.to_vec(),
+            MPTVerifier::decode_proof(&proof.account_proof)?,
+        )?;
+        let stored = MPTVerifier::verify_storage_slot(
+            &account_state.storage_hash,
+            Self::slot(target.number),
+            MPTVerifier::decode_proof(&proof.storage_proof)?,
+        )?
+        .unwrap_or_default();
+
+        // storage values drop leading zero bytes
+        let target_hash = target.hash();
+        let mut stored_hash = [0u8; 32];
+        if stored.len() > 32 {
+            return Err(HistoryError::HashMismatch {
+                number: target.number,
+            });
+        }
+        stored_hash[32 - stored.len()..].copy_from_slice(&stored);
+        if stored_hash != target_hash {
+            return Err(HistoryError::HashMismatch {
+                number: target.number,
+            });
+        }
+
+        Ok(Ancestry {
+            target_hash,
+            target_number: target.number,
+            anchor_hash: proof.anchor.hash(),
+            anchor_number,
+        })
+    }
+}
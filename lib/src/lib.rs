@@ -1,12 +1,34 @@
 use alloy_primitives::{Address, FixedBytes, U256};
 use alloy_sol_types::{sol, SolValue};
+use bloom::{AbsenceInputs, AbsenceOutput};
+use header::{Ancestry, LeanHeader};
+use history::HistoryProof;
+use multiproof::MultiProofInputs;
+use op_stack::{L1StateInputs, L1StateOutput, OutputRootInputs, OutputRootOutput};
+use oracle::{AggregateInputs, AggregateOutput, FieldsInputs, FieldsOutput};
 use serde::{Deserialize, Serialize};
-use verifier::VerifierInputs;
+use verifier::{
+    AccountKind, AccountOutput, AccountStateInputs, AccountStateOutput, MultiAccountOutput, Proofs,
+    VerifierInputs,
+};
+use withdrawals::{WithdrawalInputs, WithdrawalOutput};
+pub mod beacon;
+pub mod bloom;
+pub mod chain_spec;
 pub mod decoder;
 pub mod header;
+pub mod history;
+pub mod light_client;
+pub mod multiproof;
+pub mod op_stack;
+pub mod oracle;
+pub mod receipts;
+pub mod requests;
 pub mod states;
 pub mod swapper;
+pub mod transactions;
 pub mod verifier;
+pub mod withdrawals;
 
 sol! {
     struct Order {
@@ -18,13 +40,121 @@ sol! {
         address buy_token;
         address sell_token;
     }
+
+    struct AnchoredOrder {
+        Order order;
+        bytes32 anchor_hash;
+        uint256 anchor_number;
+    }
+
+    struct AccountStateProof {
+        address account;
+        bytes32 block_hash;
+        uint256 block_number;
+        uint256 balance;
+        uint256 nonce;
+        bytes32 code_hash;
+        bool is_contract;
+    }
+
+    struct WithdrawalProof {
+        bytes32 block_hash;
+        uint256 block_number;
+        uint256 position;
+        uint256 index;
+        uint256 validator_index;
+        address recipient;
+        uint256 amount_gwei;
+    }
+
+    struct BlockFields {
+        bytes32 block_hash;
+        uint256 block_number;
+        uint8[] fields;
+        bytes32[] values;
+    }
+
+    struct BlockAggregate {
+        bytes32 first_hash;
+        uint256 first_number;
+        bytes32 last_hash;
+        uint256 last_number;
+        uint8 field;
+        uint8 aggregation;
+        uint256 value;
+    }
+
+    struct SlotValue {
+        bytes32 slot;
+        bytes32 value;
+    }
+
+    struct AccountSlots {
+        address account;
+        SlotValue[] slots;
+    }
+
+    struct MultiAccountProof {
+        bytes32 block_hash;
+        uint256 block_number;
+        AccountSlots[] accounts;
+    }
+
+    struct LogAbsence {
+        bytes32 first_hash;
+        uint256 first_number;
+        bytes32 last_hash;
+        uint256 last_number;
+        address emitter;
+        bytes32[] topics;
+    }
+
+    struct OutputRootProof {
+        bytes32 output_root;
+        bytes32 block_hash;
+        uint256 block_number;
+        bytes32 state_root;
+        bytes32 message_passer_storage_root;
+    }
+
+    struct L1StateProof {
+        bytes32 l2_block_hash;
+        uint256 l2_block_number;
+        bytes32 l1_block_hash;
+        uint256 l1_block_number;
+        AccountSlots[] accounts;
+    }
 }
 
-pub fn pack_order(
+/// Id of the mode that produced a proof, committed as the first public value word since every
+/// mode shares one verification key. Ids never change once deployed, 2 was the decode bench
+/// which now runs as its own program.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgramMode {
+    Order = 0,
+    AnchoredOrder = 1,
+    AccountState = 3,
+    Withdrawal = 4,
+    RawOrder = 5,
+    BlockFields = 6,
+    BlockAggregate = 7,
+    LogAbsence = 8,
+    Multiproof = 9,
+    OutputRoot = 10,
+    L1State = 11,
+}
+
+/// `abi.encode(uint256(mode), out)`, decoded on chain as `(uint256, T)`.
+pub fn public_values<T: SolValue>(mode: ProgramMode, out: T) -> Vec<u8> {
+    (U256::from(mode as u8), out).abi_encode_params()
+}
+
+fn order(
     swapped: swapper::uni_v2_swapper::SwapOutput,
     block_number: u64,
     block_hash: Vec<u8>,
-) -> Vec<u8> {
+) -> Order {
     Order {
         seller: Address::from_slice(&swapped.seller),
         block_hash: FixedBytes::from_slice(&block_hash),
@@ -34,7 +164,176 @@ pub fn pack_order(
         buy_token: Address::from_slice(&swapped.buy_token),
         sell_token: Address::from_slice(&swapped.sell_token),
     }
-    .abi_encode()
+}
+
+pub fn pack_order(
+    swapped: swapper::uni_v2_swapper::SwapOutput,
+    block_number: u64,
+    block_hash: Vec<u8>,
+) -> Vec<u8> {
+    public_values(ProgramMode::Order, order(swapped, block_number, block_hash))
+}
+
+/// Same order as `pack_order`, committed under the raw header mode.
+pub fn pack_raw_order(
+    swapped: swapper::uni_v2_swapper::SwapOutput,
+    block_number: u64,
+    block_hash: Vec<u8>,
+) -> Vec<u8> {
+    public_values(
+        ProgramMode::RawOrder,
+        order(swapped, block_number, block_hash),
+    )
+}
+
+/// Order priced at an old block, settled against the recent anchor that descends from it.
+pub fn pack_anchored_order(
+    swapped: swapper::uni_v2_swapper::SwapOutput,
+    block_number: u64,
+    block_hash: Vec<u8>,
+    ancestry: &Ancestry,
+) -> Vec<u8> {
+    public_values(
+        ProgramMode::AnchoredOrder,
+        AnchoredOrder {
+            order: order(swapped, block_number, block_hash),
+            anchor_hash: FixedBytes::from(ancestry.anchor_hash),
+            anchor_number: U256::from(ancestry.anchor_number),
+        },
+    )
+}
+
+pub fn pack_account_state(out: AccountStateOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::AccountState,
+        AccountStateProof {
+            account: Address::from_slice(&out.address),
+            block_hash: FixedBytes::from_slice(&out.block_hash),
+            block_number: U256::from(out.block_number),
+            balance: out.balance,
+            nonce: out.nonce,
+            code_hash: FixedBytes::from_slice(&out.code_hash),
+            is_contract: out.kind == AccountKind::Contract,
+        },
+    )
+}
+
+pub fn pack_withdrawal(out: WithdrawalOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::Withdrawal,
+        WithdrawalProof {
+            block_hash: FixedBytes::from_slice(&out.block_hash),
+            block_number: U256::from(out.block_number),
+            position: U256::from(out.position),
+            index: U256::from(out.withdrawal.index),
+            validator_index: U256::from(out.withdrawal.validator_index),
+            recipient: Address::from_slice(&out.withdrawal.address),
+            amount_gwei: U256::from(out.withdrawal.amount),
+        },
+    )
+}
+
+pub fn pack_block_fields(out: FieldsOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::BlockFields,
+        BlockFields {
+            block_hash: FixedBytes::from_slice(&out.block_hash),
+            block_number: U256::from(out.block_number),
+            fields: out.fields.iter().map(|&field| field as u8).collect(),
+            values: out.values.into_iter().map(FixedBytes::from).collect(),
+        },
+    )
+}
+
+pub fn pack_block_aggregate(out: AggregateOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::BlockAggregate,
+        BlockAggregate {
+            first_hash: FixedBytes::from_slice(&out.first_hash),
+            first_number: U256::from(out.first_number),
+            last_hash: FixedBytes::from_slice(&out.last_hash),
+            last_number: U256::from(out.last_number),
+            field: out.field as u8,
+            aggregation: out.aggregation as u8,
+            value: out.value,
+        },
+    )
+}
+
+/// Every proven slot of every account, values left padded to a word.
+pub fn pack_multi_account(out: MultiAccountOutput, block_number: u64) -> Vec<u8> {
+    public_values(
+        ProgramMode::Multiproof,
+        MultiAccountProof {
+            block_hash: FixedBytes::from_slice(&out.block_hash),
+            block_number: U256::from(block_number),
+            accounts: account_slots(out.accounts),
+        },
+    )
+}
+
+fn account_slots(accounts: Vec<AccountOutput>) -> Vec<AccountSlots> {
+    accounts
+        .into_iter()
+        .map(|account| AccountSlots {
+            account: Address::from_slice(&account.address),
+            slots: account
+                .slots
+                .iter()
+                .map(|(slot, value)| SlotValue {
+                    slot: FixedBytes::left_padding_from(slot),
+                    value: FixedBytes::left_padding_from(value),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn pack_log_absence(out: AbsenceOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::LogAbsence,
+        LogAbsence {
+            first_hash: FixedBytes::from_slice(&out.first_hash),
+            first_number: U256::from(out.first_number),
+            last_hash: FixedBytes::from_slice(&out.last_hash),
+            last_number: U256::from(out.last_number),
+            emitter: Address::from_slice(&out.query.address),
+            topics: out
+                .query
+                .topics
+                .iter()
+                .map(|topic| FixedBytes::from_slice(topic))
+                .collect(),
+        },
+    )
+}
+
+/// Recomputed output root of an OP-stack block, to compare with the one posted on L1.
+pub fn pack_output_root(out: OutputRootOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::OutputRoot,
+        OutputRootProof {
+            output_root: FixedBytes::from_slice(&out.output_root),
+            block_hash: FixedBytes::from_slice(&out.block_hash),
+            block_number: U256::from(out.block_number),
+            state_root: FixedBytes::from_slice(&out.state_root),
+            message_passer_storage_root: FixedBytes::from_slice(&out.message_passer_storage_root),
+        },
+    )
+}
+
+/// L1 slots proven from an OP-stack block through the L1 block its `L1Block` predeploy stores.
+pub fn pack_l1_state(out: L1StateOutput) -> Vec<u8> {
+    public_values(
+        ProgramMode::L1State,
+        L1StateProof {
+            l2_block_hash: FixedBytes::from_slice(&out.l2_block_hash),
+            l2_block_number: U256::from(out.l2_block_number),
+            l1_block_hash: FixedBytes::from_slice(&out.l1_block_hash),
+            l1_block_number: U256::from(out.l1_block_number),
+            accounts: account_slots(out.accounts),
+        },
+    )
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -42,3 +341,81 @@ pub struct ObsidianInput {
     pub block_verifier_inputs: VerifierInputs,
     pub swap_payload: swapper::uni_v2_swapper::SwapInput,
 }
+
+/// An order at a block given as its RLP header, hashed as is instead of re-encoded.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RawOrderInput {
+    pub header_rlp: Vec<u8>,
+    pub address: Vec<u8>,
+    pub proofs: Proofs,
+    pub swap_payload: swapper::uni_v2_swapper::SwapInput,
+}
+
+/// How an order's block is linked to the recent anchor settlement checks.
+#[derive(Serialize, Deserialize, Debug)]
+pub enum AnchorLink {
+    /// headers after the order's block up to the anchor, oldest first
+    Headers(Vec<LeanHeader>),
+    /// the block's hash read from the EIP-2935 history contract at the anchor
+    History(Box<HistoryProof>),
+}
+
+/// Order at a block older than the `blockhash` window, linked to a recent anchor.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnchoredOrderInput {
+    pub order: ObsidianInput,
+    pub link: AnchorLink,
+}
+
+/// Node decoder exercised by the decode benchmark.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum DecodePath {
+    /// the allocating decoder the library used before borrowed nodes, kept in the bench program
+    /// as a copy
+    Owned,
+    /// borrowed decoder, slices into the proof buffers
+    Borrowed,
+}
+
+/// Input of the decode bench program, see `bench-program`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DecodeBenchInput {
+    pub path: DecodePath,
+    pub proofs: Vec<Vec<Vec<u8>>>,
+}
+
+/// Everything the zkVM program can be asked to prove.
+// read once per run, boxing the order would only add an allocation
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ProgramInput {
+    Order(ObsidianInput),
+    AnchoredOrder(AnchoredOrderInput),
+    AccountState(AccountStateInputs),
+    Withdrawal(WithdrawalInputs),
+    RawOrder(RawOrderInput),
+    BlockFields(FieldsInputs),
+    BlockAggregate(AggregateInputs),
+    LogAbsence(AbsenceInputs),
+    Multiproof(MultiProofInputs),
+    OutputRoot(OutputRootInputs),
+    L1State(L1StateInputs),
+}
+
+impl ProgramInput {
+    pub fn mode(&self) -> ProgramMode {
+        match self {
+            ProgramInput::Order(_) => ProgramMode::Order,
+            ProgramInput::AnchoredOrder(_) => ProgramMode::AnchoredOrder,
+            ProgramInput::AccountState(_) => ProgramMode::AccountState,
+            ProgramInput::Withdrawal(_) => ProgramMode::Withdrawal,
+            ProgramInput::RawOrder(_) => ProgramMode::RawOrder,
+            ProgramInput::BlockFields(_) => ProgramMode::BlockFields,
+            ProgramInput::BlockAggregate(_) => ProgramMode::BlockAggregate,
+            ProgramInput::LogAbsence(_) => ProgramMode::LogAbsence,
+            ProgramInput::Multiproof(_) => ProgramMode::Multiproof,
+            ProgramInput::OutputRoot(_) => ProgramMode::OutputRoot,
+            ProgramInput::L1State(_) => ProgramMode::L1State,
+        }
+    }
+}
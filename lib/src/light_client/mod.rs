@@ -0,0 +1,483 @@
+use std::fmt;
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    pairing, G1Affine, G1Projective, G2Affine, G2Projective,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::beacon::{
+    uint_chunk, BeaconError, BeaconFork, BeaconVerifier, ExecutionPayloadHeader,
+    BODY_EXECUTION_PAYLOAD_GINDEX, STATE_FINALIZED_CHECKPOINT_FIELD,
+    STATE_NEXT_SYNC_COMMITTEE_FIELD,
+};
+use crate::header::LeanHeader;
+
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+
+const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [0x07, 0x00, 0x00, 0x00];
+/// proof of possession ciphersuite used by the beacon chain
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A fork the beacon chain activated at `epoch`, signatures after it use its version.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ForkVersion {
+    pub epoch: u64,
+    pub version: [u8; 4],
+    pub layout: BeaconFork,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LightClientConfig {
+    pub genesis_validators_root: [u8; 32],
+    /// ordered by activation epoch, altair onward
+    pub forks: Vec<ForkVersion>,
+}
+
+impl LightClientConfig {
+    pub fn mainnet() -> Self {
+        let fork = |epoch, version: u8, layout| ForkVersion {
+            epoch,
+            version: [version, 0x00, 0x00, 0x00],
+            layout,
+        };
+        LightClientConfig {
+            genesis_validators_root: [
+                0x4b, 0x36, 0x3d, 0xb9, 0x4e, 0x28, 0x61, 0x20, 0xd7, 0x6e, 0xb9, 0x05, 0x34, 0x0f,
+                0xdd, 0x4e, 0x54, 0xbf, 0xe9, 0xf0, 0x6b, 0xf3, 0x3f, 0xf6, 0xcf, 0x5a, 0xd2, 0x7f,
+                0x51, 0x1b, 0xfe, 0x95,
+            ],
+            forks: vec![
+                fork(74240, 0x01, BeaconFork::Altair),
+                fork(144896, 0x02, BeaconFork::Bellatrix),
+                fork(194048, 0x03, BeaconFork::Capella),
+                fork(269568, 0x04, BeaconFork::Deneb),
+                fork(364032, 0x05, BeaconFork::Electra),
+                // fulu keeps the electra state and payload layouts
+                fork(411392, 0x06, BeaconFork::Electra),
+            ],
+        }
+    }
+
+    /// Commitment to the validators root and the fork schedule: sha256 over the root followed by
+    /// each fork's epoch (little endian), version and layout.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.genesis_validators_root);
+        for fork in &self.forks {
+            hasher.update(fork.epoch.to_le_bytes());
+            hasher.update(fork.version);
+            hasher.update([fork.layout as u8]);
+        }
+        hasher.finalize().into()
+    }
+
+    fn fork_at(&self, slot: u64) -> LightClientResult<&ForkVersion> {
+        let epoch = slot / SLOTS_PER_EPOCH;
+        self.forks
+            .iter()
+            .rev()
+            .find(|fork| fork.epoch <= epoch)
+            .ok_or(LightClientError::UnknownFork { slot })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct BeaconBlockHeader {
+    pub slot: u64,
+    pub proposer_index: u64,
+    pub parent_root: [u8; 32],
+    pub state_root: [u8; 32],
+    pub body_root: [u8; 32],
+}
+
+impl BeaconBlockHeader {
+    /// SSZ root of the header, which is also the beacon block root.
+    pub fn hash_tree_root(&self) -> [u8; 32] {
+        BeaconVerifier::merkleize(vec![
+            uint_chunk(self.slot),
+            uint_chunk(self.proposer_index),
+            self.parent_root,
+            self.state_root,
+            self.body_root,
+        ])
+    }
+}
+
+/// Compressed BLS public keys of the 512 members of a sync committee.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SyncCommittee {
+    pub pubkeys: Vec<Vec<u8>>,
+    pub aggregate_pubkey: Vec<u8>,
+}
+
+impl SyncCommittee {
+    fn pubkey_root(pubkey: &[u8]) -> LightClientResult<[u8; 32]> {
+        if pubkey.len() != 48 {
+            return Err(LightClientError::InvalidPubkey);
+        }
+        let mut padded = [0u8; 64];
+        padded[..48].copy_from_slice(pubkey);
+        Ok(BeaconVerifier::sha256_pair(&padded[..32], &padded[32..]))
+    }
+
+    pub fn hash_tree_root(&self) -> LightClientResult<[u8; 32]> {
+        if self.pubkeys.len() != SYNC_COMMITTEE_SIZE {
+            return Err(LightClientError::InvalidCommittee {
+                size: self.pubkeys.len(),
+            });
+        }
+        let roots = self
+            .pubkeys
+            .iter()
+            .map(|pubkey| Self::pubkey_root(pubkey))
+            .collect::<LightClientResult<Vec<_>>>()?;
+        Ok(BeaconVerifier::sha256_pair(
+            &BeaconVerifier::merkleize(roots),
+            &Self::pubkey_root(&self.aggregate_pubkey)?,
+        ))
+    }
+}
+
+/// Sync committee signature over an attested header that finalizes an older one.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LightClientUpdate {
+    pub attested_header: BeaconBlockHeader,
+    /// the committee of the attested header's next period, with its branch in the attested state
+    pub next_sync_committee: Option<SyncCommittee>,
+    pub next_sync_committee_branch: Vec<Vec<u8>>,
+    pub finalized_header: BeaconBlockHeader,
+    /// finalized header root in the attested state
+    pub finality_branch: Vec<Vec<u8>>,
+    /// execution payload header of the finalized block, with its branch from the body root;
+    /// updates that only carry the committees forward may leave it out
+    pub finalized_execution: Option<ExecutionPayloadHeader>,
+    pub execution_branch: Vec<Vec<u8>>,
+    /// one bit per committee member, least significant bit first
+    pub sync_committee_bits: Vec<u8>,
+    pub sync_committee_signature: Vec<u8>,
+    pub signature_slot: u64,
+}
+
+/// Trusted starting point: the committee of one sync period.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LightClientBootstrap {
+    pub period: u64,
+    pub current_sync_committee: SyncCommittee,
+}
+
+/// Updates followed from a bootstrap up to the finalized block behind `header`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LightClientInputs {
+    pub config: LightClientConfig,
+    pub bootstrap: LightClientBootstrap,
+    pub updates: Vec<LightClientUpdate>,
+    pub header: LeanHeader,
+}
+
+#[derive(Debug)]
+pub struct LightClientOutput {
+    /// `LightClientConfig::hash` of the config the signatures were checked under
+    pub config_hash: Vec<u8>,
+    /// root of the bootstrap committee everything is trusted from
+    pub bootstrap_committee_root: Vec<u8>,
+    pub bootstrap_period: u64,
+    pub finalized_slot: u64,
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LightClientError {
+    Beacon(BeaconError),
+    /// the committee does not have 512 members
+    InvalidCommittee {
+        size: usize,
+    },
+    /// a public key that is not a compressed G1 point
+    InvalidPubkey,
+    /// a signature that is not a compressed G2 point
+    InvalidSignature,
+    /// the aggregate signature does not verify
+    SignatureMismatch,
+    /// fewer than two thirds of the committee signed
+    InsufficientParticipation {
+        participants: usize,
+    },
+    /// no committee is known for the signature's period
+    UnknownCommittee {
+        period: u64,
+    },
+    /// no fork is configured for the slot
+    UnknownFork {
+        slot: u64,
+    },
+    /// signature, attested and finalized slots out of order
+    InvalidSlots,
+    /// no update finalized a block
+    NotFinalized,
+    /// the finalized execution block is not the given header
+    BlockHashMismatch,
+    /// the config is not the one the caller trusts
+    ConfigMismatch,
+}
+
+impl fmt::Display for LightClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LightClientError::Beacon(e) => write!(f, "beacon proof failed: {}", e),
+            LightClientError::InvalidCommittee { size } => {
+                write!(f, "sync committee of {} members", size)
+            }
+            LightClientError::InvalidPubkey => write!(f, "invalid bls public key"),
+            LightClientError::InvalidSignature => write!(f, "invalid bls signature"),
+            LightClientError::SignatureMismatch => write!(f, "sync committee signature mismatch"),
+            LightClientError::InsufficientParticipation { participants } => {
+                write!(f, "only {} sync committee members signed", participants)
+            }
+            LightClientError::UnknownCommittee { period } => {
+                write!(f, "no sync committee known for period {}", period)
+            }
+            LightClientError::UnknownFork { slot } => {
+                write!(f, "no fork configured at slot {}", slot)
+            }
+            LightClientError::InvalidSlots => write!(f, "update slots out of order"),
+            LightClientError::NotFinalized => write!(f, "no finalized header"),
+            LightClientError::BlockHashMismatch => {
+                write!(f, "finalized block hash does not match the header")
+            }
+            LightClientError::ConfigMismatch => write!(f, "untrusted light client config"),
+        }
+    }
+}
+
+impl From<BeaconError> for LightClientError {
+    fn from(e: BeaconError) -> Self {
+        LightClientError::Beacon(e)
+    }
+}
+
+pub type LightClientResult<T> = Result<T, LightClientError>;
+
+/// Committees and finalized block as followed through updates.
+pub struct LightClientStore {
+    pub config: LightClientConfig,
+    pub period: u64,
+    pub current_sync_committee: SyncCommittee,
+    pub next_sync_committee: Option<SyncCommittee>,
+    pub finalized_slot: u64,
+    pub finalized_block_hash: Option<[u8; 32]>,
+}
+
+impl LightClientStore {
+    pub fn new(config: LightClientConfig, bootstrap: LightClientBootstrap) -> Self {
+        LightClientStore {
+            config,
+            period: bootstrap.period,
+            current_sync_committee: bootstrap.current_sync_committee,
+            next_sync_committee: None,
+            finalized_slot: 0,
+            finalized_block_hash: None,
+        }
+    }
+
+    pub fn period_at(slot: u64) -> u64 {
+        slot / (SLOTS_PER_EPOCH * EPOCHS_PER_SYNC_COMMITTEE_PERIOD)
+    }
+
+    /// Checks the committee signed `object_root` with the members set in `bits`.
+    fn verify_signature(
+        &self,
+        committee: &SyncCommittee,
+        bits: &[u8],
+        signature: &[u8],
+        object_root: &[u8; 32],
+        signature_slot: u64,
+    ) -> LightClientResult<()> {
+        if bits.len() != SYNC_COMMITTEE_SIZE / 8 {
+            return Err(LightClientError::InvalidCommittee {
+                size: bits.len() * 8,
+            });
+        }
+
+        let mut aggregate = G1Projective::identity();
+        let mut participants = 0;
+        for (i, pubkey) in committee.pubkeys.iter().enumerate() {
+            if bits[i / 8] >> (i % 8) & 1 == 0 {
+                continue;
+            }
+            let bytes: &[u8; 48] = pubkey
+                .as_slice()
+                .try_into()
+                .map_err(|_| LightClientError::InvalidPubkey)?;
+            let point = Option::<G1Affine>::from(G1Affine::from_compressed(bytes))
+                .ok_or(LightClientError::InvalidPubkey)?;
+            aggregate += point;
+            participants += 1;
+        }
+        if participants * 3 < SYNC_COMMITTEE_SIZE * 2 {
+            return Err(LightClientError::InsufficientParticipation { participants });
+        }
+
+        // the committee signs with the fork of the slot before the signature slot
+        let fork = self.config.fork_at(signature_slot.saturating_sub(1))?;
+        let mut fork_version = [0u8; 32];
+        fork_version[..4].copy_from_slice(&fork.version);
+        let fork_data_root =
+            BeaconVerifier::sha256_pair(&fork_version, &self.config.genesis_validators_root);
+        let mut domain = [0u8; 32];
+        domain[..4].copy_from_slice(&DOMAIN_SYNC_COMMITTEE);
+        domain[4..].copy_from_slice(&fork_data_root[..28]);
+        let signing_root = BeaconVerifier::sha256_pair(object_root, &domain);
+
+        let signature: &[u8; 96] = signature
+            .try_into()
+            .map_err(|_| LightClientError::InvalidSignature)?;
+        let signature = Option::<G2Affine>::from(G2Affine::from_compressed(signature))
+            .ok_or(LightClientError::InvalidSignature)?;
+        let message = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(
+            signing_root,
+            BLS_DST,
+        );
+
+        // e(pk, H(m)) == e(g1, sig)
+        if pairing(&G1Affine::from(aggregate), &G2Affine::from(message))
+            != pairing(&G1Affine::generator(), &signature)
+        {
+            return Err(LightClientError::SignatureMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verifies an update and moves the finalized block and committees forward.
+    pub fn apply_update(&mut self, update: &LightClientUpdate) -> LightClientResult<()> {
+        let attested = &update.attested_header;
+        let finalized = &update.finalized_header;
+        if update.signature_slot <= attested.slot || attested.slot < finalized.slot {
+            return Err(LightClientError::InvalidSlots);
+        }
+
+        let signature_period = Self::period_at(update.signature_slot);
+        let committee = if signature_period == self.period {
+            &self.current_sync_committee
+        } else if signature_period == self.period + 1 {
+            self.next_sync_committee
+                .as_ref()
+                .ok_or(LightClientError::UnknownCommittee {
+                    period: signature_period,
+                })?
+        } else {
+            return Err(LightClientError::UnknownCommittee {
+                period: signature_period,
+            });
+        };
+        let attested_root = attested.hash_tree_root();
+        self.verify_signature(
+            committee,
+            &update.sync_committee_bits,
+            &update.sync_committee_signature,
+            &attested_root,
+            update.signature_slot,
+        )?;
+
+        let layout = self.config.fork_at(attested.slot)?.layout;
+        BeaconVerifier::verify_branch(
+            &attested.state_root,
+            &finalized.hash_tree_root(),
+            &update.finality_branch,
+            layout.state_gindex(STATE_FINALIZED_CHECKPOINT_FIELD) * 2 + 1,
+        )?;
+        // the payload header is laid out by the finalized block's fork, altair and bellatrix
+        // headers are rejected since their light client updates carry no execution branch
+        if let Some(execution) = &update.finalized_execution {
+            let finalized_layout = self.config.fork_at(finalized.slot)?.layout;
+            BeaconVerifier::verify_branch(
+                &finalized.body_root,
+                &execution.hash_tree_root(finalized_layout)?,
+                &update.execution_branch,
+                BODY_EXECUTION_PAYLOAD_GINDEX,
+            )?;
+        }
+
+        if let Some(next) = &update.next_sync_committee {
+            BeaconVerifier::verify_branch(
+                &attested.state_root,
+                &next.hash_tree_root()?,
+                &update.next_sync_committee_branch,
+                layout.state_gindex(STATE_NEXT_SYNC_COMMITTEE_FIELD),
+            )?;
+            if Self::period_at(attested.slot) == self.period && self.next_sync_committee.is_none() {
+                self.next_sync_committee = Some(next.clone());
+            }
+        }
+
+        if finalized.slot > self.finalized_slot {
+            // finalizing into the next period hands over to the next committee
+            if Self::period_at(finalized.slot) == self.period + 1 {
+                let next =
+                    self.next_sync_committee
+                        .take()
+                        .ok_or(LightClientError::UnknownCommittee {
+                            period: self.period + 1,
+                        })?;
+                self.current_sync_committee = next;
+                self.period += 1;
+            }
+            self.finalized_slot = finalized.slot;
+            self.finalized_block_hash = None;
+        }
+        // a later update may prove the payload of a block finalized without one
+        if finalized.slot == self.finalized_slot {
+            if let Some(execution) = &update.finalized_execution {
+                self.finalized_block_hash = Some(execution.block_hash);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct LightClientVerifier;
+
+impl LightClientVerifier {
+    /// Follows the updates from the bootstrap committee and checks the last finalized
+    /// execution block is `header`.
+    ///
+    /// The config comes with the inputs, it must hash to `trusted_config`, for instance
+    /// `LightClientConfig::mainnet().hash()`.
+    pub fn verify(
+        trusted_config: &[u8; 32],
+        input: LightClientInputs,
+    ) -> LightClientResult<LightClientOutput> {
+        let config_hash = input.config.hash();
+        if &config_hash != trusted_config {
+            return Err(LightClientError::ConfigMismatch);
+        }
+        let bootstrap_committee_root = input.bootstrap.current_sync_committee.hash_tree_root()?;
+        let bootstrap_period = input.bootstrap.period;
+
+        let mut store = LightClientStore::new(input.config, input.bootstrap);
+        for update in &input.updates {
+            store.apply_update(update)?;
+        }
+
+        let finalized_block_hash = store
+            .finalized_block_hash
+            .ok_or(LightClientError::NotFinalized)?;
+        let block_hash = input.header.hash();
+        if block_hash != finalized_block_hash {
+            return Err(LightClientError::BlockHashMismatch);
+        }
+
+        Ok(LightClientOutput {
+            config_hash: config_hash.to_vec(),
+            bootstrap_committee_root: bootstrap_committee_root.to_vec(),
+            bootstrap_period,
+            finalized_slot: store.finalized_slot,
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+        })
+    }
+}
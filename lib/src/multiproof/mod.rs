@@ -0,0 +1,148 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::decoder::NodeDecoder;
+use crate::header::LeanHeader;
+use crate::verifier::{
+    AccountOutput, MPTVerifier, MultiAccountOutput, Node, NodeSource, VerifierError,
+    VerifyResultWithData, Walk,
+};
+
+/// Trie nodes shared by several proofs at the same block, each stored once.
+///
+/// Keys walked through the same upper nodes decode and hash them a single time.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct MultiProof {
+    pub nodes: Vec<Vec<u8>>,
+}
+
+impl MultiProof {
+    /// Merges several path proofs, dropping repeated nodes.
+    pub fn from_proofs<'a>(proofs: impl IntoIterator<Item = &'a Vec<Vec<u8>>>) -> Self {
+        let mut seen: BTreeSet<&[u8]> = BTreeSet::new();
+        let mut nodes = Vec::new();
+        for proof in proofs {
+            for node in proof {
+                if seen.insert(node) {
+                    nodes.push(node.clone());
+                }
+            }
+        }
+        MultiProof { nodes }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MultiProofAccount {
+    pub address: Vec<u8>,
+    pub slots: Vec<Vec<u8>>,
+}
+
+/// Accounts and slots proven against one header out of a shared node set.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MultiProofInputs {
+    pub header: LeanHeader,
+    pub accounts: Vec<MultiProofAccount>,
+    pub proof: MultiProof,
+}
+
+/// Decoded multiproof nodes keyed by their hash.
+struct NodeSet<'a> {
+    nodes: BTreeMap<Vec<u8>, Node<'a>>,
+    visited: BTreeSet<Vec<u8>>,
+    duplicates: usize,
+}
+
+impl<'a> NodeSet<'a> {
+    fn new(proof: &'a MultiProof) -> VerifyResultWithData<Self> {
+        let mut nodes = BTreeMap::new();
+        let mut duplicates = 0;
+        for raw in &proof.nodes {
+            let hash = MPTVerifier::keccak(raw);
+            if nodes.contains_key(&hash) {
+                duplicates += 1;
+                continue;
+            }
+            nodes.insert(hash, NodeDecoder::decode_mpt_node(raw)?);
+        }
+        Ok(NodeSet {
+            nodes,
+            visited: BTreeSet::new(),
+            duplicates,
+        })
+    }
+
+    /// Every node must have been used by some path.
+    fn finish(self) -> VerifyResultWithData<()> {
+        let count = self.nodes.len() - self.visited.len() + self.duplicates;
+        if count > 0 {
+            return Err(VerifierError::LeftoverNodes { count });
+        }
+        Ok(())
+    }
+}
+
+impl<'a> NodeSource<'a> for NodeSet<'a> {
+    fn resolve(&mut self, hash: &[u8], depth: usize) -> VerifyResultWithData<&Node<'a>> {
+        // nodes are keyed by their own hash, a missing key means the proof lacks the node
+        let node = self
+            .nodes
+            .get(hash)
+            .ok_or(VerifierError::TruncatedProof { depth })?;
+        if !self.visited.contains(hash) {
+            self.visited.insert(hash.to_vec());
+        }
+        Ok(node)
+    }
+
+    fn skip_inline(&mut self, raw: &[u8]) {
+        let hash = MPTVerifier::keccak(raw);
+        if self.nodes.contains_key(&hash) {
+            self.visited.insert(hash);
+        }
+    }
+}
+
+impl MPTVerifier {
+    /// Verifies every account and slot in `input` against the header's state root, walking
+    /// all paths over the shared node set.
+    pub fn verify_multiproof(input: MultiProofInputs) -> VerifyResultWithData<MultiAccountOutput> {
+        let block_hash = input.header.hash();
+        let mut nodes = NodeSet::new(&input.proof)?;
+
+        let mut accounts = Vec::with_capacity(input.accounts.len());
+        for account in input.accounts {
+            let address_hash = Self::keccak(&account.address);
+            let encoded = match Self::walk(&input.header.state_root, &address_hash, &mut nodes)? {
+                Walk::Found(value) => value,
+                Walk::Absent { depth } => return Err(VerifierError::PathDivergence { depth }),
+            };
+            let account_state = Self::decode_account_state(encoded)?;
+
+            let mut slots = BTreeMap::new();
+            for slot in account.slots {
+                Self::check_slot(&slot)?;
+                let slot_hash = Self::keccak(&slot);
+                let value = match Self::walk(&account_state.storage_hash, &slot_hash, &mut nodes)? {
+                    Walk::Found(value) => Self::decode_storage_value(value)?,
+                    // unset slots read as zero
+                    Walk::Absent { .. } => Vec::new(),
+                };
+                slots.insert(slot, value);
+            }
+
+            accounts.push(AccountOutput {
+                address: account.address,
+                slots,
+            });
+        }
+
+        nodes.finish()?;
+
+        Ok(MultiAccountOutput {
+            block_hash: block_hash.to_vec(),
+            accounts,
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::header::LeanHeader;
+use crate::verifier::{AccountOutput, MPTVerifier, MultiAccountInputs, VerifierError};
+
+/// `L2ToL1MessagePasser` predeploy, its storage root is part of every output root.
+pub const L2_TO_L1_MESSAGE_PASSER_ADDRESS: [u8; 20] = [
+    0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x16,
+];
+
+/// `L1Block` predeploy, updated with the latest L1 block at the start of every L2 block.
+pub const L1_BLOCK_ADDRESS: [u8; 20] = [
+    0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x15,
+];
+
+/// storage slot of `L1Block.hash`
+pub const L1_BLOCK_HASH_SLOT: u64 = 2;
+
+/// the only output root version in use
+pub const OUTPUT_VERSION_V0: [u8; 32] = [0u8; 32];
+
+/// An L2 header and the message passer account in its state.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OutputRootInputs {
+    pub header: LeanHeader,
+    pub message_passer_proof: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct OutputRootOutput {
+    pub output_root: Vec<u8>,
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    pub state_root: Vec<u8>,
+    pub message_passer_storage_root: Vec<u8>,
+}
+
+/// L1 accounts proven under the L1 block an L2 header's `L1Block` predeploy points at.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct L1StateInputs {
+    pub l2_header: LeanHeader,
+    pub l1_block_account_proof: Vec<Vec<u8>>,
+    pub l1_block_hash_proof: Vec<Vec<u8>>,
+    /// the L1 header and the accounts and slots to prove under it
+    pub l1: MultiAccountInputs,
+}
+
+#[derive(Debug)]
+pub struct L1StateOutput {
+    pub l2_block_hash: Vec<u8>,
+    pub l2_block_number: u64,
+    pub l1_block_hash: Vec<u8>,
+    pub l1_block_number: u64,
+    /// one entry per L1 account, in input order
+    pub accounts: Vec<AccountOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OpStackError {
+    Verifier(VerifierError),
+    /// the recomputed output root differs from the one posted on L1
+    OutputRootMismatch,
+    /// the L1 header is not the block stored in the `L1Block` predeploy
+    L1HashMismatch,
+}
+
+impl fmt::Display for OpStackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpStackError::Verifier(e) => write!(f, "state proof failed: {}", e),
+            OpStackError::OutputRootMismatch => write!(f, "output root mismatch"),
+            OpStackError::L1HashMismatch => {
+                write!(f, "l1 header does not match the L1Block hash")
+            }
+        }
+    }
+}
+
+impl From<VerifierError> for OpStackError {
+    fn from(e: VerifierError) -> Self {
+        OpStackError::Verifier(e)
+    }
+}
+
+pub type OpStackResult<T> = Result<T, OpStackError>;
+
+pub struct OpStackVerifier;
+
+impl OpStackVerifier {
+    /// `keccak(version ‖ state_root ‖ message_passer_storage_root ‖ block_hash)`
+    pub fn output_root(
+        state_root: &[u8],
+        message_passer_storage_root: &[u8],
+        block_hash: &[u8],
+    ) -> Vec<u8> {
+        let mut preimage = Vec::with_capacity(128);
+        preimage.extend_from_slice(&OUTPUT_VERSION_V0);
+        preimage.extend_from_slice(state_root);
+        preimage.extend_from_slice(message_passer_storage_root);
+        preimage.extend_from_slice(block_hash);
+        MPTVerifier::keccak(&preimage)
+    }
+
+    /// Recomputes the output root of the header's block.
+    pub fn verify(input: OutputRootInputs) -> OpStackResult<OutputRootOutput> {
+        let block_hash = input.header.hash();
+        let message_passer = MPTVerifier::verify_and_get_account_state(
+            &input.header.state_root,
+            L2_TO_L1_MESSAGE_PASSER_ADDRESS.to_vec(),
+            MPTVerifier::decode_proof(&input.message_passer_proof)?,
+        )?;
+
+        Ok(OutputRootOutput {
+            output_root: Self::output_root(
+                &input.header.state_root,
+                &message_passer.storage_hash,
+                &block_hash,
+            ),
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+            state_root: input.header.state_root.to_vec(),
+            message_passer_storage_root: message_passer.storage_hash,
+        })
+    }
+
+    /// Checks the header's block against an output root posted on L1.
+    pub fn verify_output_root(
+        input: OutputRootInputs,
+        output_root: &[u8],
+    ) -> OpStackResult<OutputRootOutput> {
+        let output = Self::verify(input)?;
+        if output.output_root != output_root {
+            return Err(OpStackError::OutputRootMismatch);
+        }
+        Ok(output)
+    }
+
+    /// Reads the L1 block hash stored in the `L1Block` predeploy at the L2 header's block.
+    pub fn verify_l1_block_hash(
+        l2_header: &LeanHeader,
+        account_proof: &[Vec<u8>],
+        hash_proof: &[Vec<u8>],
+    ) -> OpStackResult<[u8; 32]> {
+        let l1_block = MPTVerifier::verify_and_get_account_state(
+            &l2_header.state_root,
+            L1_BLOCK_ADDRESS.to_vec(),
+            MPTVerifier::decode_proof(account_proof)?,
+        )?;
+        let mut slot = vec![0u8; 32];
+        slot[24..].copy_from_slice(&L1_BLOCK_HASH_SLOT.to_be_bytes());
+        let stored = MPTVerifier::verify_storage_slot(
+            &l1_block.storage_hash,
+            slot,
+            MPTVerifier::decode_proof(hash_proof)?,
+        )?
+        .unwrap_or_default();
+
+        // storage values drop leading zero bytes
+        if stored.len() > 32 {
+            return Err(OpStackError::L1HashMismatch);
+        }
+        let mut hash = [0u8; 32];
+        hash[32 - stored.len()..].copy_from_slice(&stored);
+        Ok(hash)
+    }
+
+    /// Proves L1 accounts and slots from an L2 header, through the L1 block its `L1Block`
+    /// predeploy stores.
+    pub fn verify_l1_state(input: L1StateInputs) -> OpStackResult<L1StateOutput> {
+        let l1_hash = Self::verify_l1_block_hash(
+            &input.l2_header,
+            &input.l1_block_account_proof,
+            &input.l1_block_hash_proof,
+        )?;
+        let l1_block_number = input.l1.header.number;
+        let l1 = MPTVerifier::verify_accounts(input.l1)?;
+        if l1.block_hash != l1_hash {
+            return Err(OpStackError::L1HashMismatch);
+        }
+
+        Ok(L1StateOutput {
+            l2_block_hash: input.l2_header.hash().to_vec(),
+            l2_block_number: input.l2_header.number,
+            l1_block_hash: l1.block_hash,
+            l1_block_number,
+            accounts: l1.accounts,
+        })
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt;
+
+use alloy_primitives::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::header::{HeaderError, HeaderVerifier, LeanHeader};
+
+/// Header fields the oracle can commit, the discriminant is the id committed with them.
+#[repr(u8)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum HeaderField {
+    Number = 0,
+    Timestamp = 1,
+    BaseFee = 2,
+    GasLimit = 3,
+    GasUsed = 4,
+    /// `mix_hash`, the beacon chain's randao reveal after the merge
+    PrevRandao = 5,
+    Beneficiary = 6,
+    StateRoot = 7,
+    BlobGasUsed = 8,
+    ExcessBlobGas = 9,
+    ParentBeaconBlockRoot = 10,
+}
+
+impl fmt::Display for HeaderField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Aggregation {
+    /// rounded down
+    Average = 0,
+    Sum = 1,
+    Min = 2,
+    Max = 3,
+}
+
+/// Fields to read from one header.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FieldsInputs {
+    pub header: LeanHeader,
+    pub fields: Vec<HeaderField>,
+}
+
+#[derive(Debug)]
+pub struct FieldsOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    pub fields: Vec<HeaderField>,
+    /// one 32 byte word per field, integers big endian and addresses left padded
+    pub values: Vec<[u8; 32]>,
+}
+
+/// A numeric field aggregated over a header run, ordered oldest to newest.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AggregateInputs {
+    pub headers: Vec<LeanHeader>,
+    pub field: HeaderField,
+    pub aggregation: Aggregation,
+}
+
+#[derive(Debug)]
+pub struct AggregateOutput {
+    pub first_hash: Vec<u8>,
+    pub first_number: u64,
+    pub last_hash: Vec<u8>,
+    pub last_number: u64,
+    pub field: HeaderField,
+    pub aggregation: Aggregation,
+    pub value: U256,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OracleError {
+    Header(HeaderError),
+    /// the header predates the fork that added the field
+    MissingField {
+        field: HeaderField,
+        number: u64,
+    },
+    /// the field is a hash or address and cannot be aggregated
+    NotNumeric {
+        field: HeaderField,
+    },
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OracleError::Header(e) => write!(f, "header run failed: {}", e),
+            OracleError::MissingField { field, number } => {
+                write!(f, "block {} has no {} field", number, field)
+            }
+            OracleError::NotNumeric { field } => write!(f, "{} is not numeric", field),
+        }
+    }
+}
+
+impl From<HeaderError> for OracleError {
+    fn from(e: HeaderError) -> Self {
+        OracleError::Header(e)
+    }
+}
+
+pub type OracleResult<T> = Result<T, OracleError>;
+
+pub struct OracleVerifier;
+
+impl OracleVerifier {
+    fn numeric(header: &LeanHeader, field: HeaderField) -> OracleResult<Option<u64>> {
+        Ok(match field {
+            HeaderField::Number => Some(header.number),
+            HeaderField::Timestamp => Some(header.timestamp),
+            HeaderField::BaseFee => header.base_fee_per_gas,
+            HeaderField::GasLimit => Some(header.gas_limit),
+            HeaderField::GasUsed => Some(header.gas_used),
+            HeaderField::BlobGasUsed => header.blob_gas_used,
+            HeaderField::ExcessBlobGas => header.excess_blob_gas,
+            HeaderField::PrevRandao
+            | HeaderField::Beneficiary
+            | HeaderField::StateRoot
+            | HeaderField::ParentBeaconBlockRoot => return Err(OracleError::NotNumeric { field }),
+        })
+    }
+
+    /// Value of a field as a 32 byte word.
+    pub fn field_word(header: &LeanHeader, field: HeaderField) -> OracleResult<[u8; 32]> {
+        let missing = OracleError::MissingField {
+            field,
+            number: header.number,
+        };
+        let mut word = [0u8; 32];
+        match field {
+            HeaderField::PrevRandao => word = header.mix_hash,
+            HeaderField::Beneficiary => word[12..].copy_from_slice(&header.beneficiary),
+            HeaderField::StateRoot => word = header.state_root,
+            HeaderField::ParentBeaconBlockRoot => {
+                word = header.parent_beacon_block_root.ok_or(missing)?
+            }
+            _ => {
+                let value = Self::numeric(header, field)?.ok_or(missing)?;
+                word[24..].copy_from_slice(&value.to_be_bytes());
+            }
+        }
+        Ok(word)
+    }
+
+    /// Hashes the header and reads the requested fields from it.
+    pub fn verify_fields(input: FieldsInputs) -> OracleResult<FieldsOutput> {
+        let values = input
+            .fields
+            .iter()
+            .map(|&field| Self::field_word(&input.header, field))
+            .collect::<OracleResult<Vec<_>>>()?;
+
+        Ok(FieldsOutput {
+            block_hash: input.header.hash().to_vec(),
+            block_number: input.header.number,
+            fields: input.fields,
+            values,
+        })
+    }
+
+    /// Links the header run through parent hashes and aggregates a numeric field over it.
+    pub fn verify_aggregate(input: AggregateInputs) -> OracleResult<AggregateOutput> {
+        let ancestry = HeaderVerifier::verify_ancestry(&input.headers)?;
+
+        let mut sum = U256::ZERO;
+        let mut min = u64::MAX;
+        let mut max = 0;
+        for header in &input.headers {
+            let value = Self::numeric(header, input.field)?.ok_or(OracleError::MissingField {
+                field: input.field,
+                number: header.number,
+            })?;
+            sum += U256::from(value);
+            min = min.min(value);
+            max = max.max(value);
+        }
+
+        let value = match input.aggregation {
+            Aggregation::Average => sum / U256::from(input.headers.len()),
+            Aggregation::Sum => sum,
+            Aggregation::Min => U256::from(min),
+            Aggregation::Max => U256::from(max),
+        };
+
+        Ok(AggregateOutput {
+            first_hash: ancestry.target_hash.to_vec(),
+            first_number: ancestry.target_number,
+            last_hash: ancestry.anchor_hash.to_vec(),
+            last_number: ancestry.anchor_number,
+            field: input.field,
+            aggregation: input.aggregation,
+            value,
+        })
+    }
+}
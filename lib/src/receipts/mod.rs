@@ -0,0 +1,288 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::decoder::{NodeDecoder, RlpError};
+use crate::header::LeanHeader;
+use crate::verifier::{MPTVerifier, VerifierError};
+
+/// EIP-2718 envelope type shared by transactions and their receipts.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum TxType {
+    Legacy,
+    /// EIP-2930
+    AccessList,
+    /// EIP-1559
+    DynamicFee,
+    /// EIP-4844
+    Blob,
+    /// EIP-7702
+    SetCode,
+    /// op stack L1 to L2 deposit
+    Deposit,
+}
+
+impl TxType {
+    pub fn from_byte(byte: u8) -> Option<TxType> {
+        match byte {
+            0x00 => Some(TxType::Legacy),
+            0x01 => Some(TxType::AccessList),
+            0x02 => Some(TxType::DynamicFee),
+            0x03 => Some(TxType::Blob),
+            0x04 => Some(TxType::SetCode),
+            0x7e => Some(TxType::Deposit),
+            _ => None,
+        }
+    }
+
+    /// Splits an envelope into its type and the rlp list behind the type byte.
+    pub fn split_envelope(encoded: &[u8]) -> Result<(TxType, &[u8]), EnvelopeError> {
+        let first = *encoded.first().ok_or(EnvelopeError::Empty)?;
+        // legacy payloads are bare rlp lists
+        if first >= 0xc0 {
+            return Ok((TxType::Legacy, encoded));
+        }
+        match TxType::from_byte(first) {
+            Some(TxType::Legacy) | None => Err(EnvelopeError::UnknownType { ty: first }),
+            Some(ty) => Ok((ty, &encoded[1..])),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    Empty,
+    /// type byte outside the known envelope types
+    UnknownType {
+        ty: u8,
+    },
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Empty => write!(f, "empty envelope"),
+            EnvelopeError::UnknownType { ty } => write!(f, "unknown envelope type {:#x}", ty),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Log {
+    pub address: Vec<u8>,
+    pub topics: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Receipt {
+    pub tx_type: TxType,
+    /// true when the transaction succeeded
+    pub status: bool,
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: Vec<u8>,
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    /// Logs emitted by `address` whose first topic is `topic0`, the event signature hash.
+    pub fn logs_matching<'a>(
+        &'a self,
+        address: &'a [u8],
+        topic0: &'a [u8],
+    ) -> impl Iterator<Item = &'a Log> {
+        self.logs.iter().filter(move |log| {
+            log.address == address && log.topics.first().map(Vec::as_slice) == Some(topic0)
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReceiptProof {
+    /// index of the transaction in the block
+    pub index: u64,
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// Receipts proven against one header's receipts root.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReceiptInputs {
+    pub header: LeanHeader,
+    pub receipts: Vec<ReceiptProof>,
+}
+
+#[derive(Debug)]
+pub struct ReceiptOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    /// every proven receipt by transaction index
+    pub receipts: BTreeMap<u64, Receipt>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReceiptError {
+    Verifier(VerifierError),
+    Decode(RlpError),
+    Envelope(EnvelopeError),
+    /// receipt list with the wrong number of fields for its type
+    InvalidReceipt {
+        items: usize,
+    },
+    /// status that is neither empty nor 1, pre-byzantium state roots are not supported
+    InvalidStatus {
+        length: usize,
+    },
+    /// log that is not a list of address, topics and data
+    InvalidLog {
+        items: usize,
+    },
+    /// address, topic or bloom of the wrong length
+    InvalidFieldLength {
+        length: usize,
+    },
+}
+
+impl fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptError::Verifier(e) => write!(f, "receipt proof failed: {}", e),
+            ReceiptError::Decode(e) => write!(f, "invalid receipt rlp: {}", e),
+            ReceiptError::Envelope(e) => write!(f, "invalid receipt envelope: {}", e),
+            ReceiptError::InvalidReceipt { items } => {
+                write!(f, "receipt with {} fields", items)
+            }
+            ReceiptError::InvalidStatus { length } => {
+                write!(f, "receipt status of {} bytes", length)
+            }
+            ReceiptError::InvalidLog { items } => write!(f, "log with {} fields", items),
+            ReceiptError::InvalidFieldLength { length } => {
+                write!(f, "fixed size field of {} bytes", length)
+            }
+        }
+    }
+}
+
+impl From<VerifierError> for ReceiptError {
+    fn from(e: VerifierError) -> Self {
+        ReceiptError::Verifier(e)
+    }
+}
+
+impl From<RlpError> for ReceiptError {
+    fn from(e: RlpError) -> Self {
+        ReceiptError::Decode(e)
+    }
+}
+
+impl From<EnvelopeError> for ReceiptError {
+    fn from(e: EnvelopeError) -> Self {
+        ReceiptError::Envelope(e)
+    }
+}
+
+pub type ReceiptResult<T> = Result<T, ReceiptError>;
+
+pub struct ReceiptVerifier;
+
+impl ReceiptVerifier {
+    fn fixed_bytes(raw: &[u8], length: usize) -> ReceiptResult<Vec<u8>> {
+        let bytes = NodeDecoder::decode_bytes(raw)?;
+        if bytes.len() != length {
+            return Err(ReceiptError::InvalidFieldLength {
+                length: bytes.len(),
+            });
+        }
+        Ok(bytes.to_vec())
+    }
+
+    fn decode_log(raw: &[u8]) -> ReceiptResult<Log> {
+        let fields = NodeDecoder::decode_list_raw(raw)?;
+        if fields.len() != 3 {
+            return Err(ReceiptError::InvalidLog {
+                items: fields.len(),
+            });
+        }
+
+        let mut topics = Vec::new();
+        for topic in NodeDecoder::list_items(fields[1])? {
+            topics.push(Self::fixed_bytes(topic?, 32)?);
+        }
+
+        Ok(Log {
+            address: Self::fixed_bytes(fields[0], 20)?,
+            topics,
+            data: NodeDecoder::decode_bytes(fields[2])?.to_vec(),
+        })
+    }
+
+    /// Decodes a receipt as stored in the receipts trie, legacy or typed.
+    pub fn decode_receipt(encoded: &[u8]) -> ReceiptResult<Receipt> {
+        let (tx_type, payload) = TxType::split_envelope(encoded)?;
+        let fields = NodeDecoder::decode_list_raw(payload)?;
+        // deposit receipts may append the deposit nonce and receipt version
+        let valid = match tx_type {
+            TxType::Deposit => (4..=6).contains(&fields.len()),
+            _ => fields.len() == 4,
+        };
+        if !valid {
+            return Err(ReceiptError::InvalidReceipt {
+                items: fields.len(),
+            });
+        }
+
+        let status = match NodeDecoder::decode_bytes(fields[0])? {
+            [] => false,
+            [1] => true,
+            other => {
+                return Err(ReceiptError::InvalidStatus {
+                    length: other.len(),
+                })
+            }
+        };
+
+        let mut logs = Vec::new();
+        for log in NodeDecoder::list_items(fields[3])? {
+            logs.push(Self::decode_log(log?)?);
+        }
+
+        Ok(Receipt {
+            tx_type,
+            status,
+            cumulative_gas_used: NodeDecoder::decode_u64(fields[1])?,
+            logs_bloom: Self::fixed_bytes(fields[2], 256)?,
+            logs,
+        })
+    }
+
+    /// Proves the receipt of the `index`-th transaction under `receipts_root`.
+    pub fn verify_receipt(
+        receipts_root: &[u8],
+        index: u64,
+        proof: &[Vec<u8>],
+    ) -> ReceiptResult<Receipt> {
+        let encoded = MPTVerifier::verify_indexed_value(
+            receipts_root,
+            index,
+            MPTVerifier::decode_proof(proof)?,
+        )?;
+        Self::decode_receipt(encoded)
+    }
+
+    /// Hashes the header once and proves every receipt against its receipts root.
+    pub fn verify_receipts(input: ReceiptInputs) -> ReceiptResult<ReceiptOutput> {
+        let block_hash = input.header.hash();
+
+        let mut receipts = BTreeMap::new();
+        for receipt in input.receipts {
+            let decoded =
+                Self::verify_receipt(&input.header.receipts_root, receipt.index, &receipt.proof)?;
+            receipts.insert(receipt.index, decoded);
+        }
+
+        Ok(ReceiptOutput {
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+            receipts,
+        })
+    }
+}
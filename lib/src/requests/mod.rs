@@ -0,0 +1,217 @@
+use std::{fmt, slice::ChunksExact};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::header::LeanHeader;
+
+/// Request type bytes, requests must appear in this order.
+pub const DEPOSIT_REQUEST_TYPE: u8 = 0x00;
+pub const WITHDRAWAL_REQUEST_TYPE: u8 = 0x01;
+pub const CONSOLIDATION_REQUEST_TYPE: u8 = 0x02;
+
+const PUBKEY_LENGTH: usize = 48;
+const SIGNATURE_LENGTH: usize = 96;
+const DEPOSIT_LENGTH: usize = PUBKEY_LENGTH + 32 + 8 + SIGNATURE_LENGTH + 8;
+const WITHDRAWAL_LENGTH: usize = 20 + PUBKEY_LENGTH + 8;
+const CONSOLIDATION_LENGTH: usize = 20 + PUBKEY_LENGTH * 2;
+
+/// EIP-6110 deposit picked up from the deposit contract logs.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DepositRequest {
+    pub pubkey: Vec<u8>,
+    pub withdrawal_credentials: Vec<u8>,
+    /// amount in gwei
+    pub amount: u64,
+    pub signature: Vec<u8>,
+    pub index: u64,
+}
+
+/// EIP-7002 withdrawal or exit triggered from the execution layer.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct WithdrawalRequest {
+    pub source_address: Vec<u8>,
+    pub validator_pubkey: Vec<u8>,
+    /// amount in gwei, zero requests a full exit
+    pub amount: u64,
+}
+
+/// EIP-7251 consolidation of one validator into another.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ConsolidationRequest {
+    pub source_address: Vec<u8>,
+    pub source_pubkey: Vec<u8>,
+    pub target_pubkey: Vec<u8>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionRequests {
+    pub deposits: Vec<DepositRequest>,
+    pub withdrawals: Vec<WithdrawalRequest>,
+    pub consolidations: Vec<ConsolidationRequest>,
+}
+
+/// Execution requests of a block, each the type byte followed by the request data.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RequestsInputs {
+    pub header: LeanHeader,
+    pub requests: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct RequestsOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    pub requests: ExecutionRequests,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequestsError {
+    /// the header predates prague and commits to no requests
+    MissingRequestsHash,
+    /// request without a type byte or without data, empty requests are left out of the list
+    EmptyRequest,
+    UnknownType {
+        ty: u8,
+    },
+    /// types must be strictly ascending, one entry per type
+    UnorderedTypes {
+        ty: u8,
+    },
+    /// request data that is not a whole number of requests of its type
+    InvalidLength {
+        ty: u8,
+        length: usize,
+    },
+    /// the requests do not hash to the header's requests hash
+    HashMismatch,
+}
+
+impl fmt::Display for RequestsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestsError::MissingRequestsHash => write!(f, "header has no requests hash"),
+            RequestsError::EmptyRequest => write!(f, "empty execution request"),
+            RequestsError::UnknownType { ty } => write!(f, "unknown request type {:#x}", ty),
+            RequestsError::UnorderedTypes { ty } => {
+                write!(f, "request type {:#x} out of order", ty)
+            }
+            RequestsError::InvalidLength { ty, length } => {
+                write!(f, "request type {:#x} with {} data bytes", ty, length)
+            }
+            RequestsError::HashMismatch => write!(f, "requests do not match the requests hash"),
+        }
+    }
+}
+
+pub type RequestsResult<T> = Result<T, RequestsError>;
+
+pub struct RequestsVerifier;
+
+impl RequestsVerifier {
+    fn read_u64_le(bytes: &[u8]) -> u64 {
+        let mut out = [0u8; 8];
+        out.copy_from_slice(bytes);
+        u64::from_le_bytes(out)
+    }
+
+    /// EIP-7685 commitment: sha256 over the sha256 of every request.
+    pub fn requests_hash(requests: &[Vec<u8>]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for request in requests {
+            hasher.update(Sha256::digest(request));
+        }
+        hasher.finalize().into()
+    }
+
+    /// Splits the data of one request type into fixed size entries.
+    fn entries(ty: u8, data: &[u8], length: usize) -> RequestsResult<ChunksExact<'_, u8>> {
+        let entries = data.chunks_exact(length);
+        if !entries.remainder().is_empty() {
+            return Err(RequestsError::InvalidLength {
+                ty,
+                length: data.len(),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Decodes the requests list, checking the types appear once each and in order.
+    pub fn decode(requests: &[Vec<u8>]) -> RequestsResult<ExecutionRequests> {
+        let mut decoded = ExecutionRequests::default();
+        let mut last: Option<u8> = None;
+
+        for request in requests {
+            let (&ty, data) = request.split_first().ok_or(RequestsError::EmptyRequest)?;
+            if data.is_empty() {
+                return Err(RequestsError::EmptyRequest);
+            }
+            if last.is_some_and(|last| ty <= last) {
+                return Err(RequestsError::UnorderedTypes { ty });
+            }
+            last = Some(ty);
+
+            // ssz containers of fixed size fields, integers are little endian
+            match ty {
+                DEPOSIT_REQUEST_TYPE => {
+                    for entry in Self::entries(ty, data, DEPOSIT_LENGTH)? {
+                        let (pubkey, rest) = entry.split_at(PUBKEY_LENGTH);
+                        let (withdrawal_credentials, rest) = rest.split_at(32);
+                        let (amount, rest) = rest.split_at(8);
+                        let (signature, index) = rest.split_at(SIGNATURE_LENGTH);
+                        decoded.deposits.push(DepositRequest {
+                            pubkey: pubkey.to_vec(),
+                            withdrawal_credentials: withdrawal_credentials.to_vec(),
+                            amount: Self::read_u64_le(amount),
+                            signature: signature.to_vec(),
+                            index: Self::read_u64_le(index),
+                        });
+                    }
+                }
+                WITHDRAWAL_REQUEST_TYPE => {
+                    for entry in Self::entries(ty, data, WITHDRAWAL_LENGTH)? {
+                        let (source_address, rest) = entry.split_at(20);
+                        let (validator_pubkey, amount) = rest.split_at(PUBKEY_LENGTH);
+                        decoded.withdrawals.push(WithdrawalRequest {
+                            source_address: source_address.to_vec(),
+                            validator_pubkey: validator_pubkey.to_vec(),
+                            amount: Self::read_u64_le(amount),
+                        });
+                    }
+                }
+                CONSOLIDATION_REQUEST_TYPE => {
+                    for entry in Self::entries(ty, data, CONSOLIDATION_LENGTH)? {
+                        let (source_address, rest) = entry.split_at(20);
+                        let (source_pubkey, target_pubkey) = rest.split_at(PUBKEY_LENGTH);
+                        decoded.consolidations.push(ConsolidationRequest {
+                            source_address: source_address.to_vec(),
+                            source_pubkey: source_pubkey.to_vec(),
+                            target_pubkey: target_pubkey.to_vec(),
+                        });
+                    }
+                }
+                ty => return Err(RequestsError::UnknownType { ty }),
+            }
+        }
+
+        Ok(decoded)
+    }
+
+    /// Checks the requests against the header's requests hash and decodes them.
+    pub fn verify(input: RequestsInputs) -> RequestsResult<RequestsOutput> {
+        let requests_hash = input
+            .header
+            .requests_hash
+            .ok_or(RequestsError::MissingRequestsHash)?;
+        if Self::requests_hash(&input.requests) != requests_hash {
+            return Err(RequestsError::HashMismatch);
+        }
+        let requests = Self::decode(&input.requests)?;
+
+        Ok(RequestsOutput {
+            block_hash: input.header.hash().to_vec(),
+            block_number: input.header.number,
+            requests,
+        })
+    }
+}
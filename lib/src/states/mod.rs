@@ -1,5 +1,12 @@
 pub mod uni_v2 {
     use alloy_primitives::U256;
+
+    /// storage slot of the packed `reserve0`, `reserve1` and `blockTimestampLast`
+    pub const RESERVES_SLOT: [u8; 32] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 8,
+    ];
+
     #[derive(Debug)]
     pub struct UniV2ReservesState {
         pub reserve0: U256,
@@ -0,0 +1,330 @@
+use std::{collections::BTreeMap, fmt};
+
+use alloy_primitives::{PrimitiveSignature, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::decoder::{NodeDecoder, RlpError};
+use crate::header::LeanHeader;
+use crate::receipts::{EnvelopeError, TxType};
+use crate::verifier::{MPTVerifier, VerifierError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub y_parity: bool,
+    pub r: U256,
+    pub s: U256,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxSender {
+    /// the sender is recovered from the signature over `signing_hash`
+    Signed {
+        signature: Signature,
+        signing_hash: Vec<u8>,
+    },
+    /// deposits name their sender and carry no signature
+    Deposit { from: Vec<u8> },
+}
+
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    pub tx_type: TxType,
+    /// keccak of the envelope, the transaction hash
+    pub hash: Vec<u8>,
+    /// `None` for legacy transactions signed without replay protection, and deposits
+    pub chain_id: Option<u64>,
+    /// zero for deposits, which have no nonce
+    pub nonce: u64,
+    pub gas_limit: u64,
+    /// `None` for contract creations
+    pub to: Option<Vec<u8>>,
+    pub value: U256,
+    pub input: Vec<u8>,
+    pub sender: TxSender,
+}
+
+impl Transaction {
+    /// Address that submitted the transaction.
+    pub fn sender(&self) -> TransactionResult<Vec<u8>> {
+        match &self.sender {
+            TxSender::Signed {
+                signature,
+                signing_hash,
+            } => PrimitiveSignature::new(signature.r, signature.s, signature.y_parity)
+                .recover_address_from_prehash(&B256::from_slice(signing_hash))
+                .map(|address| address.to_vec())
+                .map_err(|_| TransactionError::Recovery),
+            TxSender::Deposit { from } => Ok(from.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransactionProof {
+    /// index of the transaction in the block
+    pub index: u64,
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// Transactions proven against one header's transactions root.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransactionInputs {
+    pub header: LeanHeader,
+    pub transactions: Vec<TransactionProof>,
+}
+
+#[derive(Debug)]
+pub struct TransactionOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    /// every proven transaction by index
+    pub transactions: BTreeMap<u64, Transaction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionError {
+    Verifier(VerifierError),
+    Decode(RlpError),
+    Envelope(EnvelopeError),
+    /// transaction list with the wrong number of fields for its type
+    InvalidTransaction {
+        items: usize,
+    },
+    /// address of the wrong length
+    InvalidAddress {
+        length: usize,
+    },
+    /// blob and set code transactions cannot create contracts
+    MissingRecipient,
+    /// legacy `v` or typed y parity out of range
+    InvalidSignature,
+    /// the signature does not recover to any address
+    Recovery,
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionError::Verifier(e) => write!(f, "transaction proof failed: {}", e),
+            TransactionError::Decode(e) => write!(f, "invalid transaction rlp: {}", e),
+            TransactionError::Envelope(e) => write!(f, "invalid transaction envelope: {}", e),
+            TransactionError::InvalidTransaction { items } => {
+                write!(f, "transaction with {} fields", items)
+            }
+            TransactionError::InvalidAddress { length } => {
+                write!(f, "address of {} bytes", length)
+            }
+            TransactionError::MissingRecipient => {
+                write!(f, "transaction type requires a recipient")
+            }
+            TransactionError::InvalidSignature => write!(f, "invalid signature parity"),
+            TransactionError::Recovery => write!(f, "could not recover the sender"),
+        }
+    }
+}
+
+impl From<VerifierError> for TransactionError {
+    fn from(e: VerifierError) -> Self {
+        TransactionError::Verifier(e)
+    }
+}
+
+impl From<RlpError> for TransactionError {
+    fn from(e: RlpError) -> Self {
+        TransactionError::Decode(e)
+    }
+}
+
+impl From<EnvelopeError> for TransactionError {
+    fn from(e: EnvelopeError) -> Self {
+        TransactionError::Envelope(e)
+    }
+}
+
+pub type TransactionResult<T> = Result<T, TransactionError>;
+
+/// Where the common fields sit in a signed envelope's field list.
+struct Layout {
+    chain_id: bool,
+    /// gas price, or max priority fee and max fee
+    fee_fields: usize,
+    items: usize,
+}
+
+impl Layout {
+    fn of(tx_type: TxType) -> Layout {
+        let (chain_id, fee_fields, items) = match tx_type {
+            TxType::Legacy => (false, 1, 9),
+            TxType::AccessList => (true, 1, 11),
+            TxType::DynamicFee => (true, 2, 12),
+            TxType::Blob => (true, 2, 14),
+            TxType::SetCode => (true, 2, 13),
+            // decoded separately, there is no signature
+            TxType::Deposit => (false, 0, 8),
+        };
+        Layout {
+            chain_id,
+            fee_fields,
+            items,
+        }
+    }
+
+    fn nonce(&self) -> usize {
+        self.chain_id as usize
+    }
+
+    fn gas_limit(&self) -> usize {
+        self.nonce() + 1 + self.fee_fields
+    }
+}
+
+pub struct TransactionVerifier;
+
+impl TransactionVerifier {
+    fn decode_address(raw: &[u8]) -> TransactionResult<Option<Vec<u8>>> {
+        match NodeDecoder::decode_bytes(raw)? {
+            [] => Ok(None),
+            address if address.len() == 20 => Ok(Some(address.to_vec())),
+            other => Err(TransactionError::InvalidAddress {
+                length: other.len(),
+            }),
+        }
+    }
+
+    /// keccak of the optional type byte followed by the given raw fields as an rlp list.
+    fn hash_fields(type_byte: Option<u8>, fields: &[&[u8]], extra: &[u8]) -> Vec<u8> {
+        let payload_length = fields.iter().map(|f| f.len()).sum::<usize>() + extra.len();
+        let mut out = Vec::with_capacity(payload_length + 10);
+        out.extend(type_byte);
+        alloy_rlp::Header {
+            list: true,
+            payload_length,
+        }
+        .encode(&mut out);
+        for field in fields {
+            out.extend_from_slice(field);
+        }
+        out.extend_from_slice(extra);
+        MPTVerifier::keccak(&out)
+    }
+
+    fn decode_deposit(hash: Vec<u8>, fields: &[&[u8]]) -> TransactionResult<Transaction> {
+        // source hash, from, to, mint, value, gas, is system tx, data
+        let from = Self::decode_address(fields[1])?
+            .ok_or(TransactionError::InvalidAddress { length: 0 })?;
+        Ok(Transaction {
+            tx_type: TxType::Deposit,
+            hash,
+            chain_id: None,
+            nonce: 0,
+            gas_limit: NodeDecoder::decode_u64(fields[5])?,
+            to: Self::decode_address(fields[2])?,
+            value: NodeDecoder::decode_u256(fields[4])?,
+            input: NodeDecoder::decode_bytes(fields[7])?.to_vec(),
+            sender: TxSender::Deposit { from },
+        })
+    }
+
+    /// Decodes a transaction as stored in the transactions trie, legacy or typed.
+    pub fn decode_transaction(encoded: &[u8]) -> TransactionResult<Transaction> {
+        let (tx_type, payload) = TxType::split_envelope(encoded)?;
+        let fields = NodeDecoder::decode_list_raw(payload)?;
+        let layout = Layout::of(tx_type);
+        if fields.len() != layout.items {
+            return Err(TransactionError::InvalidTransaction {
+                items: fields.len(),
+            });
+        }
+
+        let hash = MPTVerifier::keccak(encoded);
+        if tx_type == TxType::Deposit {
+            return Self::decode_deposit(hash, &fields);
+        }
+
+        let gas = layout.gas_limit();
+        let to = Self::decode_address(fields[gas + 1])?;
+        if to.is_none() && matches!(tx_type, TxType::Blob | TxType::SetCode) {
+            return Err(TransactionError::MissingRecipient);
+        }
+
+        let signed = layout.items - 3;
+        let v = NodeDecoder::decode_u64(fields[signed])?;
+        let (chain_id, y_parity, signing_hash) = if tx_type == TxType::Legacy {
+            match v {
+                27 | 28 => (None, v == 28, Self::hash_fields(None, &fields[..6], &[])),
+                // eip-155 signs over chain id, 0, 0
+                v if v >= 35 => {
+                    let chain_id = (v - 35) / 2;
+                    let mut extra = alloy_rlp::encode(chain_id);
+                    extra.extend_from_slice(&[0x80, 0x80]);
+                    (
+                        Some(chain_id),
+                        (v - 35) % 2 == 1,
+                        Self::hash_fields(None, &fields[..6], &extra),
+                    )
+                }
+                _ => return Err(TransactionError::InvalidSignature),
+            }
+        } else {
+            if v > 1 {
+                return Err(TransactionError::InvalidSignature);
+            }
+            (
+                Some(NodeDecoder::decode_u64(fields[0])?),
+                v == 1,
+                Self::hash_fields(Some(encoded[0]), &fields[..signed], &[]),
+            )
+        };
+
+        Ok(Transaction {
+            tx_type,
+            hash,
+            chain_id,
+            nonce: NodeDecoder::decode_u64(fields[layout.nonce()])?,
+            gas_limit: NodeDecoder::decode_u64(fields[gas])?,
+            to,
+            value: NodeDecoder::decode_u256(fields[gas + 2])?,
+            input: NodeDecoder::decode_bytes(fields[gas + 3])?.to_vec(),
+            sender: TxSender::Signed {
+                signature: Signature {
+                    y_parity,
+                    r: NodeDecoder::decode_u256(fields[signed + 1])?,
+                    s: NodeDecoder::decode_u256(fields[signed + 2])?,
+                },
+                signing_hash,
+            },
+        })
+    }
+
+    /// Proves the `index`-th transaction under `transactions_root`.
+    pub fn verify_transaction(
+        transactions_root: &[u8],
+        index: u64,
+        proof: &[Vec<u8>],
+    ) -> TransactionResult<Transaction> {
+        let encoded = MPTVerifier::verify_indexed_value(
+            transactions_root,
+            index,
+            MPTVerifier::decode_proof(proof)?,
+        )?;
+        Self::decode_transaction(encoded)
+    }
+
+    /// Hashes the header once and proves every transaction against its transactions root.
+    pub fn verify_transactions(input: TransactionInputs) -> TransactionResult<TransactionOutput> {
+        let block_hash = input.header.hash();
+
+        let mut transactions = BTreeMap::new();
+        for tx in input.transactions {
+            let decoded =
+                Self::verify_transaction(&input.header.transactions_root, tx.index, &tx.proof)?;
+            transactions.insert(tx.index, decoded);
+        }
+
+        Ok(TransactionOutput {
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+            transactions,
+        })
+    }
+}
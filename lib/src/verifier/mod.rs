@@ -1,21 +1,35 @@
+use std::{collections::BTreeMap, fmt};
+
 use crate::header::LeanHeader;
 use alloy_primitives::U256;
-use alloy_sol_types::{sol, SolStruct};
 use serde::{Deserialize, Serialize};
 use tiny_keccak::Hasher;
 
-use crate::decoder::NodeDecoder;
+use crate::decoder::{NodeDecoder, RlpError};
+
+/// Reference from a branch or extension to the next node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeChild<'a> {
+    Empty,
+    Hash(&'a [u8]),
+    /// raw rlp of a node shorter than 32 bytes, embedded in its parent
+    Inline(&'a [u8]),
+}
 
+/// Decoded trie node, every field is a slice of the encoded node.
+// branch children stay inline so decoding a node never allocates
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
-pub enum NodeType {
-    Branch(Vec<Vec<u8>>),
-    Extension(bool, Vec<u8>, Vec<u8>),
-    Leaf(bool, Vec<u8>, Vec<u8>),
+pub enum NodeType<'a> {
+    /// sixteen children and the value stored at the branch
+    Branch([NodeChild<'a>; 16], &'a [u8]),
+    Extension(bool, &'a [u8], NodeChild<'a>),
+    Leaf(bool, &'a [u8], &'a [u8]),
 }
 
-pub struct Node {
-    pub original: Vec<u8>,
-    pub node: NodeType,
+pub struct Node<'a> {
+    pub original: &'a [u8],
+    pub node: NodeType<'a>,
 }
 
 #[derive(Debug)]
@@ -26,27 +40,246 @@ pub struct AccountState {
     pub nonce: U256,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum AccountKind {
+    /// no code, the code hash is the hash of empty bytes
+    Eoa,
+    Contract,
+}
+
+impl AccountState {
+    pub fn kind(&self) -> AccountKind {
+        if self.code_hash == EMPTY_CODE_HASH {
+            AccountKind::Eoa
+        } else {
+            AccountKind::Contract
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct StorageProof {
+    pub slot: Vec<u8>,
+    pub proof: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Proofs {
     pub account_proof: Vec<Vec<u8>>,
-    pub storage_proof: Vec<Vec<u8>>,
+    pub storage_proofs: Vec<StorageProof>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct VerifierInputs {
     pub header: LeanHeader,
     pub address: Vec<u8>,
-    pub storage_slot: Vec<u8>,
     pub proofs: Proofs,
 }
 
 #[derive(Debug)]
 pub struct VerifierOutput {
     pub block_hash: Vec<u8>,
-    pub slot_data: Vec<u8>,
+    /// value of every proven slot, unset slots map to an empty value
+    pub slots: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+/// One account proven against a header, without any storage.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AccountStateInputs {
+    pub header: LeanHeader,
+    pub address: Vec<u8>,
+    pub account_proof: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct AccountStateOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    pub address: Vec<u8>,
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: Vec<u8>,
+    pub kind: AccountKind,
+}
+
+/// Bytecode claimed to run at `address`, checked against the proven code hash.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CodeInputs {
+    pub header: LeanHeader,
+    pub address: Vec<u8>,
+    pub account_proof: Vec<Vec<u8>>,
+    pub code: Vec<u8>,
+    /// when set, the code hash must be one of these
+    pub allowed_code_hashes: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Debug)]
+pub struct CodeOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    pub address: Vec<u8>,
+    pub code_hash: Vec<u8>,
 }
 
-pub type VerifyResultWithData<T> = Result<T, String>;
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AccountProof {
+    pub address: Vec<u8>,
+    pub proofs: Proofs,
+}
+
+/// Several accounts, each with its own slots, proven against the same header.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MultiAccountInputs {
+    pub header: LeanHeader,
+    pub accounts: Vec<AccountProof>,
+}
+
+#[derive(Debug)]
+pub struct AccountOutput {
+    pub address: Vec<u8>,
+    /// value of every proven slot, unset slots map to an empty value
+    pub slots: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct MultiAccountOutput {
+    pub block_hash: Vec<u8>,
+    /// one entry per input account, in input order
+    pub accounts: Vec<AccountOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifierError {
+    /// a proof node or value is not valid rlp
+    Decode(RlpError),
+    /// the node at `depth` does not hash to the reference held by its parent
+    HashMismatch { depth: usize },
+    /// the proof shows the key is not in the trie, but a value was required
+    PathDivergence { depth: usize },
+    /// the proof holds nodes past the end of the path
+    LeftoverNodes { count: usize },
+    /// the proof ended before the path reached a value or proved absence
+    TruncatedProof { depth: usize },
+    /// the key is longer than any trie we verify against
+    OversizedKey { length: usize },
+    /// a storage slot that is not a 32 byte word
+    InvalidSlot { length: usize },
+    /// the path visited more nodes than the key allows
+    DepthLimitExceeded { depth: usize },
+    /// an account value that is not a list of four items
+    InvalidAccountState { items: usize },
+    /// the proof shows the key is in the trie, but absence was required
+    UnexpectedInclusion,
+    /// the supplied bytecode does not hash to the account's code hash
+    CodeHashMismatch,
+    /// the account's code hash is not in the allow-list
+    CodeNotAllowed,
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierError::Decode(e) => write!(f, "invalid rlp: {}", e),
+            VerifierError::HashMismatch { depth } => write!(f, "hash mismatch at depth {}", depth),
+            VerifierError::PathDivergence { depth } => {
+                write!(f, "path diverges from key at depth {}", depth)
+            }
+            VerifierError::LeftoverNodes { count } => {
+                write!(f, "{} proof nodes left after the path ended", count)
+            }
+            VerifierError::TruncatedProof { depth } => {
+                write!(f, "proof truncated at depth {}", depth)
+            }
+            VerifierError::OversizedKey { length } => write!(f, "key of {} bytes too long", length),
+            VerifierError::InvalidSlot { length } => {
+                write!(f, "storage slot of {} bytes is not a word", length)
+            }
+            VerifierError::DepthLimitExceeded { depth } => {
+                write!(f, "proof depth {} exceeds the limit", depth)
+            }
+            VerifierError::InvalidAccountState { items } => {
+                write!(f, "inconsistent account state with length {}", items)
+            }
+            VerifierError::UnexpectedInclusion => write!(f, "key is present in the trie"),
+            VerifierError::CodeHashMismatch => write!(f, "bytecode does not match the code hash"),
+            VerifierError::CodeNotAllowed => write!(f, "code hash is not allowed"),
+        }
+    }
+}
+
+impl From<RlpError> for VerifierError {
+    fn from(e: RlpError) -> Self {
+        VerifierError::Decode(e)
+    }
+}
+
+pub type VerifyResultWithData<T> = Result<T, VerifierError>;
+
+/// keccak256 of the rlp encoding of an empty string, the root of an empty trie
+pub const EMPTY_ROOT_HASH: [u8; 32] = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+/// keccak256 of empty bytes, the code hash of every account without code
+pub const EMPTY_CODE_HASH: [u8; 32] = [
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+/// keys are at most a keccak hash long
+pub const MAX_KEY_LENGTH: usize = 32;
+
+/// every node on a path consumes at least one nibble, except the one holding the value
+pub const MAX_PROOF_DEPTH: usize = MAX_KEY_LENGTH * 2 + 1;
+
+/// Outcome of walking a proof along a key.
+pub(crate) enum Walk<'a> {
+    Found(&'a [u8]),
+    /// the key is proven absent at `depth`
+    Absent {
+        depth: usize,
+    },
+}
+
+/// Where the walker finds the node behind a hash reference.
+pub(crate) trait NodeSource<'a> {
+    /// node that hashes to `hash`, visited as the `depth`-th node of the path
+    fn resolve(&mut self, hash: &[u8], depth: usize) -> VerifyResultWithData<&Node<'a>>;
+
+    /// called for every embedded node so sources that repeat it can skip it
+    fn skip_inline(&mut self, raw: &[u8]);
+}
+
+/// A single path as returned by `eth_getProof`, nodes ordered from the root.
+struct PathProof<'a> {
+    nodes: Vec<Node<'a>>,
+    next: usize,
+}
+
+impl<'a> NodeSource<'a> for PathProof<'a> {
+    fn resolve(&mut self, hash: &[u8], depth: usize) -> VerifyResultWithData<&Node<'a>> {
+        let node = self
+            .nodes
+            .get(self.next)
+            .ok_or(VerifierError::TruncatedProof { depth })?;
+        if MPTVerifier::keccak(node.original) != hash {
+            return Err(VerifierError::HashMismatch { depth });
+        }
+        self.next += 1;
+        Ok(node)
+    }
+
+    fn skip_inline(&mut self, raw: &[u8]) {
+        if self
+            .nodes
+            .get(self.next)
+            .is_some_and(|node| node.original == raw)
+        {
+            self.next += 1;
+        }
+    }
+}
 
 pub struct MPTVerifier;
 
@@ -60,7 +293,7 @@ impl MPTVerifier {
         nibbles
     }
 
-    fn keccak(input: &[u8]) -> Vec<u8> {
+    pub(crate) fn keccak(input: &[u8]) -> Vec<u8> {
         let mut hasher = tiny_keccak::Keccak::v256();
         hasher.update(input);
         let out = &mut [0_u8; 32];
@@ -68,105 +301,199 @@ impl MPTVerifier {
         out.to_vec()
     }
 
-    fn verify_and_get_data(
-        root_hash: Vec<u8>,
+    /// Strips the flag nibble (and the padding nibble for even paths) from a compact path.
+    fn path_to_nibbles(is_odd: bool, slug: &[u8]) -> Vec<u8> {
+        let mut slug_nibbles = Self::key_to_nibbles(slug);
+        // always pop first nibble because it has type
+        slug_nibbles.remove(0);
+        if !is_odd {
+            // remove if not odd
+            slug_nibbles.remove(0);
+        }
+        slug_nibbles
+    }
+
+    /// Walks the trie from `root_hash` along `key`, pulling nodes from `source`.
+    ///
+    /// The key is absent when the walk ends at an empty branch slot, a leaf or extension whose
+    /// path diverges, or an empty trie. Every hashed node must match its parent's reference.
+    pub(crate) fn walk<'a>(
+        root_hash: &[u8],
         key: &[u8],
-        proof: Vec<Node>,
-    ) -> VerifyResultWithData<Vec<u8>> {
-        let mut current_hash = root_hash;
+        source: &mut impl NodeSource<'a>,
+    ) -> VerifyResultWithData<Walk<'a>> {
+        if key.len() > MAX_KEY_LENGTH {
+            return Err(VerifierError::OversizedKey { length: key.len() });
+        }
+
+        if root_hash == EMPTY_ROOT_HASH {
+            return Ok(Walk::Absent { depth: 0 });
+        }
+
         let nibbles = Self::key_to_nibbles(key);
         let mut nibble_index = 0;
-        for node_proof in proof {
-            if current_hash.len() < 32 {
-                // println!("{:0x?} {:0x?}", node_proof.original, current_hash);
-
-                if current_hash != node_proof.original {
-                    panic!(
-                        "expected {:0x?} got {:0x?}",
-                        node_proof.original, current_hash
-                    )
-                }
-            } else {
-                let out_hash = Self::keccak(&node_proof.original);
-                // println!("{:0x?} {:0x?}", out_hash, current_hash);
+        let mut depth = 0;
+        // the root is always referenced by hash, `None` until we leave it
+        let mut next: Option<NodeChild<'a>> = None;
 
-                if out_hash != current_hash {
-                    panic!("expected {:0x?} got {:0x?}", out_hash, current_hash)
-                }
+        loop {
+            if depth >= MAX_PROOF_DEPTH {
+                return Err(VerifierError::DepthLimitExceeded { depth });
             }
 
+            let inline;
+            let node_proof = match next {
+                None => source.resolve(root_hash, depth)?,
+                Some(NodeChild::Hash(current_hash)) => source.resolve(current_hash, depth)?,
+                Some(NodeChild::Inline(raw)) => {
+                    // embedded nodes live inside their parent, skip them if the proof repeats them
+                    source.skip_inline(raw);
+                    inline = NodeDecoder::decode_mpt_node(raw)?;
+                    &inline
+                }
+                // nothing is stored below an empty slot
+                Some(NodeChild::Empty) => return Ok(Walk::Absent { depth }),
+            };
+
             match node_proof.node {
-                NodeType::Branch(val) => {
-                    if nibble_index > nibbles.len() {
-                        //println!("{:0x?} found", val[16].clone());
-                        return Ok(val[16].clone());
+                NodeType::Branch(ref children, value) => {
+                    if nibble_index >= nibbles.len() {
+                        if value.is_empty() {
+                            return Ok(Walk::Absent { depth });
+                        }
+                        return Ok(Walk::Found(value));
                     }
 
                     let nibble = nibbles[nibble_index] as usize;
-                    //println!("{:?} {:?} {:0x?}", nibble, nibble_index, val[nibble]);
                     nibble_index += 1;
-                    if val[nibble].is_empty() {
-                        panic!("path not found in branch");
-                    }
-
-                    if val[nibble].len() < 32 {
-                        // copy everything
-                        current_hash.copy_from_slice(&val[nibble]);
-                    } else {
-                        // only copy 32 bytes, which should always be the  full length
-                        current_hash.copy_from_slice(&val[nibble][0..32]);
-                    }
+                    next = Some(children[nibble]);
                 }
                 NodeType::Leaf(is_odd, slug, value) => {
-                    let mut slug_nibbles = Self::key_to_nibbles(&slug);
-                    // always pop first nibble because it has type
-                    slug_nibbles.remove(0);
-                    if !is_odd {
-                        // remove if not odd
-                        slug_nibbles.remove(0);
-                    }
-
-                    for &val in slug_nibbles.iter() {
-                        if nibbles[nibble_index] != val {
-                            panic!("did not match")
-                        }
-                        nibble_index += 1;
+                    // the leaf must hold exactly the rest of the key, any other leaf here proves absence
+                    if Self::path_to_nibbles(is_odd, slug) != nibbles[nibble_index..] {
+                        return Ok(Walk::Absent { depth });
                     }
 
-                    // we should have matched everything by now
-                    if nibble_index != nibbles.len() {
-                        panic!("incomplete leaf node")
+                    return Ok(Walk::Found(value));
+                }
+                NodeType::Extension(is_odd, slug, child) => {
+                    let slug_nibbles = Self::path_to_nibbles(is_odd, slug);
+                    if !nibbles[nibble_index..].starts_with(&slug_nibbles) {
+                        return Ok(Walk::Absent { depth });
                     }
+                    nibble_index += slug_nibbles.len();
 
-                    return Ok(value);
+                    next = Some(child);
                 }
-                NodeType::Extension(is_odd, slug, next) => {
-                    let mut slug_nibbles = Self::key_to_nibbles(&slug);
-                    // always pop first nibble because it has type
-                    slug_nibbles.remove(0);
-                    if !is_odd {
-                        // remove if not odd
-                        slug_nibbles.remove(0);
-                    }
+            }
+            depth += 1;
+        }
+    }
 
-                    for &val in slug_nibbles.iter() {
-                        if nibbles[nibble_index] != val {
-                            panic!("did not match")
-                        }
-                        nibble_index += 1;
-                    }
+    /// Walks a single path proof, which must be consumed exactly.
+    fn walk_path<'a>(
+        root_hash: &[u8],
+        key: &[u8],
+        proof: Vec<Node<'a>>,
+    ) -> VerifyResultWithData<Walk<'a>> {
+        let mut source = PathProof {
+            nodes: proof,
+            next: 0,
+        };
+        let walk = Self::walk(root_hash, key, &mut source)?;
+
+        let count = source.nodes.len() - source.next;
+        if count > 0 {
+            return Err(VerifierError::LeftoverNodes { count });
+        }
+        Ok(walk)
+    }
 
-                    if next.len() < 32 {
-                        // copy everything
-                        current_hash.copy_from_slice(&next);
-                    } else {
-                        // only copy 32 bytes, which should always be the  full length
-                        current_hash.copy_from_slice(&next[0..32]);
-                    }
-                }
+    /// Walks the proof from `root_hash` along `key`, `None` when the key is proven absent.
+    fn verify_and_get_data<'a>(
+        root_hash: &[u8],
+        key: &[u8],
+        proof: Vec<Node<'a>>,
+    ) -> VerifyResultWithData<Option<&'a [u8]>> {
+        match Self::walk_path(root_hash, key, proof)? {
+            Walk::Found(value) => Ok(Some(value)),
+            Walk::Absent { .. } => Ok(None),
+        }
+    }
+
+    /// Walks the proof from `root_hash` along `key`, failing unless the key is present.
+    fn verify_inclusion<'a>(
+        root_hash: &[u8],
+        key: &[u8],
+        proof: Vec<Node<'a>>,
+    ) -> VerifyResultWithData<&'a [u8]> {
+        match Self::walk_path(root_hash, key, proof)? {
+            Walk::Found(value) => Ok(value),
+            Walk::Absent { depth } => Err(VerifierError::PathDivergence { depth }),
+        }
+    }
+
+    pub(crate) fn decode_account_state(encoded: &[u8]) -> VerifyResultWithData<AccountState> {
+        let mut fields: [&[u8]; 4] = [&[]; 4];
+        let mut items = 0;
+        for item in NodeDecoder::list_items(encoded)? {
+            if items < fields.len() {
+                fields[items] = item?;
+            }
+            items += 1;
+        }
+        if items != 4 {
+            return Err(VerifierError::InvalidAccountState { items });
+        }
+
+        // nonce and balance are canonical integers of at most 32 bytes
+        Ok(AccountState {
+            storage_hash: NodeDecoder::decode_bytes(fields[2])?.to_vec(),
+            balance: NodeDecoder::decode_u256(fields[1])?,
+            code_hash: NodeDecoder::decode_bytes(fields[3])?.to_vec(),
+            nonce: NodeDecoder::decode_u256(fields[0])?,
+        })
+    }
+
+    /// Storage values are words, anything longer cannot be packed into one.
+    pub(crate) fn decode_storage_value(encoded: &[u8]) -> VerifyResultWithData<Vec<u8>> {
+        let value = NodeDecoder::decode_bytes(encoded)?;
+        if value.len() > 32 {
+            return Err(RlpError::IntegerOverflow {
+                length: value.len(),
             }
+            .into());
         }
-        Ok(Vec::new())
+        Ok(value.to_vec())
+    }
+
+    /// Slots are committed as words, so every slot must be one.
+    pub(crate) fn check_slot(slot: &[u8]) -> VerifyResultWithData<()> {
+        if slot.len() != 32 {
+            return Err(VerifierError::InvalidSlot { length: slot.len() });
+        }
+        Ok(())
+    }
+
+    /// Decodes every node of a proof, borrowing from the proof buffers.
+    pub fn decode_proof(proof: &[Vec<u8>]) -> VerifyResultWithData<Vec<Node<'_>>> {
+        let mut nodes: Vec<Node> = Vec::with_capacity(proof.len());
+        for node in proof {
+            nodes.push(NodeDecoder::decode_mpt_node(node)?);
+        }
+        Ok(nodes)
+    }
+
+    /// Verifies the account proof, `None` when it proves the account does not exist.
+    pub fn verify_account_state(
+        state_root: &[u8],
+        address: Vec<u8>,
+        proof: Vec<Node>,
+    ) -> VerifyResultWithData<Option<AccountState>> {
+        let address_hash = Self::keccak(&address);
+        Self::verify_and_get_data(state_root, &address_hash, proof)?
+            .map(Self::decode_account_state)
+            .transpose()
     }
 
     pub fn verify_and_get_account_state(
@@ -175,21 +502,32 @@ impl MPTVerifier {
         proof: Vec<Node>,
     ) -> VerifyResultWithData<AccountState> {
         let address_hash = Self::keccak(&address);
-        let out = Self::verify_and_get_data(state_root.to_vec(), &address_hash, proof).unwrap();
-        let decode = NodeDecoder::decode_rlp(&out);
-        if decode.len() < 4 {
-            return Err(format!(
-                "inconsistent account state with length {}",
-                decode.len()
-            ));
+        let out = Self::verify_inclusion(state_root, &address_hash, proof)?;
+        Self::decode_account_state(out)
+    }
+
+    /// Proves that no account exists at `address`.
+    pub fn verify_account_exclusion(
+        state_root: &[u8],
+        address: Vec<u8>,
+        proof: Vec<Node>,
+    ) -> VerifyResultWithData<()> {
+        match Self::verify_account_state(state_root, address, proof)? {
+            Some(_) => Err(VerifierError::UnexpectedInclusion),
+            None => Ok(()),
         }
+    }
 
-        Ok(AccountState {
-            storage_hash: decode[2].clone(),
-            balance: U256::from_be_slice(decode[1].as_slice()),
-            code_hash: decode[3].clone(),
-            nonce: U256::from_be_slice(decode[0].as_slice()),
-        })
+    /// Verifies the storage proof, `None` when it proves the slot is unset (zero).
+    pub fn verify_storage_slot(
+        storage_root: &[u8],
+        slot: Vec<u8>,
+        proof: Vec<Node>,
+    ) -> VerifyResultWithData<Option<Vec<u8>>> {
+        let slot_hash = Self::keccak(&slot);
+        Self::verify_and_get_data(storage_root, &slot_hash, proof)?
+            .map(Self::decode_storage_value)
+            .transpose()
     }
 
     pub fn verify_and_get_slot(
@@ -198,46 +536,156 @@ impl MPTVerifier {
         proof: Vec<Node>,
     ) -> VerifyResultWithData<Vec<u8>> {
         let slot_hash = Self::keccak(&slot);
-        let out = Self::verify_and_get_data(state_root.to_vec(), &slot_hash, proof).unwrap();
-        let decode = NodeDecoder::decode_rlp(&out);
-        if decode.is_empty() {
-            return Err(format!("no storage found {}", decode.len()));
+        let out = Self::verify_inclusion(state_root, &slot_hash, proof)?;
+        Self::decode_storage_value(out)
+    }
+
+    /// Proves that `slot` is unset (zero) under `storage_root`.
+    pub fn verify_slot_exclusion(
+        storage_root: &[u8],
+        slot: Vec<u8>,
+        proof: Vec<Node>,
+    ) -> VerifyResultWithData<()> {
+        match Self::verify_storage_slot(storage_root, slot, proof)? {
+            Some(_) => Err(VerifierError::UnexpectedInclusion),
+            None => Ok(()),
         }
+    }
 
-        Ok(decode[0].clone())
+    /// Key of the `index`-th entry of the transaction, receipt and withdrawal tries.
+    pub fn index_key(index: u64) -> Vec<u8> {
+        alloy_rlp::encode(index)
     }
 
-    pub fn verify_slot(input: VerifierInputs) -> VerifyResultWithData<VerifierOutput> {
-        // compute the block hash here
+    /// Proves the value stored at `index` of a list trie, keyed by `rlp(index)` rather than a hash.
+    pub fn verify_indexed_value<'a>(
+        root_hash: &[u8],
+        index: u64,
+        proof: Vec<Node<'a>>,
+    ) -> VerifyResultWithData<&'a [u8]> {
+        Self::verify_inclusion(root_hash, &Self::index_key(index), proof)
+    }
+
+    /// Proves balance, nonce and code hash of one account at the header's block.
+    pub fn verify_account(input: AccountStateInputs) -> VerifyResultWithData<AccountStateOutput> {
         let block_hash = input.header.hash();
 
-        let mut account_proofs: Vec<Node> = Vec::new();
-        for node in input.proofs.account_proof {
-            account_proofs.push(NodeDecoder::decode_mpt_node(&node));
+        let account_state = Self::verify_and_get_account_state(
+            &input.header.state_root,
+            input.address.clone(),
+            Self::decode_proof(&input.account_proof)?,
+        )?;
+        let kind = account_state.kind();
+
+        Ok(AccountStateOutput {
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+            address: input.address,
+            balance: account_state.balance,
+            nonce: account_state.nonce,
+            code_hash: account_state.code_hash,
+            kind,
+        })
+    }
+
+    /// Verifies the account and that `code` is the bytecode behind its code hash.
+    pub fn verify_code(
+        state_root: &[u8],
+        address: Vec<u8>,
+        proof: Vec<Node>,
+        code: &[u8],
+    ) -> VerifyResultWithData<AccountState> {
+        let account_state = Self::verify_and_get_account_state(state_root, address, proof)?;
+        if Self::keccak(code) != account_state.code_hash {
+            return Err(VerifierError::CodeHashMismatch);
         }
+        Ok(account_state)
+    }
+
+    /// Proves the bytecode at `address` at the header's block, optionally against an allow-list.
+    pub fn verify_contract_code(input: CodeInputs) -> VerifyResultWithData<CodeOutput> {
+        let block_hash = input.header.hash();
 
+        let account_state = Self::verify_code(
+            &input.header.state_root,
+            input.address.clone(),
+            Self::decode_proof(&input.account_proof)?,
+            &input.code,
+        )?;
+        if let Some(allowed) = &input.allowed_code_hashes {
+            if !allowed.contains(&account_state.code_hash) {
+                return Err(VerifierError::CodeNotAllowed);
+            }
+        }
+
+        Ok(CodeOutput {
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+            address: input.address,
+            code_hash: account_state.code_hash,
+        })
+    }
+
+    /// Verifies the account once and every storage proof against its storage root.
+    pub fn verify_account_slots(
+        state_root: &[u8],
+        address: Vec<u8>,
+        proofs: Proofs,
+    ) -> VerifyResultWithData<BTreeMap<Vec<u8>, Vec<u8>>> {
         // start from state root
         let account_state = Self::verify_and_get_account_state(
-            input.header.state_root.as_slice(),
-            input.address,
-            account_proofs,
+            state_root,
+            address,
+            Self::decode_proof(&proofs.account_proof)?,
         )?;
 
-        let mut storage_proofs: Vec<Node> = Vec::new();
-        for node in input.proofs.storage_proof {
-            storage_proofs.push(NodeDecoder::decode_mpt_node(&node));
+        // verify with computed storage hash
+        let mut slots = BTreeMap::new();
+        for storage in proofs.storage_proofs {
+            Self::check_slot(&storage.slot)?;
+            let value = Self::verify_storage_slot(
+                &account_state.storage_hash,
+                storage.slot.clone(),
+                Self::decode_proof(&storage.proof)?,
+            )?;
+            slots.insert(storage.slot, value.unwrap_or_default());
         }
+        Ok(slots)
+    }
 
-        // verify with computed storage hash
-        let storage_value = Self::verify_and_get_slot(
-            &account_state.storage_hash,
-            input.storage_slot,
-            storage_proofs,
-        )?;
+    pub fn verify_slot(input: VerifierInputs) -> VerifyResultWithData<VerifierOutput> {
+        // compute the block hash here
+        let block_hash = input.header.hash();
+
+        let slots =
+            Self::verify_account_slots(&input.header.state_root, input.address, input.proofs)?;
 
         Ok(VerifierOutput {
             block_hash: block_hash.to_vec(),
-            slot_data: storage_value,
+            slots,
+        })
+    }
+
+    /// Hashes the header once and verifies every account against its state root.
+    pub fn verify_accounts(input: MultiAccountInputs) -> VerifyResultWithData<MultiAccountOutput> {
+        let block_hash = input.header.hash();
+
+        let mut accounts = Vec::with_capacity(input.accounts.len());
+        for account in input.accounts {
+            let slots = Self::verify_account_slots(
+                &input.header.state_root,
+                account.address.clone(),
+                account.proofs,
+            )?;
+            accounts.push(AccountOutput {
+                address: account.address,
+                slots,
+            });
+        }
+
+        Ok(MultiAccountOutput {
+            block_hash: block_hash.to_vec(),
+            accounts,
         })
     }
 }
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::decoder::{NodeDecoder, RlpError};
+use crate::header::LeanHeader;
+use crate::verifier::{MPTVerifier, VerifierError};
+
+/// EIP-4895 validator withdrawal, credited to `address` without a transaction.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub index: u64,
+    pub validator_index: u64,
+    pub address: Vec<u8>,
+    /// amount in gwei
+    pub amount: u64,
+}
+
+/// One withdrawal proven by its position in the block's withdrawal list.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WithdrawalInputs {
+    pub header: LeanHeader,
+    pub position: u64,
+    pub proof: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct WithdrawalOutput {
+    pub block_hash: Vec<u8>,
+    pub block_number: u64,
+    pub position: u64,
+    pub withdrawal: Withdrawal,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WithdrawalError {
+    Verifier(VerifierError),
+    Decode(RlpError),
+    /// the header predates shanghai and commits to no withdrawals
+    MissingWithdrawalsRoot,
+    /// withdrawal list with other than four fields
+    InvalidWithdrawal {
+        items: usize,
+    },
+    /// address of the wrong length
+    InvalidAddress {
+        length: usize,
+    },
+}
+
+impl fmt::Display for WithdrawalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WithdrawalError::Verifier(e) => write!(f, "withdrawal proof failed: {}", e),
+            WithdrawalError::Decode(e) => write!(f, "invalid withdrawal rlp: {}", e),
+            WithdrawalError::MissingWithdrawalsRoot => write!(f, "header has no withdrawals root"),
+            WithdrawalError::InvalidWithdrawal { items } => {
+                write!(f, "withdrawal with {} fields", items)
+            }
+            WithdrawalError::InvalidAddress { length } => {
+                write!(f, "address of {} bytes", length)
+            }
+        }
+    }
+}
+
+impl From<VerifierError> for WithdrawalError {
+    fn from(e: VerifierError) -> Self {
+        WithdrawalError::Verifier(e)
+    }
+}
+
+impl From<RlpError> for WithdrawalError {
+    fn from(e: RlpError) -> Self {
+        WithdrawalError::Decode(e)
+    }
+}
+
+pub type WithdrawalResult<T> = Result<T, WithdrawalError>;
+
+pub struct WithdrawalVerifier;
+
+impl WithdrawalVerifier {
+    /// Decodes a withdrawal as stored in the withdrawals trie.
+    pub fn decode_withdrawal(encoded: &[u8]) -> WithdrawalResult<Withdrawal> {
+        let fields = NodeDecoder::decode_list_raw(encoded)?;
+        if fields.len() != 4 {
+            return Err(WithdrawalError::InvalidWithdrawal {
+                items: fields.len(),
+            });
+        }
+
+        let address = NodeDecoder::decode_bytes(fields[2])?;
+        if address.len() != 20 {
+            return Err(WithdrawalError::InvalidAddress {
+                length: address.len(),
+            });
+        }
+
+        Ok(Withdrawal {
+            index: NodeDecoder::decode_u64(fields[0])?,
+            validator_index: NodeDecoder::decode_u64(fields[1])?,
+            address: address.to_vec(),
+            amount: NodeDecoder::decode_u64(fields[3])?,
+        })
+    }
+
+    /// Proves the withdrawal at `position` under `withdrawals_root`.
+    pub fn verify_withdrawal(
+        withdrawals_root: &[u8],
+        position: u64,
+        proof: &[Vec<u8>],
+    ) -> WithdrawalResult<Withdrawal> {
+        let encoded = MPTVerifier::verify_indexed_value(
+            withdrawals_root,
+            position,
+            MPTVerifier::decode_proof(proof)?,
+        )?;
+        Self::decode_withdrawal(encoded)
+    }
+
+    pub fn verify(input: WithdrawalInputs) -> WithdrawalResult<WithdrawalOutput> {
+        let withdrawals_root = input
+            .header
+            .withdrawals_root
+            .ok_or(WithdrawalError::MissingWithdrawalsRoot)?;
+        let block_hash = input.header.hash();
+
+        let withdrawal = Self::verify_withdrawal(&withdrawals_root, input.position, &input.proof)?;
+
+        Ok(WithdrawalOutput {
+            block_hash: block_hash.to_vec(),
+            block_number: input.header.number,
+            position: input.position,
+            withdrawal,
+        })
+    }
+}
@@ -0,0 +1,245 @@
+//! State proofs against recorded data.
+//!
+//! `op_sepolia_26207959.json` holds eth_getProof shaped responses for OP Sepolia block 26207959,
+//! taken from the trie nodes and bytecode kona-executor recorded to execute block 26207960, with
+//! that block's parent header. The L1Block predeploy carries its proxy slots and the L1 origin
+//! fields, and every account carries its recorded bytecode. The last entry is an address the
+//! state trie proves absent.
+
+mod common;
+
+use alloy_primitives::{hex, keccak256};
+use common::{proof_fixture, proofs, quantity, state_header};
+use obsidian_lib::verifier::{
+    AccountProof, CodeInputs, MPTVerifier, MultiAccountInputs, Proofs, VerifierError,
+    VerifierInputs,
+};
+use serde_json::Value;
+
+const L1_BLOCK: [u8; 20] = hex!("4200000000000000000000000000000000000015");
+
+fn state() -> Value {
+    proof_fixture("op_sepolia_26207959")
+}
+
+#[test]
+fn reads_every_recorded_slot() {
+    let fixture = state();
+    let header = state_header(&fixture);
+    let l1_block = &fixture["accounts"][0];
+
+    let slots =
+        MPTVerifier::verify_account_slots(&header.state_root, L1_BLOCK.to_vec(), proofs(l1_block))
+            .unwrap();
+
+    let recorded = l1_block["storageProof"].as_array().unwrap();
+    assert_eq!(slots.len(), recorded.len());
+    for slot in recorded {
+        let key = common::bytes(&slot["key"]);
+        assert_eq!(slots[&key], quantity(&slot["value"]));
+    }
+    // slot 2 is the hash of the L1 origin
+    assert_eq!(
+        slots[&[[0u8; 31].as_slice(), &[2]].concat()],
+        hex!("e19e7253ba9308da9fcfdcc044397159b91b8b935d94da87af166a724ecf5ca7")
+    );
+}
+
+#[test]
+fn verify_slot_hashes_the_header() {
+    let fixture = state();
+    let header = state_header(&fixture);
+    let output = MPTVerifier::verify_slot(VerifierInputs {
+        header: header.clone(),
+        address: L1_BLOCK.to_vec(),
+        proofs: proofs(&fixture["accounts"][0]),
+    })
+    .unwrap();
+
+    assert_eq!(output.block_hash, header.hash());
+    assert_eq!(output.slots.len(), 7);
+}
+
+#[test]
+fn rejects_slot_proof_of_another_slot() {
+    let fixture = state();
+    let header = state_header(&fixture);
+    let mut proofs = proofs(&fixture["accounts"][0]);
+    // the proof of slot 0 says nothing about slot 1
+    let other = proofs.storage_proofs[1].slot.clone();
+    proofs.storage_proofs[0].slot = other;
+
+    let result = MPTVerifier::verify_account_slots(&header.state_root, L1_BLOCK.to_vec(), proofs);
+    assert!(matches!(result, Err(VerifierError::HashMismatch { .. })));
+}
+
+#[test]
+fn rejects_slots_under_another_account() {
+    let fixture = state();
+    let header = state_header(&fixture);
+    let mut proofs = proofs(&fixture["accounts"][0]);
+    // storage proofs only hold under the storage root of the account they were taken for
+    proofs.account_proof = common::branch(&fixture["accounts"][3]["accountProof"]);
+
+    let result = MPTVerifier::verify_account_slots(
+        &header.state_root,
+        common::bytes(&fixture["accounts"][3]["address"]),
+        proofs,
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::HashMismatch { depth: 0 }
+    );
+}
+
+fn account_proofs(fixture: &Value) -> Vec<AccountProof> {
+    fixture["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|account| AccountProof {
+            address: common::bytes(&account["address"]),
+            proofs: proofs(account),
+        })
+        .collect()
+}
+
+#[test]
+fn verifies_every_account_against_one_header() {
+    let fixture = state();
+    let header = state_header(&fixture);
+    let output = MPTVerifier::verify_accounts(MultiAccountInputs {
+        header: header.clone(),
+        accounts: account_proofs(&fixture),
+    })
+    .unwrap();
+
+    assert_eq!(output.block_hash, header.hash());
+    let addresses: Vec<_> = output.accounts.iter().map(|a| a.address.clone()).collect();
+    let expected: Vec<_> = account_proofs(&fixture)
+        .into_iter()
+        .map(|a| a.address)
+        .collect();
+    assert_eq!(addresses, expected);
+    assert_eq!(output.accounts[0].slots.len(), 7);
+    assert!(output.accounts[1..].iter().all(|a| a.slots.is_empty()));
+}
+
+#[test]
+fn rejects_missing_account() {
+    let fixture = state();
+    let mut accounts = account_proofs(&fixture);
+    let absent = &fixture["absent"];
+    accounts.push(AccountProof {
+        address: common::bytes(&absent["address"]),
+        proofs: Proofs {
+            account_proof: common::branch(&absent["accountProof"]),
+            storage_proofs: vec![],
+        },
+    });
+
+    // the state trie proves the address absent, which is not a provable account
+    let result = MPTVerifier::verify_accounts(MultiAccountInputs {
+        header: state_header(&fixture),
+        accounts,
+    });
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::PathDivergence { depth: 6 }
+    );
+
+    MPTVerifier::verify_account_exclusion(
+        &state_header(&fixture).state_root,
+        common::bytes(&absent["address"]),
+        MPTVerifier::decode_proof(&common::branch(&absent["accountProof"])).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn rejects_accounts_against_another_header() {
+    let fixture = state();
+    let mut header = state_header(&fixture);
+    header.state_root = [0x11; 32];
+
+    let result = MPTVerifier::verify_accounts(MultiAccountInputs {
+        header,
+        accounts: account_proofs(&fixture),
+    });
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::HashMismatch { depth: 0 }
+    );
+}
+
+fn code_inputs(fixture: &Value, index: usize) -> CodeInputs {
+    let account = &fixture["accounts"][index];
+    CodeInputs {
+        header: state_header(fixture),
+        address: common::bytes(&account["address"]),
+        account_proof: common::branch(&account["accountProof"]),
+        code: common::bytes(&account["code"]),
+        allowed_code_hashes: None,
+    }
+}
+
+#[test]
+fn verifies_recorded_code() {
+    let fixture = state();
+    for index in [0, 1, 3] {
+        let output = MPTVerifier::verify_contract_code(code_inputs(&fixture, index)).unwrap();
+        assert_eq!(
+            output.code_hash,
+            common::bytes(&fixture["accounts"][index]["codeHash"])
+        );
+        assert_eq!(output.block_hash, state_header(&fixture).hash());
+    }
+}
+
+#[test]
+fn verifies_empty_code_of_an_eoa() {
+    let fixture = state();
+    let inputs = code_inputs(&fixture, 2);
+    assert!(inputs.code.is_empty());
+
+    let output = MPTVerifier::verify_contract_code(inputs).unwrap();
+    assert_eq!(output.code_hash, keccak256([]).to_vec());
+}
+
+#[test]
+fn rejects_code_of_another_account() {
+    let fixture = state();
+    let mut inputs = code_inputs(&fixture, 0);
+    // the L1Block proxy proven with the beacon roots contract's bytecode
+    inputs.code = common::bytes(&fixture["accounts"][1]["code"]);
+
+    let result = MPTVerifier::verify_contract_code(inputs);
+    assert_eq!(result.unwrap_err(), VerifierError::CodeHashMismatch);
+}
+
+#[test]
+fn rejects_code_with_one_byte_changed() {
+    let fixture = state();
+    let mut inputs = code_inputs(&fixture, 3);
+    let last = inputs.code.len() - 1;
+    inputs.code[last] ^= 1;
+
+    let result = MPTVerifier::verify_contract_code(inputs);
+    assert_eq!(result.unwrap_err(), VerifierError::CodeHashMismatch);
+}
+
+#[test]
+fn checks_code_hash_against_the_allow_list() {
+    let fixture = state();
+    let proxy_hash = common::bytes(&fixture["accounts"][0]["codeHash"]);
+
+    let mut inputs = code_inputs(&fixture, 0);
+    inputs.allowed_code_hashes = Some(vec![keccak256([]).to_vec(), proxy_hash.clone()]);
+    let output = MPTVerifier::verify_contract_code(inputs).unwrap();
+    assert_eq!(output.code_hash, proxy_hash);
+
+    let mut inputs = code_inputs(&fixture, 3);
+    inputs.allowed_code_hashes = Some(vec![proxy_hash]);
+    let result = MPTVerifier::verify_contract_code(inputs);
+    assert_eq!(result.unwrap_err(), VerifierError::CodeNotAllowed);
+}
@@ -0,0 +1,309 @@
+//! Execution payload fields proven from a beacon block root.
+//!
+//! The fixtures in `fixtures/beacon` are execution proofs snowbridge recorded from the beacon API
+//! of local devnets (Apache-2.0): `deneb_devnet_215.json` from a deneb devnet in January 2024,
+//! `electra_devnet_182.json` from an electra devnet in June 2025. Each holds a beacon block
+//! header, its execution payload header and the payload's branch from the body root. Branches to
+//! single payload fields are built here from the recorded payload fields. No recorded mainnet
+//! block with its body branch, nor any capella one, was at hand.
+
+mod common;
+
+use common::{beacon_fixture as fixture, beacon_header, header_rlp, payload_header};
+use obsidian_lib::beacon::{
+    BeaconError, BeaconFork, BeaconInputs, BeaconVerifier, BLOCK_BODY_ROOT_GINDEX,
+    BODY_EXECUTION_PAYLOAD_GINDEX, PAYLOAD_BLOCK_HASH_FIELD,
+};
+use obsidian_lib::chain_spec::{MAINNET, SEPOLIA};
+use obsidian_lib::header::LeanHeader;
+use serde_json::Value;
+
+const DENEB: &str = "deneb_devnet_215";
+const ELECTRA: &str = "electra_devnet_182";
+
+/// Field positions in an `ExecutionPayloadHeader`.
+const PAYLOAD_STATE_ROOT: u64 = 2;
+const PAYLOAD_BLOCK_NUMBER: u64 = 6;
+const PAYLOAD_TIMESTAMP: u64 = 9;
+
+fn uint_chunk(value: u64) -> [u8; 32] {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&value.to_le_bytes());
+    chunk
+}
+
+fn block_root(fixture: &Value) -> [u8; 32] {
+    beacon_header(fixture).hash_tree_root()
+}
+
+fn payload_field_gindex(fork: BeaconFork, field: u64) -> u64 {
+    BeaconVerifier::concat_gindices(&[
+        BLOCK_BODY_ROOT_GINDEX,
+        BODY_EXECUTION_PAYLOAD_GINDEX,
+        fork.payload_gindex(field).unwrap(),
+    ])
+}
+
+/// Branch from a payload field to the block root: within the payload, the recorded branch to the
+/// body root, then within the block header.
+fn payload_field_branch(fixture: &Value, fork: BeaconFork, field: u64) -> Vec<Vec<u8>> {
+    let header = beacon_header(fixture);
+    let header_leaves = [
+        uint_chunk(header.slot),
+        uint_chunk(header.proposer_index),
+        header.parent_root,
+        header.state_root,
+        header.body_root,
+    ];
+    let payload_leaves = payload_header(fixture).leaves(fork).unwrap();
+
+    let mut branch = common::ssz_branch(&payload_leaves, field as usize);
+    branch.extend(common::branch(&fixture["execution_branch"]));
+    branch.extend(common::ssz_branch(&header_leaves, 4));
+    branch
+}
+
+fn payload_header_sits_in_body(name: &str, fork: BeaconFork) {
+    let fixture = fixture(name);
+    let payload_root = payload_header(&fixture).hash_tree_root(fork).unwrap();
+
+    BeaconVerifier::verify_branch(
+        &beacon_header(&fixture).body_root,
+        &payload_root,
+        &common::branch(&fixture["execution_branch"]),
+        BODY_EXECUTION_PAYLOAD_GINDEX,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deneb_payload_header_sits_in_body() {
+    payload_header_sits_in_body(DENEB, BeaconFork::Deneb);
+}
+
+#[test]
+fn electra_payload_header_sits_in_body() {
+    payload_header_sits_in_body(ELECTRA, BeaconFork::Electra);
+}
+
+fn proves_payload_block_hash(name: &str, fork: BeaconFork) {
+    let fixture = fixture(name);
+    let block_hash = payload_header(&fixture).block_hash;
+
+    BeaconVerifier::verify_payload_block_hash(
+        &block_root(&fixture),
+        fork,
+        &block_hash,
+        &payload_field_branch(&fixture, fork, PAYLOAD_BLOCK_HASH_FIELD),
+    )
+    .unwrap();
+}
+
+#[test]
+fn proves_deneb_payload_block_hash() {
+    proves_payload_block_hash(DENEB, BeaconFork::Deneb);
+}
+
+#[test]
+fn proves_electra_payload_block_hash() {
+    proves_payload_block_hash(ELECTRA, BeaconFork::Electra);
+}
+
+#[test]
+fn proves_payload_fields() {
+    let fixture = fixture(ELECTRA);
+    let fork = BeaconFork::Electra;
+    let block_root = block_root(&fixture);
+    let payload = payload_header(&fixture);
+
+    for (field, leaf) in [
+        (PAYLOAD_STATE_ROOT, payload.state_root),
+        (PAYLOAD_BLOCK_NUMBER, uint_chunk(payload.block_number)),
+        (PAYLOAD_TIMESTAMP, uint_chunk(payload.timestamp)),
+    ] {
+        BeaconVerifier::verify_branch(
+            &block_root,
+            &leaf,
+            &payload_field_branch(&fixture, fork, field),
+            payload_field_gindex(fork, field),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn capella_payload_block_hash_sits_higher() {
+    // 15 capella fields fit 16 leaves, the blob gas fields of deneb widen the payload to 32
+    assert_eq!(
+        BeaconFork::Capella.payload_gindex(PAYLOAD_BLOCK_HASH_FIELD),
+        Ok(28)
+    );
+    assert_eq!(
+        BeaconFork::Deneb.payload_gindex(PAYLOAD_BLOCK_HASH_FIELD),
+        Ok(44)
+    );
+    assert_eq!(
+        BeaconFork::Electra.payload_gindex(PAYLOAD_BLOCK_HASH_FIELD),
+        Ok(44)
+    );
+    assert_eq!(
+        BeaconFork::Altair.payload_gindex(PAYLOAD_BLOCK_HASH_FIELD),
+        Err(BeaconError::PayloadLayout {
+            fork: BeaconFork::Altair
+        })
+    );
+
+    // a deneb branch is one level too deep for a capella block
+    let fixture = fixture(DENEB);
+    let err = BeaconVerifier::verify_payload_block_hash(
+        &block_root(&fixture),
+        BeaconFork::Capella,
+        &payload_header(&fixture).block_hash,
+        &payload_field_branch(&fixture, BeaconFork::Deneb, PAYLOAD_BLOCK_HASH_FIELD),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        BeaconError::BranchLength {
+            expected: 11,
+            found: 12
+        }
+    );
+}
+
+#[test]
+fn rejects_block_hash_of_another_block() {
+    let deneb = fixture(DENEB);
+    let other_hash = payload_header(&fixture(ELECTRA)).block_hash;
+
+    let err = BeaconVerifier::verify_payload_block_hash(
+        &block_root(&deneb),
+        BeaconFork::Deneb,
+        &other_hash,
+        &payload_field_branch(&deneb, BeaconFork::Deneb, PAYLOAD_BLOCK_HASH_FIELD),
+    )
+    .unwrap_err();
+    assert!(matches!(err, BeaconError::RootMismatch { .. }));
+}
+
+#[test]
+fn rejects_field_proven_at_another_position() {
+    let fixture = fixture(ELECTRA);
+    let fork = BeaconFork::Electra;
+    let payload = payload_header(&fixture);
+
+    // the block number branch does not prove the timestamp slot
+    let err = BeaconVerifier::verify_branch(
+        &block_root(&fixture),
+        &uint_chunk(payload.block_number),
+        &payload_field_branch(&fixture, fork, PAYLOAD_BLOCK_NUMBER),
+        payload_field_gindex(fork, PAYLOAD_TIMESTAMP),
+    )
+    .unwrap_err();
+    assert!(matches!(err, BeaconError::RootMismatch { .. }));
+}
+
+#[test]
+fn rejects_truncated_branch() {
+    let fixture = fixture(DENEB);
+    let mut proof = payload_field_branch(&fixture, BeaconFork::Deneb, PAYLOAD_BLOCK_HASH_FIELD);
+    proof.pop();
+
+    let err = BeaconVerifier::verify_payload_block_hash(
+        &block_root(&fixture),
+        BeaconFork::Deneb,
+        &payload_header(&fixture).block_hash,
+        &proof,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        BeaconError::BranchLength {
+            expected: 12,
+            found: 11
+        }
+    );
+}
+
+#[test]
+fn rejects_payload_header_outside_its_fork_layout() {
+    let deneb = payload_header(&fixture(DENEB));
+    let mut capella = deneb.clone();
+    capella.blob_gas_used = None;
+    capella.excess_blob_gas = None;
+
+    assert_eq!(
+        deneb.hash_tree_root(BeaconFork::Capella),
+        Err(BeaconError::PayloadLayout {
+            fork: BeaconFork::Capella
+        })
+    );
+    assert_eq!(
+        capella.hash_tree_root(BeaconFork::Deneb),
+        Err(BeaconError::PayloadLayout {
+            fork: BeaconFork::Deneb
+        })
+    );
+    // bellatrix payloads have no withdrawals root and altair blocks no payload
+    assert_eq!(
+        capella.hash_tree_root(BeaconFork::Bellatrix),
+        Err(BeaconError::PayloadLayout {
+            fork: BeaconFork::Bellatrix
+        })
+    );
+}
+
+#[test]
+fn fork_follows_the_header_timestamp() {
+    let cancun = MAINNET.cancun_timestamp;
+    let prague = MAINNET.prague_timestamp;
+
+    // the first block of a fork commits to the last beacon block before it
+    assert_eq!(BeaconFork::at(&MAINNET, cancun), BeaconFork::Capella);
+    assert_eq!(BeaconFork::at(&MAINNET, cancun + 12), BeaconFork::Deneb);
+    assert_eq!(BeaconFork::at(&MAINNET, prague), BeaconFork::Deneb);
+    assert_eq!(BeaconFork::at(&MAINNET, prague + 12), BeaconFork::Electra);
+    assert_eq!(
+        BeaconFork::at(&MAINNET, MAINNET.shanghai_timestamp),
+        BeaconFork::Bellatrix
+    );
+}
+
+fn validator_inputs(header: LeanHeader) -> BeaconInputs {
+    BeaconInputs {
+        header,
+        validator_index: 5,
+        balance_proof: Vec::new(),
+        balance_chunk: vec![0; 32],
+        effective_balance_proof: Vec::new(),
+        effective_balance_leaf: vec![0; 32],
+    }
+}
+
+#[test]
+fn validator_proofs_take_the_fork_of_the_header() {
+    let header = LeanHeader::decode(&header_rlp("prague_sepolia_8341157.hex")).unwrap();
+    let depth = |fork: BeaconFork| {
+        let gindex = BeaconVerifier::balance_gindex(fork, 5);
+        (63 - gindex.leading_zeros()) as usize
+    };
+    // the electra state is one level deeper, the branch length shows which fork was taken
+    assert_eq!(depth(BeaconFork::Electra), depth(BeaconFork::Deneb) + 1);
+
+    let err = BeaconVerifier::verify_validator(&SEPOLIA, validator_inputs(header)).unwrap_err();
+    assert_eq!(
+        err,
+        BeaconError::BranchLength {
+            expected: depth(BeaconFork::Electra),
+            found: 0
+        }
+    );
+}
+
+#[test]
+fn rejects_validator_proof_before_cancun() {
+    let header = LeanHeader::decode(&header_rlp("shanghai_mainnet_17585076.hex")).unwrap();
+
+    let err = BeaconVerifier::verify_validator(&MAINNET, validator_inputs(header)).unwrap_err();
+    assert_eq!(err, BeaconError::MissingBeaconRoot);
+}
@@ -0,0 +1,111 @@
+//! Log absence over header runs.
+//!
+//! `cancun_sepolia_7342352.hex` is the Sepolia block holding an OP chain deployment, as the
+//! superchain-registry report testdata records it. The deployment's `OPContractsManager` emits
+//! `Deployed` there, so its address and topic must test present. No recorded mainnet header with a
+//! known log was at hand.
+
+mod common;
+
+use alloy_primitives::hex;
+use common::header_rlp;
+use obsidian_lib::bloom::{AbsenceInputs, BloomError, BloomVerifier, LogQuery};
+use obsidian_lib::header::LeanHeader;
+
+/// Two linked headers after the recorded cancun header, with empty blooms.
+fn run() -> Vec<LeanHeader> {
+    let mut first = LeanHeader::decode(&header_rlp("cancun_mainnet_20000000.hex")).unwrap();
+    first.logs_bloom = vec![0; 256];
+    let mut second = first.clone();
+    second.parent_hash = first.hash();
+    second.number += 1;
+    vec![first, second]
+}
+
+/// `OPContractsManager` of the deployment, emitting `Deployed` in the recorded block.
+const OPCM: [u8; 20] = hex!("760b1d2dc68dc51fb6e8b2b8722b8ed08903540c");
+
+/// `Deployed(uint256,uint256,bytes)` topic.
+const DEPLOYED: [u8; 32] = hex!("9fbdf97c6b496bf20189c3c23d0640336fce48e18810c9b84558ec31de0ab9b0");
+
+fn recorded_header() -> LeanHeader {
+    LeanHeader::decode(&header_rlp("cancun_sepolia_7342352.hex")).unwrap()
+}
+
+fn query() -> LogQuery {
+    LogQuery {
+        address: vec![0x42; 20],
+        topics: vec![vec![0x17; 32]],
+    }
+}
+
+#[test]
+fn proves_absence_over_empty_blooms() {
+    let headers = run();
+    let out = BloomVerifier::verify_absence(AbsenceInputs {
+        headers: headers.clone(),
+        query: query(),
+    })
+    .unwrap();
+    assert_eq!(out.first_number, 20_000_000);
+    assert_eq!(out.last_hash, headers[1].hash().to_vec());
+}
+
+#[test]
+fn rejects_bloom_of_the_wrong_size_before_hashing() {
+    let mut headers = run();
+    headers[1].logs_bloom.pop();
+
+    let err = BloomVerifier::verify_absence(AbsenceInputs {
+        headers,
+        query: query(),
+    })
+    .unwrap_err();
+    assert_eq!(err, BloomError::InvalidBloom { length: 255 });
+}
+
+#[test]
+fn recorded_bloom_holds_the_deployed_log() {
+    let header = recorded_header();
+    assert_eq!(
+        header.hash(),
+        hex!("f2071c8d0ee5e344bb56290f6b0e47c0274795a2b2a42772a340ee9e04d1bda9")
+    );
+
+    let bloom = &header.logs_bloom;
+    assert_eq!(BloomVerifier::contains(bloom, &OPCM), Ok(true));
+    assert_eq!(BloomVerifier::contains(bloom, &DEPLOYED), Ok(true));
+    assert_eq!(BloomVerifier::contains(bloom, &[0x42; 20]), Ok(false));
+
+    let err = BloomVerifier::verify_absence(AbsenceInputs {
+        headers: vec![header],
+        query: LogQuery {
+            address: OPCM.to_vec(),
+            topics: vec![DEPLOYED.to_vec()],
+        },
+    })
+    .unwrap_err();
+    assert_eq!(err, BloomError::PossiblyPresent { number: 7_342_352 });
+}
+
+#[test]
+fn proves_absence_of_an_unrelated_emitter_in_the_recorded_block() {
+    let header = recorded_header();
+    // the recorded topic alone does not place the query in the block
+    let query = LogQuery {
+        address: vec![0x42; 20],
+        topics: vec![DEPLOYED.to_vec()],
+    };
+    assert_eq!(
+        BloomVerifier::may_contain(&header.logs_bloom, &query),
+        Ok(false)
+    );
+
+    let out = BloomVerifier::verify_absence(AbsenceInputs {
+        headers: vec![header.clone()],
+        query,
+    })
+    .unwrap();
+    assert_eq!(out.first_hash, header.hash().to_vec());
+    assert_eq!(out.last_number, 7_342_352);
+}
@@ -0,0 +1,186 @@
+//! Fork fields of recorded headers against every registered chain.
+//!
+//! Each layout is a recorded header, `header.rs` lists them. The headers are moved to either side
+//! of every fork boundary of every chain, so a layout one fork behind or ahead of the block's
+//! fork is rejected and the matching one accepted.
+
+mod common;
+
+use common::header_rlp;
+use obsidian_lib::chain_spec::{ChainSpec, Fork, BASE, CHAIN_SPECS, MAINNET, OP_MAINNET, SEPOLIA};
+use obsidian_lib::header::{HeaderError, LeanHeader};
+
+/// The recorded header whose fields end with `fork`'s, frontier for none.
+fn layout(fork: Option<Fork>) -> LeanHeader {
+    let file = match fork {
+        None => "frontier_mainnet_1000001.hex",
+        Some(Fork::London) => "london_mainnet_14037611.hex",
+        Some(Fork::Shanghai) => "shanghai_mainnet_17585076.hex",
+        Some(Fork::Cancun) => "cancun_mainnet_20000000.hex",
+        Some(Fork::Prague) => "prague_sepolia_8341157.hex",
+    };
+    LeanHeader::decode(&header_rlp(file)).unwrap()
+}
+
+fn at(mut header: LeanHeader, number: u64, timestamp: u64) -> LeanHeader {
+    header.number = number;
+    header.timestamp = timestamp;
+    header
+}
+
+/// The first field `fork` adds.
+fn first_field(fork: Fork) -> &'static str {
+    match fork {
+        Fork::London => "base_fee_per_gas",
+        Fork::Shanghai => "withdrawals_root",
+        Fork::Cancun => "blob_gas_used",
+        Fork::Prague => "requests_hash",
+    }
+}
+
+fn fork_field(spec: &ChainSpec, fork: Fork, number: u64, expected: bool) -> HeaderError {
+    HeaderError::ForkField {
+        chain: spec.name,
+        fork,
+        field: first_field(fork),
+        number,
+        expected,
+    }
+}
+
+#[test]
+fn registry_finds_every_chain_by_id() {
+    for spec in &CHAIN_SPECS {
+        assert_eq!(ChainSpec::from_chain_id(spec.chain_id), Some(spec));
+    }
+    assert_eq!(ChainSpec::from_chain_id(5), None);
+}
+
+#[test]
+fn timestamp_forks_take_their_fields_on_every_chain() {
+    for spec in &CHAIN_SPECS {
+        // well past london on every chain
+        let number = spec.london_block + 1_000_000;
+        for (fork, before, activation) in [
+            (Fork::Shanghai, Fork::London, spec.shanghai_timestamp),
+            (Fork::Cancun, Fork::Shanghai, spec.cancun_timestamp),
+            (Fork::Prague, Fork::Cancun, spec.prague_timestamp),
+        ] {
+            let last = activation - 1;
+            at(layout(Some(before)), number, last)
+                .validate(spec)
+                .unwrap_or_else(|e| panic!("{} {}: {}", spec.name, fork, e));
+            at(layout(Some(fork)), number, activation)
+                .validate(spec)
+                .unwrap_or_else(|e| panic!("{} {}: {}", spec.name, fork, e));
+
+            assert_eq!(
+                at(layout(Some(before)), number, activation).validate(spec),
+                Err(fork_field(spec, fork, number, true)),
+                "{} {}",
+                spec.name,
+                fork
+            );
+            assert_eq!(
+                at(layout(Some(fork)), number, last).validate(spec),
+                Err(fork_field(spec, fork, number, false)),
+                "{} {}",
+                spec.name,
+                fork
+            );
+        }
+    }
+}
+
+#[test]
+fn london_takes_its_field_from_its_block_on_every_chain() {
+    for spec in &CHAIN_SPECS {
+        let timestamp = spec.shanghai_timestamp - 1;
+        let london = spec.london_block;
+
+        at(layout(Some(Fork::London)), london, timestamp)
+            .validate(spec)
+            .unwrap();
+        assert_eq!(
+            at(layout(None), london, timestamp).validate(spec),
+            Err(fork_field(spec, Fork::London, london, true)),
+            "{}",
+            spec.name
+        );
+
+        // chains with london from genesis have no frontier blocks at all
+        if london > 0 {
+            at(layout(None), london - 1, timestamp)
+                .validate(spec)
+                .unwrap();
+            assert_eq!(
+                at(layout(Some(Fork::London)), london - 1, timestamp).validate(spec),
+                Err(fork_field(spec, Fork::London, london - 1, false)),
+                "{}",
+                spec.name
+            );
+        }
+    }
+}
+
+#[test]
+fn recorded_headers_validate_on_their_chain_only() {
+    for fork in [Fork::London, Fork::Shanghai, Fork::Cancun] {
+        layout(Some(fork)).validate(&MAINNET).unwrap();
+    }
+    layout(None).validate(&MAINNET).unwrap();
+    let sepolia = layout(Some(Fork::Prague));
+    sepolia.validate(&SEPOLIA).unwrap();
+
+    // the sepolia block number is before london on mainnet
+    assert_eq!(
+        sepolia.validate(&MAINNET),
+        Err(fork_field(&MAINNET, Fork::London, 8_341_157, false))
+    );
+    // so is the mainnet one on OP Mainnet, whose london came with bedrock
+    assert_eq!(
+        layout(Some(Fork::London)).validate(&OP_MAINNET),
+        Err(fork_field(&OP_MAINNET, Fork::London, 14_037_611, false))
+    );
+}
+
+#[test]
+fn isthmus_lags_prague() {
+    // between prague on mainnet and isthmus on the OP-stack chains
+    let timestamp = MAINNET.prague_timestamp + 3600;
+    assert!(timestamp < BASE.prague_timestamp);
+    let number = OP_MAINNET.london_block + 1;
+
+    let prague = at(layout(Some(Fork::Prague)), number, timestamp);
+    prague.validate(&MAINNET).unwrap();
+    let cancun = at(layout(Some(Fork::Cancun)), number, timestamp);
+    for spec in [&BASE, &OP_MAINNET] {
+        cancun.validate(spec).unwrap();
+        assert_eq!(
+            prague.validate(spec),
+            Err(fork_field(spec, Fork::Prague, number, false))
+        );
+    }
+    assert_eq!(
+        cancun.validate(&MAINNET),
+        Err(fork_field(&MAINNET, Fork::Prague, number, true))
+    );
+}
+
+#[test]
+fn names_the_missing_field_of_a_partial_fork() {
+    let spec = &MAINNET;
+    let mut header = layout(Some(Fork::Cancun));
+    header.parent_beacon_block_root = None;
+
+    assert_eq!(
+        header.validate(spec),
+        Err(HeaderError::ForkField {
+            chain: "mainnet",
+            fork: Fork::Cancun,
+            field: "parent_beacon_block_root",
+            number: 20_000_000,
+            expected: true,
+        })
+    );
+}
@@ -0,0 +1,227 @@
+//! Fixture loading shared by the integration tests.
+#![allow(dead_code)]
+
+use alloy_primitives::{hex, keccak256, U256};
+use alloy_trie::proof::ProofRetainer;
+use alloy_trie::{HashBuilder, Nibbles};
+use obsidian_lib::beacon::ExecutionPayloadHeader;
+use obsidian_lib::header::LeanHeader;
+use obsidian_lib::light_client::BeaconBlockHeader;
+use obsidian_lib::verifier::{MPTVerifier, Proofs, StorageProof};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+pub const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// Hex encoded RLP of a header in `fixtures/headers`.
+pub fn header_rlp(name: &str) -> Vec<u8> {
+    let raw = std::fs::read_to_string(format!("{}/headers/{}", FIXTURES, name)).unwrap();
+    hex::decode(raw.trim()).unwrap()
+}
+
+/// A beacon API execution proof in `fixtures/beacon`, as snowbridge records them.
+pub fn beacon_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/beacon/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+pub fn bytes(value: &Value) -> Vec<u8> {
+    hex::decode(value.as_str().unwrap()).unwrap()
+}
+
+pub fn root(value: &Value) -> [u8; 32] {
+    bytes(value).try_into().unwrap()
+}
+
+pub fn number(value: &Value) -> u64 {
+    value.as_u64().unwrap()
+}
+
+pub fn branch(nodes: &Value) -> Vec<Vec<u8>> {
+    nodes.as_array().unwrap().iter().map(bytes).collect()
+}
+
+pub fn beacon_header(fixture: &Value) -> BeaconBlockHeader {
+    block_header(&fixture["header"])
+}
+
+pub fn block_header(header: &Value) -> BeaconBlockHeader {
+    BeaconBlockHeader {
+        slot: number(&header["slot"]),
+        proposer_index: number(&header["proposer_index"]),
+        parent_root: root(&header["parent_root"]),
+        state_root: root(&header["state_root"]),
+        body_root: root(&header["body_root"]),
+    }
+}
+
+/// The recorded payload header, deneb shaped in both fixtures as electra kept the layout.
+pub fn payload_header(fixture: &Value) -> ExecutionPayloadHeader {
+    let payload = &fixture["execution_header"]["Deneb"];
+    ExecutionPayloadHeader {
+        parent_hash: root(&payload["parent_hash"]),
+        fee_recipient: bytes(&payload["fee_recipient"]).try_into().unwrap(),
+        state_root: root(&payload["state_root"]),
+        receipts_root: root(&payload["receipts_root"]),
+        logs_bloom: bytes(&payload["logs_bloom"]),
+        prev_randao: root(&payload["prev_randao"]),
+        block_number: number(&payload["block_number"]),
+        gas_limit: number(&payload["gas_limit"]),
+        gas_used: number(&payload["gas_used"]),
+        timestamp: number(&payload["timestamp"]),
+        extra_data: bytes(&payload["extra_data"]),
+        base_fee_per_gas: U256::from(number(&payload["base_fee_per_gas"])).to_be_bytes(),
+        block_hash: root(&payload["block_hash"]),
+        transactions_root: root(&payload["transactions_root"]),
+        withdrawals_root: root(&payload["withdrawals_root"]),
+        blob_gas_used: Some(number(&payload["blob_gas_used"])),
+        excess_blob_gas: Some(number(&payload["excess_blob_gas"])),
+    }
+}
+
+/// Sibling roots from the leaf at `index` up to the root of `leaves`.
+pub fn ssz_branch(leaves: &[[u8; 32]], index: usize) -> Vec<Vec<u8>> {
+    let mut layer = leaves.to_vec();
+    layer.resize(leaves.len().next_power_of_two(), [0u8; 32]);
+    let mut index = index;
+    let mut branch = Vec::new();
+    while layer.len() > 1 {
+        branch.push(layer[index ^ 1].to_vec());
+        layer = layer
+            .chunks(2)
+            .map(|pair| {
+                Sha256::new()
+                    .chain_update(pair[0])
+                    .chain_update(pair[1])
+                    .finalize()
+                    .into()
+            })
+            .collect();
+        index /= 2;
+    }
+    branch
+}
+
+/// Light client data snowbridge recorded from the beacon API of an electra devnet.
+pub fn light_client_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/light_client/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// Recorded receipts in `fixtures/receipts`.
+pub fn receipt_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/receipts/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// Recorded transactions in `fixtures/transactions`.
+pub fn transaction_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/transactions/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// Recorded withdrawals in `fixtures/withdrawals`.
+pub fn withdrawal_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/withdrawals/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// Recorded execution requests in `fixtures/requests`.
+pub fn request_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/requests/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// An `eth_getProof` response in `fixtures/proofs`.
+pub fn proof_fixture(name: &str) -> Value {
+    let raw = std::fs::read_to_string(format!("{}/proofs/{}.json", FIXTURES, name)).unwrap();
+    serde_json::from_str(&raw).unwrap()
+}
+
+/// The responses carry no block, their state root is the hash of the first account proof node.
+pub fn proof_state_root(response: &Value) -> Vec<u8> {
+    keccak256(bytes(&response["accountProof"][0])).to_vec()
+}
+
+/// A trie keyed by the hash of each key, built by alloy-trie, with the proof for `target`.
+pub fn secure_trie(entries: &[(Vec<u8>, Vec<u8>)], target: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let mut leaves: Vec<(Nibbles, &[u8])> = entries
+        .iter()
+        .map(|(key, value)| (Nibbles::unpack(keccak256(key)), value.as_slice()))
+        .collect();
+    leaves.sort();
+
+    let target = Nibbles::unpack(keccak256(target));
+    let mut builder =
+        HashBuilder::default().with_proof_retainer(ProofRetainer::from_iter([target.clone()]));
+    for (key, value) in leaves {
+        builder.add_leaf(key, value);
+    }
+    let root = builder.root();
+    let proof = builder
+        .take_proof_nodes()
+        .matching_nodes_sorted(&target)
+        .into_iter()
+        .map(|(_, node)| node.to_vec())
+        .collect();
+    (root.to_vec(), proof)
+}
+
+/// A list trie keyed by `rlp(index)`, as the transaction, receipt and withdrawal tries are, built
+/// by alloy-trie, with the proof for `target`.
+pub fn list_trie(values: &[Vec<u8>], target: u64) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let mut leaves: Vec<(Nibbles, &[u8])> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let key = MPTVerifier::index_key(index as u64);
+            (Nibbles::unpack(key), value.as_slice())
+        })
+        .collect();
+    leaves.sort();
+
+    let target = Nibbles::unpack(MPTVerifier::index_key(target));
+    let mut builder =
+        HashBuilder::default().with_proof_retainer(ProofRetainer::from_iter([target.clone()]));
+    for (key, value) in leaves {
+        builder.add_leaf(key, value);
+    }
+    let root = builder.root();
+    let proof = builder
+        .take_proof_nodes()
+        .matching_nodes_sorted(&target)
+        .into_iter()
+        .map(|(_, node)| node.to_vec())
+        .collect();
+    (root.to_vec(), proof)
+}
+
+/// The header a state fixture's proofs were taken against.
+pub fn state_header(fixture: &Value) -> LeanHeader {
+    LeanHeader::decode(&bytes(&fixture["header"])).unwrap()
+}
+
+/// Account and storage proofs of one `eth_getProof` response.
+pub fn proofs(response: &Value) -> Proofs {
+    Proofs {
+        account_proof: branch(&response["accountProof"]),
+        storage_proofs: response["storageProof"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|slot| StorageProof {
+                slot: bytes(&slot["key"]),
+                proof: branch(&slot["proof"]),
+            })
+            .collect(),
+    }
+}
+
+/// Storage value as `eth_getProof` reports it, minimal big endian without the rlp prefix.
+pub fn quantity(value: &Value) -> Vec<u8> {
+    let digits = value.as_str().unwrap().trim_start_matches("0x");
+    if digits == "0" {
+        return Vec::new();
+    }
+    hex::decode(format!("{:0>1$}", digits, digits.len() + digits.len() % 2)).unwrap()
+}
@@ -0,0 +1,240 @@
+use alloy_primitives::U256;
+use obsidian_lib::decoder::{NodeDecoder, RlpError, RlpItem, MAX_DEPTH};
+
+fn string(bytes: &[u8]) -> RlpItem {
+    RlpItem::String(bytes.to_vec())
+}
+
+#[test]
+fn decodes_nested_lists() {
+    // [ [], [[]], [ [], [[]] ] ]
+    let input = [0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0];
+    let empty = RlpItem::List(vec![]);
+    let wrapped = RlpItem::List(vec![empty.clone()]);
+    assert_eq!(
+        NodeDecoder::decode(&input).unwrap(),
+        RlpItem::List(vec![
+            empty.clone(),
+            wrapped.clone(),
+            RlpItem::List(vec![empty, wrapped]),
+        ])
+    );
+}
+
+#[test]
+fn decodes_strings() {
+    assert_eq!(NodeDecoder::decode(&[0x7f]).unwrap(), string(&[0x7f]));
+    assert_eq!(NodeDecoder::decode(&[0x80]).unwrap(), string(&[]));
+    assert_eq!(NodeDecoder::decode(&[0x81, 0x80]).unwrap(), string(&[0x80]));
+
+    let mut long = vec![0xb8, 56];
+    long.extend([0xaa; 56]);
+    assert_eq!(NodeDecoder::decode(&long).unwrap(), string(&[0xaa; 56]));
+}
+
+#[test]
+fn rejects_single_byte_wrapped_as_string() {
+    assert_eq!(
+        NodeDecoder::decode(&[0x81, 0x7f]),
+        Err(RlpError::NonCanonicalSingleByte { offset: 0 })
+    );
+    assert_eq!(
+        NodeDecoder::decode(&[0xc2, 0x81, 0x00]),
+        Err(RlpError::NonCanonicalSingleByte { offset: 1 })
+    );
+}
+
+#[test]
+fn rejects_long_form_for_short_payload() {
+    let mut string = vec![0xb8, 55];
+    string.extend([0xaa; 55]);
+    assert_eq!(
+        NodeDecoder::decode(&string),
+        Err(RlpError::NonCanonicalLength { offset: 1 })
+    );
+
+    let mut list = vec![0xf8, 3];
+    list.extend([0x01, 0x02, 0x03]);
+    assert_eq!(
+        NodeDecoder::decode(&list),
+        Err(RlpError::NonCanonicalLength { offset: 1 })
+    );
+}
+
+#[test]
+fn rejects_length_with_leading_zero() {
+    let mut input = vec![0xb9, 0x00, 0x38];
+    input.extend([0xaa; 56]);
+    assert_eq!(
+        NodeDecoder::decode(&input),
+        Err(RlpError::LeadingZeroLength { offset: 1 })
+    );
+}
+
+#[test]
+fn rejects_length_wider_than_usize() {
+    let mut input = vec![0xbf];
+    input.extend([0xff; 8]);
+    assert!(matches!(
+        NodeDecoder::decode(&input),
+        Err(RlpError::LengthOverflow { .. }) | Err(RlpError::UnexpectedEnd { .. })
+    ));
+}
+
+#[test]
+fn rejects_truncated_input() {
+    assert_eq!(
+        NodeDecoder::decode(&[]),
+        Err(RlpError::UnexpectedEnd {
+            offset: 0,
+            needed: 1
+        })
+    );
+    assert_eq!(
+        NodeDecoder::decode(&[0x83, 0x01, 0x02]),
+        Err(RlpError::UnexpectedEnd {
+            offset: 1,
+            needed: 1
+        })
+    );
+}
+
+#[test]
+fn rejects_trailing_bytes() {
+    assert_eq!(
+        NodeDecoder::decode(&[0x01, 0x02]),
+        Err(RlpError::TrailingBytes {
+            consumed: 1,
+            length: 2
+        })
+    );
+    assert_eq!(
+        NodeDecoder::decode_bytes(&[0x81, 0x80, 0x00]),
+        Err(RlpError::TrailingBytes {
+            consumed: 2,
+            length: 3
+        })
+    );
+    assert_eq!(
+        NodeDecoder::decode_list_raw(&[0xc0, 0xc0]).err(),
+        Some(RlpError::TrailingBytes {
+            consumed: 1,
+            length: 2
+        })
+    );
+}
+
+#[test]
+fn rejects_item_running_past_its_list() {
+    // the list claims one byte but its only item is two bytes long
+    assert_eq!(
+        NodeDecoder::decode(&[0xc1, 0x81, 0x80]),
+        Err(RlpError::ListLengthMismatch { offset: 0 })
+    );
+}
+
+/// `depth` lists wrapped around an empty list.
+fn nested(depth: usize) -> Vec<u8> {
+    let mut input = vec![0xc0];
+    for _ in 0..depth {
+        let mut wrapped = Vec::new();
+        if input.len() < 56 {
+            wrapped.push(0xc0 + input.len() as u8);
+        } else {
+            wrapped.extend([0xf8, input.len() as u8]);
+        }
+        wrapped.extend(input);
+        input = wrapped;
+    }
+    input
+}
+
+#[test]
+fn caps_list_nesting() {
+    assert!(NodeDecoder::decode(&nested(MAX_DEPTH - 1)).is_ok());
+
+    let deep = nested(MAX_DEPTH);
+    // the innermost empty list is the one past the limit
+    assert_eq!(
+        NodeDecoder::decode(&deep),
+        Err(RlpError::DepthExceeded {
+            offset: deep.len() - 1
+        })
+    );
+}
+
+#[test]
+fn rejects_wrong_item_kind() {
+    assert_eq!(
+        NodeDecoder::decode_bytes(&[0xc0]),
+        Err(RlpError::UnexpectedList)
+    );
+    assert_eq!(
+        NodeDecoder::decode_list_raw(&[0x80]).err(),
+        Some(RlpError::UnexpectedString)
+    );
+    assert_eq!(
+        NodeDecoder::decode_rlp(&[0xc2, 0x01, 0xc0]),
+        Err(RlpError::UnexpectedList)
+    );
+}
+
+#[test]
+fn decodes_canonical_integers() {
+    assert_eq!(NodeDecoder::decode_u64(&[0x80]), Ok(0));
+    assert_eq!(NodeDecoder::decode_u64(&[0x82, 0x04, 0x00]), Ok(1024));
+    assert_eq!(
+        NodeDecoder::decode_u64(&[0x82, 0x00, 0x01]),
+        Err(RlpError::LeadingZeroInteger)
+    );
+
+    let mut wide = vec![0x89];
+    wide.extend([0x01; 9]);
+    assert_eq!(
+        NodeDecoder::decode_u64(&wide),
+        Err(RlpError::IntegerOverflow { length: 9 })
+    );
+    assert_eq!(
+        NodeDecoder::decode_u256(&wide),
+        Ok(U256::from_be_slice(&[0x01; 9]))
+    );
+}
+
+#[test]
+fn rejects_invalid_nodes() {
+    // three item list is neither a branch nor a leaf
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&[0xc3, 0x01, 0x02, 0x03]).err(),
+        Some(RlpError::InvalidNode { items: 3 })
+    );
+    // eighteen items is rejected without collecting the rest
+    let mut long = vec![0xd2];
+    long.extend([0x80; 18]);
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&long).err(),
+        Some(RlpError::InvalidNode { items: 18 })
+    );
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&[0xc2, 0x80, 0x01]).err(),
+        Some(RlpError::EmptyNodePath)
+    );
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&[0xc2, 0x40, 0x01]).err(),
+        Some(RlpError::InvalidPathPrefix { prefix: 4 })
+    );
+    // even leaf with a non zero padding nibble
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&[0xc2, 0x21, 0x01]).err(),
+        Some(RlpError::InvalidPathPrefix { prefix: 2 })
+    );
+    // extension pointing at nothing
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&[0xc2, 0x00, 0x80]).err(),
+        Some(RlpError::InvalidChild { length: 0 })
+    );
+    // extension child that is neither a hash nor embedded
+    assert_eq!(
+        NodeDecoder::decode_mpt_node(&[0xc4, 0x00, 0x82, 0x01, 0x02]).err(),
+        Some(RlpError::InvalidChild { length: 2 })
+    );
+}
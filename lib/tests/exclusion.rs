@@ -0,0 +1,173 @@
+use alloy_primitives::{hex, keccak256, U256};
+use alloy_rlp::Encodable;
+use alloy_trie::TrieAccount;
+use obsidian_lib::decoder::NodeDecoder;
+use obsidian_lib::verifier::{
+    MPTVerifier, NodeType, VerifierError, EMPTY_CODE_HASH, EMPTY_ROOT_HASH,
+};
+
+mod common;
+use common::{branch, bytes, proof_fixture, proof_state_root, secure_trie};
+
+/// Hashes to b188e13 like the account of getproof_7ae1d57b, then leaves its leaf's path.
+const DIVERGENT_ADDRESS: [u8; 20] = hex!("000000000000000000000000000000001e2aca5f");
+
+fn storage_value(value: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    value.encode(&mut out);
+    let mut item = Vec::new();
+    out.as_slice().encode(&mut item);
+    item
+}
+
+/// Keys whose hashes share the first nibble, so the root of a trie over them is an extension,
+/// and a key whose hash starts with another nibble.
+fn extension_keys(width: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let key = |i: u64| {
+        let mut key = vec![0u8; width];
+        key[width - 8..].copy_from_slice(&i.to_be_bytes());
+        key
+    };
+    let first = key(0);
+    let nibble = keccak256(&first)[0] >> 4;
+    let second = (1..)
+        .map(key)
+        .find(|k| {
+            let hash = keccak256(k);
+            // the extension stops after the shared nibble
+            hash[0] >> 4 == nibble && hash[0] & 0x0f != keccak256(&first)[0] & 0x0f
+        })
+        .unwrap();
+    let other = (1..)
+        .map(key)
+        .find(|k| keccak256(k)[0] >> 4 != nibble)
+        .unwrap();
+    (first, second, other)
+}
+
+fn is_extension(node: &[u8]) -> bool {
+    matches!(
+        NodeDecoder::decode_mpt_node(node).unwrap().node,
+        NodeType::Extension(..)
+    )
+}
+
+#[test]
+fn account_absent_at_empty_branch_slot() {
+    // the EIP-1186 example proof stops at a branch with no child for the address's nibble
+    let response = proof_fixture("eip1186_example");
+    let proof = branch(&response["accountProof"]);
+
+    let state = MPTVerifier::verify_account_state(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    )
+    .unwrap();
+    assert!(state.is_none());
+
+    MPTVerifier::verify_account_exclusion(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn account_absent_at_divergent_leaf() {
+    let response = proof_fixture("getproof_7ae1d57b");
+    let proof = branch(&response["accountProof"]);
+
+    MPTVerifier::verify_account_exclusion(
+        &proof_state_root(&response),
+        DIVERGENT_ADDRESS.to_vec(),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    )
+    .unwrap();
+
+    // the leaf proves the recorded account itself is present
+    let result = MPTVerifier::verify_account_exclusion(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(result, Err(VerifierError::UnexpectedInclusion));
+}
+
+#[test]
+fn account_absent_at_divergent_extension() {
+    let (first, second, other) = extension_keys(20);
+    let account = |nonce: u64| {
+        alloy_rlp::encode(TrieAccount {
+            nonce,
+            balance: U256::ZERO,
+            storage_root: EMPTY_ROOT_HASH.into(),
+            code_hash: EMPTY_CODE_HASH.into(),
+        })
+    };
+    let (root, proof) = secure_trie(&[(first, account(1)), (second, account(2))], &other);
+    assert!(is_extension(&proof[0]));
+
+    MPTVerifier::verify_account_exclusion(&root, other, MPTVerifier::decode_proof(&proof).unwrap())
+        .unwrap();
+}
+
+#[test]
+fn slot_absent_at_empty_branch_slot() {
+    // slot 0 of the recorded account is unset, its proof stops at an empty branch slot
+    let response = proof_fixture("getproof_7ae1d57b");
+    let slot = &response["storageProof"][0];
+    assert_eq!(slot["value"], "0x0");
+
+    MPTVerifier::verify_slot_exclusion(
+        &bytes(&response["storageHash"]),
+        bytes(&slot["key"]),
+        MPTVerifier::decode_proof(&branch(&slot["proof"])).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn slot_absent_at_divergent_leaf() {
+    // a single slot trie is one leaf, every other slot diverges from it
+    let set = [0u8; 32].to_vec();
+    let mut unset = [0u8; 32];
+    unset[31] = 1;
+    let (root, proof) = secure_trie(&[(set.clone(), storage_value(7))], &unset);
+
+    MPTVerifier::verify_slot_exclusion(
+        &root,
+        unset.to_vec(),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    )
+    .unwrap();
+
+    let value =
+        MPTVerifier::verify_and_get_slot(&root, set, MPTVerifier::decode_proof(&proof).unwrap())
+            .unwrap();
+    assert_eq!(value, [7]);
+}
+
+#[test]
+fn slot_absent_at_divergent_extension() {
+    let (first, second, other) = extension_keys(32);
+    let (root, proof) = secure_trie(
+        &[(first, storage_value(1)), (second, storage_value(2))],
+        &other,
+    );
+    // the root extension is the whole proof
+    assert_eq!(proof.len(), 1);
+    assert!(is_extension(&proof[0]));
+
+    MPTVerifier::verify_slot_exclusion(
+        &root,
+        other.clone(),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        MPTVerifier::verify_storage_slot(&root, other, MPTVerifier::decode_proof(&proof).unwrap()),
+        Ok(None)
+    );
+}
@@ -0,0 +1,188 @@
+//! Header decoding against recorded RLP, one header per layout.
+//!
+//! Every fixture is a recorded block, checked against the hash its chain reports. Frontier,
+//! London, Shanghai and Cancun are mainnet blocks: London is the header ethportal-api records, and
+//! Shanghai the header rebuilt from the builder payload of `withdrawals.rs`. No recorded mainnet
+//! Prague header was at hand, the Prague fixture is Sepolia block 8341157.
+
+mod common;
+
+use alloy_primitives::{hex, keccak256};
+use alloy_rlp::{Encodable, Header};
+use common::header_rlp;
+use obsidian_lib::decoder::NodeDecoder;
+use obsidian_lib::header::{HeaderError, HeaderFields, LeanHeader};
+
+struct Fixture {
+    file: &'static str,
+    fields: usize,
+    number: u64,
+    hash: &'static str,
+}
+
+const FIXTURES: [Fixture; 5] = [
+    Fixture {
+        file: "frontier_mainnet_1000001.hex",
+        fields: 15,
+        number: 1_000_001,
+        hash: "cb5cab7266694daa0d28cbf40496c08dd30bf732c41e0455e7ad389c10d79f4f",
+    },
+    Fixture {
+        file: "london_mainnet_14037611.hex",
+        fields: 16,
+        number: 14_037_611,
+        hash: "a8227474afb7372058aceb724e44fd32bcebf3d39bc2e5e00dcdda2e442eebde",
+    },
+    Fixture {
+        file: "shanghai_mainnet_17585076.hex",
+        fields: 17,
+        number: 17_585_076,
+        hash: "54732505f10ea67ae1ececf3dd4aa0bef1d78770f234bd26ad2cd1eec0b36596",
+    },
+    Fixture {
+        file: "cancun_mainnet_20000000.hex",
+        fields: 20,
+        number: 20_000_000,
+        hash: "d24fd73f794058a3807db926d8898c6481e902b7edb91ce0d479d6760f276183",
+    },
+    Fixture {
+        file: "prague_sepolia_8341157.hex",
+        fields: 21,
+        number: 8_341_157,
+        hash: "0eea59ec81aaf5c8382efd0ae3885003965b9fb26680ec54afefdb51341abbb7",
+    },
+];
+
+/// Which of the fields after the 15 frontier ones the header carries, in list order.
+fn optional_fields(header: &LeanHeader) -> [bool; 6] {
+    [
+        header.base_fee_per_gas.is_some(),
+        header.withdrawals_root.is_some(),
+        header.blob_gas_used.is_some(),
+        header.excess_blob_gas.is_some(),
+        header.parent_beacon_block_root.is_some(),
+        header.requests_hash.is_some(),
+    ]
+}
+
+/// The header list with `extra` appended as one more item.
+fn with_extra_field(raw: &[u8], extra: &[u8]) -> Vec<u8> {
+    let mut payload: Vec<u8> = NodeDecoder::decode_list_raw(raw).unwrap().concat();
+    extra.encode(&mut payload);
+    let mut out = Vec::new();
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut out);
+    out.extend(payload);
+    out
+}
+
+#[test]
+fn decodes_every_layout() {
+    for fixture in &FIXTURES {
+        let raw = header_rlp(fixture.file);
+        let header = LeanHeader::decode(&raw).unwrap();
+
+        assert_eq!(header.number, fixture.number, "{}", fixture.file);
+        assert_eq!(
+            optional_fields(&header)
+                .iter()
+                .filter(|&&present| present)
+                .count(),
+            fixture.fields - 15,
+            "{}",
+            fixture.file
+        );
+        // fields of later forks are only present after the ones before them
+        assert!(optional_fields(&header)
+            .windows(2)
+            .all(|pair| pair[0] || !pair[1]));
+    }
+}
+
+#[test]
+fn reencodes_to_the_recorded_bytes() {
+    for fixture in &FIXTURES {
+        let raw = header_rlp(fixture.file);
+        let header = LeanHeader::decode(&raw).unwrap();
+
+        let mut encoded = Vec::new();
+        header.encode(&mut encoded);
+        assert_eq!(encoded, raw, "{}", fixture.file);
+        assert_eq!(header.hash(), keccak256(&raw).0, "{}", fixture.file);
+        assert_eq!(
+            header.hash().to_vec(),
+            hex::decode(fixture.hash).unwrap(),
+            "{}",
+            fixture.file
+        );
+    }
+}
+
+#[test]
+fn reads_fields_without_full_decoding() {
+    for fixture in &FIXTURES {
+        let raw = header_rlp(fixture.file);
+        let header = LeanHeader::decode(&raw).unwrap();
+        let fields = HeaderFields::decode(&raw).unwrap();
+
+        assert_eq!(fields.hash, keccak256(&raw).0, "{}", fixture.file);
+        assert_eq!(fields.parent_hash, header.parent_hash);
+        assert_eq!(fields.state_root, header.state_root);
+        assert_eq!(fields.number, header.number);
+        assert_eq!(fields.timestamp, header.timestamp);
+    }
+}
+
+#[test]
+fn rejects_truncated_headers() {
+    for fixture in &FIXTURES {
+        let raw = header_rlp(fixture.file);
+        for length in [raw.len() - 1, raw.len() / 2, 3] {
+            let truncated = &raw[..length];
+            assert!(
+                matches!(LeanHeader::decode(truncated), Err(HeaderError::Decode(_))),
+                "{} cut to {}",
+                fixture.file,
+                length
+            );
+            assert!(
+                matches!(HeaderFields::decode(truncated), Err(HeaderError::Decode(_))),
+                "{} cut to {}",
+                fixture.file,
+                length
+            );
+        }
+    }
+}
+
+#[test]
+fn rejects_headers_with_extra_fields() {
+    let raw = with_extra_field(&header_rlp("prague_sepolia_8341157.hex"), &[0x42; 32]);
+
+    assert!(matches!(
+        LeanHeader::decode(&raw),
+        Err(HeaderError::InvalidHeader { items: 22 })
+    ));
+    assert_eq!(
+        HeaderFields::decode(&raw),
+        Err(HeaderError::InvalidHeader { items: 22 })
+    );
+}
+
+#[test]
+fn rejects_bytes_after_the_header() {
+    let mut raw = header_rlp("cancun_mainnet_20000000.hex");
+    raw.push(0x80);
+
+    assert!(matches!(
+        LeanHeader::decode(&raw),
+        Err(HeaderError::Decode(_))
+    ));
+    assert!(matches!(
+        HeaderFields::decode(&raw),
+        Err(HeaderError::Decode(_))
+    ));
+}
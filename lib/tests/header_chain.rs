@@ -0,0 +1,287 @@
+//! Consensus rules between headers, from the recorded cancun header onward.
+
+mod common;
+
+use common::header_rlp;
+use obsidian_lib::chain_spec::{
+    ChainSpec, BASE, BPO1_BLOBS, BPO2_BLOBS, CANCUN_BLOBS, GAS_PER_BLOB, MAINNET, OP_MAINNET,
+    OSAKA_BLOBS, PRAGUE_BLOBS, SEPOLIA,
+};
+use obsidian_lib::header::{HeaderError, HeaderVerifier, LeanHeader};
+
+const MAINNET_BPO2: u64 = 1_767_747_671;
+const JOVIAN: u64 = 1_764_691_201;
+
+fn cancun_header() -> LeanHeader {
+    LeanHeader::decode(&header_rlp("cancun_mainnet_20000000.hex")).unwrap()
+}
+
+/// A prague header on `spec` at `timestamp`, shaped like the recorded cancun header.
+fn parent_at(timestamp: u64) -> LeanHeader {
+    let mut header = cancun_header();
+    header.timestamp = timestamp;
+    header.requests_hash = Some([0xe3; 32]);
+    header
+}
+
+/// The child `spec` expects after `parent` twelve seconds later, with `blob_gas_used`.
+fn child_of(spec: &ChainSpec, parent: &LeanHeader, blob_gas_used: u64) -> LeanHeader {
+    let mut child = parent.clone();
+    child.parent_hash = parent.hash();
+    child.number = parent.number + 1;
+    child.timestamp = parent.timestamp + 12;
+    child.gas_used = parent.gas_limit / 3;
+    child.base_fee_per_gas =
+        Some(HeaderVerifier::next_base_fee(spec, parent, child.timestamp).unwrap());
+    child.blob_gas_used = Some(blob_gas_used);
+    child.excess_blob_gas = Some(match spec.blob_params(child.timestamp) {
+        Some(blobs) => HeaderVerifier::next_excess_blob_gas(spec, parent, blobs),
+        None => 0,
+    });
+    child
+}
+
+/// Version 0 extra data: denominator and elasticity.
+fn holocene_extra_data(denominator: u32, elasticity: u32) -> Vec<u8> {
+    let mut extra = vec![0];
+    extra.extend_from_slice(&denominator.to_be_bytes());
+    extra.extend_from_slice(&elasticity.to_be_bytes());
+    extra
+}
+
+/// Version 1 extra data: denominator, elasticity and minimum base fee.
+fn jovian_extra_data(denominator: u32, elasticity: u32, min_base_fee: u64) -> Vec<u8> {
+    let mut extra = vec![1];
+    extra.extend_from_slice(&denominator.to_be_bytes());
+    extra.extend_from_slice(&elasticity.to_be_bytes());
+    extra.extend_from_slice(&min_base_fee.to_be_bytes());
+    extra
+}
+
+#[test]
+fn verifies_recorded_header_alone() {
+    let header = cancun_header();
+    let ancestry = HeaderVerifier::verify_chain(&MAINNET, &[header.clone()]).unwrap();
+    assert_eq!(ancestry.target_hash, header.hash());
+    assert_eq!(ancestry.anchor_number, 20_000_000);
+}
+
+#[test]
+fn verify_chain_checks_the_first_header() {
+    let mut header = cancun_header();
+    header.parent_beacon_block_root = None;
+    let child = child_of(&MAINNET, &header, 0);
+    assert!(matches!(
+        HeaderVerifier::verify_chain(&MAINNET, &[header, child]),
+        Err(HeaderError::ForkField {
+            field: "parent_beacon_block_root",
+            ..
+        })
+    ));
+
+    let mut header = cancun_header();
+    header.gas_used = header.gas_limit + 1;
+    assert_eq!(
+        HeaderVerifier::verify_chain(&MAINNET, &[header]).unwrap_err(),
+        HeaderError::GasUsedExceedsLimit { number: 20_000_000 }
+    );
+}
+
+#[test]
+fn blob_schedule_follows_fork_timestamps() {
+    assert_eq!(MAINNET.blob_params(1_710_338_134), None);
+    assert_eq!(MAINNET.blob_params(1_710_338_135), Some(CANCUN_BLOBS));
+    assert_eq!(MAINNET.blob_params(1_746_612_311), Some(PRAGUE_BLOBS));
+    assert_eq!(MAINNET.blob_params(1_764_798_551), Some(OSAKA_BLOBS));
+    assert_eq!(MAINNET.blob_params(1_765_290_071), Some(BPO1_BLOBS));
+    assert_eq!(MAINNET.blob_params(MAINNET_BPO2), Some(BPO2_BLOBS));
+    assert_eq!(BASE.blob_params(MAINNET_BPO2), None);
+
+    assert_eq!(BPO1_BLOBS.target_blob_gas, 10 * GAS_PER_BLOB);
+    assert_eq!(BPO1_BLOBS.max_blob_gas, 15 * GAS_PER_BLOB);
+    assert_eq!(BPO2_BLOBS.target_blob_gas, 14 * GAS_PER_BLOB);
+    assert_eq!(BPO2_BLOBS.max_blob_gas, 21 * GAS_PER_BLOB);
+}
+
+#[test]
+fn reserve_price_keeps_excess_blob_gas_growing() {
+    let mut parent = parent_at(MAINNET_BPO2 + 12);
+    parent.excess_blob_gas = Some(14 * GAS_PER_BLOB);
+    parent.blob_gas_used = Some(15 * GAS_PER_BLOB);
+    // a blob costs far less than 8192 gas at a 1 gwei base fee
+    parent.base_fee_per_gas = Some(1_000_000_000);
+
+    let child = child_of(&MAINNET, &parent, 0);
+    // 14 blobs of excess, plus 15 used scaled by (21 - 14) / 21
+    assert_eq!(child.excess_blob_gas, Some(19 * GAS_PER_BLOB));
+    HeaderVerifier::verify_child(&MAINNET, &parent, &child).unwrap();
+
+    let mut unreserved = child.clone();
+    unreserved.excess_blob_gas = Some(15 * GAS_PER_BLOB);
+    assert_eq!(
+        HeaderVerifier::verify_child(&MAINNET, &parent, &unreserved).unwrap_err(),
+        HeaderError::ExcessBlobGasMismatch {
+            number: child.number,
+            expected: 19 * GAS_PER_BLOB,
+            found: 15 * GAS_PER_BLOB,
+        }
+    );
+}
+
+#[test]
+fn excess_blob_gas_drops_by_target_above_reserve_price() {
+    let mut parent = parent_at(MAINNET_BPO2 + 12);
+    parent.excess_blob_gas = Some(14 * GAS_PER_BLOB);
+    parent.blob_gas_used = Some(15 * GAS_PER_BLOB);
+    parent.base_fee_per_gas = Some(7);
+
+    let child = child_of(&MAINNET, &parent, 0);
+    assert_eq!(child.excess_blob_gas, Some(15 * GAS_PER_BLOB));
+    HeaderVerifier::verify_child(&MAINNET, &parent, &child).unwrap();
+}
+
+#[test]
+fn bpo_blob_limits_apply_from_their_timestamp() {
+    // the child is the first block of bpo2
+    let parent = parent_at(MAINNET_BPO2 - 12);
+    let child = child_of(&MAINNET, &parent, 21 * GAS_PER_BLOB);
+    HeaderVerifier::verify_child(&MAINNET, &parent, &child).unwrap();
+
+    let child = child_of(&MAINNET, &parent, 22 * GAS_PER_BLOB);
+    assert_eq!(
+        HeaderVerifier::verify_child(&MAINNET, &parent, &child).unwrap_err(),
+        HeaderError::InvalidBlobGasUsed {
+            number: child.number
+        }
+    );
+}
+
+#[test]
+fn jovian_extra_data_sets_a_minimum_base_fee() {
+    let mut parent = parent_at(JOVIAN + 2);
+    parent.base_fee_per_gas = Some(1_000);
+    parent.gas_used = 0;
+    parent.blob_gas_used = Some(0);
+    parent.excess_blob_gas = Some(0);
+    parent.extra_data = jovian_extra_data(250, 6, 25_000_000);
+
+    let child = child_of(&BASE, &parent, 40_000);
+    assert_eq!(child.base_fee_per_gas, Some(25_000_000));
+    HeaderVerifier::verify_child(&BASE, &parent, &child).unwrap();
+}
+
+#[test]
+fn jovian_bills_the_data_availability_footprint() {
+    let mut parent = parent_at(JOVIAN + 2);
+    parent.base_fee_per_gas = Some(1_000_000);
+    parent.gas_used = 0;
+    parent.blob_gas_used = Some(parent.gas_limit);
+    parent.extra_data = jovian_extra_data(250, 6, 0);
+
+    let child = child_of(&BASE, &parent, 0);
+    assert!(child.base_fee_per_gas.unwrap() > 1_000_000);
+}
+
+#[test]
+fn rejects_extra_data_of_the_wrong_version() {
+    // holocene extra data after jovian
+    let mut parent = parent_at(JOVIAN + 2);
+    parent.extra_data = vec![0, 0, 0, 0, 250, 0, 0, 0, 6];
+    assert_eq!(
+        HeaderVerifier::next_base_fee(&BASE, &parent, parent.timestamp + 2).unwrap_err(),
+        HeaderError::InvalidExtraData {
+            number: parent.number
+        }
+    );
+
+    // jovian extra data before jovian
+    let mut parent = parent_at(JOVIAN - 100);
+    parent.extra_data = jovian_extra_data(250, 6, 1);
+    assert_eq!(
+        HeaderVerifier::next_base_fee(&BASE, &parent, parent.timestamp + 2).unwrap_err(),
+        HeaderError::InvalidExtraData {
+            number: parent.number
+        }
+    );
+}
+
+#[test]
+fn extra_data_follows_holocene_and_jovian_on_every_op_chain() {
+    for spec in [&BASE, &OP_MAINNET] {
+        let op = spec.op_stack.unwrap();
+        let base_fee = |timestamp: u64, extra_data: Vec<u8>| {
+            let mut parent = parent_at(timestamp);
+            parent.base_fee_per_gas = Some(1_000_000);
+            parent.gas_used = parent.gas_limit;
+            parent.extra_data = extra_data;
+            HeaderVerifier::next_base_fee(spec, &parent, timestamp + 2)
+        };
+        let invalid = Err(HeaderError::InvalidExtraData { number: 20_000_000 });
+        // the recorded builder tag
+        let recorded = cancun_header().extra_data;
+
+        // before holocene the extra data is free and canyon's parameters apply
+        let canyon = base_fee(op.holocene_timestamp - 1, recorded.clone()).unwrap();
+        assert_eq!(base_fee(op.holocene_timestamp, recorded.clone()), invalid);
+
+        // zero parameters keep canyon's, others replace them
+        let holocene = op.holocene_timestamp;
+        assert_eq!(base_fee(holocene, holocene_extra_data(0, 0)), Ok(canyon));
+        assert_eq!(base_fee(holocene, holocene_extra_data(250, 6)), Ok(canyon));
+        assert!(base_fee(holocene, holocene_extra_data(50, 6)).unwrap() > canyon);
+        assert_eq!(base_fee(holocene, holocene_extra_data(0, 6)), invalid);
+        assert_eq!(base_fee(holocene, holocene_extra_data(250, 0)), invalid);
+        assert_eq!(base_fee(holocene, jovian_extra_data(250, 6, 0)), invalid);
+        assert_eq!(
+            base_fee(holocene, holocene_extra_data(250, 6)[..8].to_vec()),
+            invalid
+        );
+
+        // jovian switches to version 1 with its minimum base fee
+        let jovian = op.jovian_timestamp;
+        assert_eq!(
+            base_fee(jovian - 1, holocene_extra_data(250, 6)),
+            Ok(canyon)
+        );
+        assert_eq!(base_fee(jovian, holocene_extra_data(250, 6)), invalid);
+        assert_eq!(base_fee(jovian, jovian_extra_data(250, 6, 0)), Ok(canyon));
+        assert_eq!(
+            base_fee(jovian, jovian_extra_data(250, 6, u64::MAX)),
+            Ok(u64::MAX)
+        );
+        let mut trailing = jovian_extra_data(250, 6, 0);
+        trailing.push(0);
+        assert_eq!(base_fee(jovian, trailing), invalid);
+    }
+}
+
+#[test]
+fn ethereum_chains_ignore_op_extra_data() {
+    let mut parent = parent_at(JOVIAN + 2);
+    let expected = HeaderVerifier::next_base_fee(&MAINNET, &parent, parent.timestamp + 12).unwrap();
+
+    parent.extra_data = jovian_extra_data(1, 1, u64::MAX);
+    assert_eq!(
+        HeaderVerifier::next_base_fee(&MAINNET, &parent, parent.timestamp + 12),
+        Ok(expected)
+    );
+}
+
+#[test]
+fn rejects_block_numbers_past_the_last_one() {
+    // sepolia had london from genesis, so block 0 may carry every field
+    let mut parent = cancun_header();
+    parent.number = u64::MAX;
+    let mut child = child_of(&SEPOLIA, &cancun_header(), 0);
+    child.parent_hash = parent.hash();
+    child.number = 0;
+
+    assert_eq!(
+        HeaderVerifier::verify_child(&SEPOLIA, &parent, &child).unwrap_err(),
+        HeaderError::NonConsecutive { number: 0 }
+    );
+    assert_eq!(
+        HeaderVerifier::verify_ancestry(&[parent, child]).unwrap_err(),
+        HeaderError::NonConsecutive { number: 0 }
+    );
+}
@@ -0,0 +1,165 @@
+//! EIP-2935 history proofs between two recorded Prague headers.
+//!
+//! `prague_sepolia_8339830.hex` and `prague_sepolia_8341157.hex` are Sepolia blocks 1327 apart,
+//! re-encoded from the blocks the superchain registry records and checked against their hashes.
+//! No `eth_getProof` of the history contract was at hand, so its storage is rebuilt by alloy-trie
+//! from hashes the recorded headers carry, and the later header stands in as the anchor with its
+//! state root swapped for the rebuilt one.
+
+mod common;
+
+use alloy_primitives::{hex, keccak256, U256};
+use alloy_trie::TrieAccount;
+use common::{header_rlp, secure_trie};
+use obsidian_lib::header::LeanHeader;
+use obsidian_lib::history::{
+    HistoryError, HistoryProof, HistoryVerifier, HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS,
+};
+use obsidian_lib::verifier::VerifierError;
+
+/// Runtime code of the history contract, as the EIP deploys it.
+const HISTORY_STORAGE_CODE: [u8; 83] = hex!("3373fffffffffffffffffffffffffffffffffffffffe14604657602036036042575f35600143038111604257611fff81430311604257611fff9006545f5260205ff35b5f5ffd5b5f35611fff60014303065500");
+
+fn target() -> LeanHeader {
+    LeanHeader::decode(&header_rlp("prague_sepolia_8339830.hex")).unwrap()
+}
+
+fn recorded_anchor() -> LeanHeader {
+    LeanHeader::decode(&header_rlp("prague_sepolia_8341157.hex")).unwrap()
+}
+
+/// The ring buffer as it holds the recorded hashes: the target's, its parent's and the anchor's
+/// parent's.
+fn ring_buffer(anchor: &LeanHeader, target: &LeanHeader) -> Vec<(Vec<u8>, Vec<u8>)> {
+    [
+        (target.number, target.hash()),
+        (target.number - 1, target.parent_hash),
+        (anchor.number - 1, anchor.parent_hash),
+    ]
+    .into_iter()
+    .map(|(number, hash)| {
+        (
+            HistoryVerifier::slot(number),
+            alloy_rlp::encode(hash.as_slice()),
+        )
+    })
+    .collect()
+}
+
+/// The recorded anchor over a state holding only the history contract, with its proofs for
+/// `number`'s slot.
+fn history_proof(slots: &[(Vec<u8>, Vec<u8>)], number: u64) -> HistoryProof {
+    let (storage_root, storage_proof) = secure_trie(slots, &HistoryVerifier::slot(number));
+    let account = alloy_rlp::encode(TrieAccount {
+        nonce: 1,
+        balance: U256::ZERO,
+        storage_root: storage_root.as_slice().try_into().unwrap(),
+        code_hash: keccak256(HISTORY_STORAGE_CODE),
+    });
+    let (state_root, account_proof) = secure_trie(
+        &[(HISTORY_STORAGE_ADDRESS.to_vec(), account)],
+        &HISTORY_STORAGE_ADDRESS,
+    );
+
+    let mut anchor = recorded_anchor();
+    anchor.state_root = state_root.try_into().unwrap();
+    HistoryProof {
+        anchor,
+        account_proof,
+        storage_proof,
+    }
+}
+
+#[test]
+fn slot_wraps_around_the_window() {
+    assert_eq!(HistoryVerifier::slot(8339830)[24..], 1392u64.to_be_bytes());
+    assert_eq!(
+        HistoryVerifier::slot(8339830),
+        HistoryVerifier::slot(8339830 + HISTORY_SERVE_WINDOW)
+    );
+}
+
+#[test]
+fn proves_recorded_block_from_the_anchor() {
+    let target = target();
+    let proof = history_proof(&ring_buffer(&recorded_anchor(), &target), target.number);
+    let anchor_hash = proof.anchor.hash();
+
+    let ancestry = HistoryVerifier::verify(&target, proof).unwrap();
+    assert_eq!(
+        ancestry.target_hash,
+        hex!("689f13093fa3bbde79bc5bea7025638512d58c051f2507db764430d805473b43")
+    );
+    assert_eq!(ancestry.target_number, 8339830);
+    assert_eq!(ancestry.anchor_hash, anchor_hash);
+    assert_eq!(ancestry.anchor_number, 8341157);
+}
+
+#[test]
+fn rejects_target_outside_the_window() {
+    let target = target();
+    let slots = ring_buffer(&recorded_anchor(), &target);
+
+    // the anchor's own hash is not stored until its child
+    let mut proof = history_proof(&slots, target.number);
+    proof.anchor.number = target.number;
+    assert_eq!(
+        HistoryVerifier::verify(&target, proof).unwrap_err(),
+        HistoryError::OutOfWindow {
+            target: 8339830,
+            anchor: 8339830,
+        }
+    );
+
+    // one block past the window the slot already holds a later hash
+    let mut proof = history_proof(&slots, target.number);
+    proof.anchor.number = target.number + HISTORY_SERVE_WINDOW + 1;
+    assert_eq!(
+        HistoryVerifier::verify(&target, proof).unwrap_err(),
+        HistoryError::OutOfWindow {
+            target: 8339830,
+            anchor: 8339830 + HISTORY_SERVE_WINDOW + 1,
+        }
+    );
+}
+
+#[test]
+fn rejects_header_not_matching_the_stored_hash() {
+    let recorded = target();
+    let proof = history_proof(&ring_buffer(&recorded_anchor(), &recorded), recorded.number);
+
+    let mut target = recorded.clone();
+    target.gas_used += 1;
+    assert_eq!(
+        HistoryVerifier::verify(&target, proof).unwrap_err(),
+        HistoryError::HashMismatch { number: 8339830 }
+    );
+}
+
+#[test]
+fn rejects_unset_slot() {
+    // the parent's slot is set, the target's is not
+    let target = target();
+    let slots: Vec<_> = ring_buffer(&recorded_anchor(), &target)
+        .into_iter()
+        .skip(1)
+        .collect();
+    let proof = history_proof(&slots, target.number);
+
+    assert_eq!(
+        HistoryVerifier::verify(&target, proof).unwrap_err(),
+        HistoryError::HashMismatch { number: 8339830 }
+    );
+}
+
+#[test]
+fn rejects_anchor_over_another_state() {
+    let target = target();
+    let mut proof = history_proof(&ring_buffer(&recorded_anchor(), &target), target.number);
+    proof.anchor = recorded_anchor();
+
+    assert_eq!(
+        HistoryVerifier::verify(&target, proof).unwrap_err(),
+        HistoryError::Verifier(VerifierError::HashMismatch { depth: 0 })
+    );
+}
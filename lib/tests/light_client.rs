@@ -0,0 +1,404 @@
+//! Light client updates.
+//!
+//! `fixtures/light_client` holds a bootstrap checkpoint and updates snowbridge recorded from the
+//! beacon API of an electra devnet (Apache-2.0), signed by the devnet's own sync committees. The
+//! devnet started at electra with mainnet's fork versions and presets, under its own validators
+//! root. Its updates cross from period 0 into period 1. They carry no finalized payload, and the
+//! devnet's first two committees hold the same keys.
+//!
+//! The other updates finalize the recorded devnet blocks of `fixtures/beacon`, attested and
+//! signed by a generated committee.
+
+mod common;
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    G1Affine, G1Projective, G2Affine, G2Projective, Scalar,
+};
+use common::{
+    beacon_fixture, beacon_header, block_header, header_rlp, light_client_fixture, payload_header,
+};
+use obsidian_lib::beacon::{
+    BeaconError, BeaconFork, BeaconVerifier, STATE_CURRENT_SYNC_COMMITTEE_FIELD,
+    STATE_FINALIZED_CHECKPOINT_FIELD,
+};
+use obsidian_lib::header::LeanHeader;
+use obsidian_lib::light_client::{
+    BeaconBlockHeader, ForkVersion, LightClientBootstrap, LightClientConfig, LightClientError,
+    LightClientInputs, LightClientStore, LightClientUpdate, LightClientVerifier, SyncCommittee,
+    SLOTS_PER_EPOCH, SYNC_COMMITTEE_SIZE,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+fn sha256_pair(left: &[u8], right: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Committee whose member `i` holds the secret key `i + 1`.
+fn committee() -> SyncCommittee {
+    let mut point = G1Projective::identity();
+    let mut pubkeys = Vec::with_capacity(SYNC_COMMITTEE_SIZE);
+    for _ in 0..SYNC_COMMITTEE_SIZE {
+        point += G1Projective::generator();
+        pubkeys.push(G1Affine::from(point).to_compressed().to_vec());
+    }
+    let aggregate = G1Projective::generator() * committee_secret();
+    SyncCommittee {
+        pubkeys,
+        aggregate_pubkey: G1Affine::from(aggregate).to_compressed().to_vec(),
+    }
+}
+
+/// Sum of every member's secret key, signing with it aggregates all of their signatures.
+fn committee_secret() -> Scalar {
+    Scalar::from((SYNC_COMMITTEE_SIZE * (SYNC_COMMITTEE_SIZE + 1) / 2) as u64)
+}
+
+/// Root of `leaf` hashed up through `branch` along `gindex`.
+fn branch_root(leaf: [u8; 32], branch: &[Vec<u8>], gindex: u64) -> [u8; 32] {
+    branch
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| {
+            if (gindex >> level) & 1 == 1 {
+                sha256_pair(sibling, &node)
+            } else {
+                sha256_pair(&node, sibling)
+            }
+        })
+}
+
+/// Update finalizing the fixture's block, attested 64 slots later and signed by every member.
+fn update(config: &LightClientConfig, fixture: &Value, layout: BeaconFork) -> LightClientUpdate {
+    let finalized_header = beacon_header(fixture);
+
+    let finality_gindex =
+        (64 << (layout == BeaconFork::Electra) as u32) + STATE_FINALIZED_CHECKPOINT_FIELD * 2 + 1;
+    let finality_branch: Vec<Vec<u8>> = (0..63 - finality_gindex.leading_zeros())
+        .map(|level| vec![level as u8 + 1; 32])
+        .collect();
+    let attested_header = BeaconBlockHeader {
+        slot: finalized_header.slot + 2 * SLOTS_PER_EPOCH,
+        proposer_index: 7,
+        parent_root: [0x0a; 32],
+        state_root: branch_root(
+            finalized_header.hash_tree_root(),
+            &finality_branch,
+            finality_gindex,
+        ),
+        body_root: [0x0b; 32],
+    };
+    let signature_slot = attested_header.slot + 1;
+
+    let version = config
+        .forks
+        .iter()
+        .rev()
+        .find(|fork| fork.epoch <= (signature_slot - 1) / SLOTS_PER_EPOCH)
+        .unwrap()
+        .version;
+    let mut fork_version = [0u8; 32];
+    fork_version[..4].copy_from_slice(&version);
+    let fork_data_root = sha256_pair(&fork_version, &config.genesis_validators_root);
+    let mut domain = [0x07, 0, 0, 0].to_vec();
+    domain.extend_from_slice(&fork_data_root[..28]);
+    let signing_root = sha256_pair(&attested_header.hash_tree_root(), &domain);
+    let message = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v09::Sha256>>>::hash_to_curve(
+        signing_root,
+        BLS_DST,
+    );
+
+    LightClientUpdate {
+        attested_header,
+        next_sync_committee: None,
+        next_sync_committee_branch: Vec::new(),
+        finalized_header,
+        finality_branch,
+        finalized_execution: Some(payload_header(fixture)),
+        execution_branch: common::branch(&fixture["execution_branch"]),
+        sync_committee_bits: vec![0xff; SYNC_COMMITTEE_SIZE / 8],
+        sync_committee_signature: G2Affine::from(message * committee_secret())
+            .to_compressed()
+            .to_vec(),
+        signature_slot,
+    }
+}
+
+/// A chain that ran `layout` from genesis, under mainnet's validators root.
+fn generated_config(layout: BeaconFork) -> LightClientConfig {
+    LightClientConfig {
+        genesis_validators_root: LightClientConfig::mainnet().genesis_validators_root,
+        forks: vec![ForkVersion {
+            epoch: 0,
+            version: [0x05, 0x00, 0x00, 0x00],
+            layout,
+        }],
+    }
+}
+
+fn store_and_update(fixture: &str, layout: BeaconFork) -> (LightClientStore, LightClientUpdate) {
+    let config = generated_config(layout);
+    let update = update(&config, &beacon_fixture(fixture), layout);
+    let bootstrap = LightClientBootstrap {
+        period: LightClientStore::period_at(update.signature_slot),
+        current_sync_committee: committee(),
+    };
+    (LightClientStore::new(config, bootstrap), update)
+}
+
+fn follows_update_to_recorded_block(fixture: &str, layout: BeaconFork) {
+    let (mut store, update) = store_and_update(fixture, layout);
+
+    store.apply_update(&update).unwrap();
+    assert_eq!(store.finalized_slot, update.finalized_header.slot);
+    assert_eq!(
+        store.finalized_block_hash,
+        Some(payload_header(&beacon_fixture(fixture)).block_hash)
+    );
+}
+
+#[test]
+fn follows_deneb_update_to_recorded_block() {
+    follows_update_to_recorded_block("deneb_devnet_215", BeaconFork::Deneb);
+}
+
+#[test]
+fn follows_electra_update_to_recorded_block() {
+    follows_update_to_recorded_block("electra_devnet_182", BeaconFork::Electra);
+}
+
+#[test]
+fn rejects_payload_header_not_in_finalized_body() {
+    let (mut store, mut update) = store_and_update("deneb_devnet_215", BeaconFork::Deneb);
+    update.finalized_execution.as_mut().unwrap().gas_used += 1;
+
+    let err = store.apply_update(&update).unwrap_err();
+    assert_eq!(
+        err,
+        LightClientError::Beacon(BeaconError::RootMismatch { gindex: 25 })
+    );
+}
+
+#[test]
+fn rejects_payload_header_without_its_fork_fields() {
+    let (mut store, mut update) = store_and_update("electra_devnet_182", BeaconFork::Electra);
+    update.finalized_execution.as_mut().unwrap().blob_gas_used = None;
+
+    let err = store.apply_update(&update).unwrap_err();
+    assert_eq!(
+        err,
+        LightClientError::Beacon(BeaconError::PayloadLayout {
+            fork: BeaconFork::Electra
+        })
+    );
+}
+
+#[test]
+fn rejects_header_other_than_the_finalized_block() {
+    let (store, update) = store_and_update("deneb_devnet_215", BeaconFork::Deneb);
+    let config_hash = store.config.hash();
+    // no execution header of the devnet blocks was recorded, any other header must be refused
+    let inputs = LightClientInputs {
+        config: store.config,
+        bootstrap: LightClientBootstrap {
+            period: store.period,
+            current_sync_committee: store.current_sync_committee,
+        },
+        updates: vec![update],
+        header: LeanHeader::decode(&header_rlp("cancun_mainnet_20000000.hex")).unwrap(),
+    };
+
+    let err = LightClientVerifier::verify(&config_hash, inputs).unwrap_err();
+    assert_eq!(err, LightClientError::BlockHashMismatch);
+}
+
+#[test]
+fn mainnet_schedules_fulu() {
+    let fulu = LightClientConfig::mainnet().forks.pop().unwrap();
+    assert_eq!(fulu.epoch, 411392);
+    assert_eq!(fulu.version, [0x06, 0x00, 0x00, 0x00]);
+    assert_eq!(fulu.layout, BeaconFork::Electra);
+}
+
+fn recorded_committee(committee: &Value) -> SyncCommittee {
+    SyncCommittee {
+        pubkeys: common::branch(&committee["pubkeys"]),
+        aggregate_pubkey: common::bytes(&committee["aggregate_pubkey"]),
+    }
+}
+
+fn recorded_update(name: &str) -> LightClientUpdate {
+    let update = light_client_fixture(name);
+    let next = &update["next_sync_committee_update"];
+    LightClientUpdate {
+        attested_header: block_header(&update["attested_header"]),
+        next_sync_committee: (!next.is_null())
+            .then(|| recorded_committee(&next["next_sync_committee"])),
+        next_sync_committee_branch: if next.is_null() {
+            Vec::new()
+        } else {
+            common::branch(&next["next_sync_committee_branch"])
+        },
+        finalized_header: block_header(&update["finalized_header"]),
+        finality_branch: common::branch(&update["finality_branch"]),
+        finalized_execution: None,
+        execution_branch: Vec::new(),
+        sync_committee_bits: common::bytes(&update["sync_aggregate"]["sync_committee_bits"]),
+        sync_committee_signature: common::bytes(
+            &update["sync_aggregate"]["sync_committee_signature"],
+        ),
+        signature_slot: update["signature_slot"].as_u64().unwrap(),
+    }
+}
+
+/// Every fork from altair to electra at genesis, under the devnet's validators root.
+fn recorded_config() -> LightClientConfig {
+    let checkpoint = light_client_fixture("initial-checkpoint");
+    let forks = [
+        BeaconFork::Altair,
+        BeaconFork::Bellatrix,
+        BeaconFork::Capella,
+        BeaconFork::Deneb,
+        BeaconFork::Electra,
+    ];
+    LightClientConfig {
+        genesis_validators_root: common::root(&checkpoint["validators_root"]),
+        forks: forks
+            .into_iter()
+            .zip(1..)
+            .map(|(layout, version)| ForkVersion {
+                epoch: 0,
+                version: [version, 0x00, 0x00, 0x00],
+                layout,
+            })
+            .collect(),
+    }
+}
+
+fn recorded_store(config: LightClientConfig) -> LightClientStore {
+    let checkpoint = light_client_fixture("initial-checkpoint");
+    LightClientStore::new(
+        config,
+        LightClientBootstrap {
+            period: LightClientStore::period_at(block_header(&checkpoint["header"]).slot),
+            current_sync_committee: recorded_committee(&checkpoint["current_sync_committee"]),
+        },
+    )
+}
+
+const RECORDED_UPDATES: [&str; 4] = [
+    "sync-committee-update",
+    "finalized-header-update",
+    "interim-finalized-header-update",
+    "next-sync-committee-update",
+];
+
+#[test]
+fn recorded_bootstrap_committee_sits_in_checkpoint_state() {
+    let checkpoint = light_client_fixture("initial-checkpoint");
+    let committee = recorded_committee(&checkpoint["current_sync_committee"]);
+
+    BeaconVerifier::verify_branch(
+        &block_header(&checkpoint["header"]).state_root,
+        &committee.hash_tree_root().unwrap(),
+        &common::branch(&checkpoint["current_sync_committee_branch"]),
+        BeaconFork::Electra.state_gindex(STATE_CURRENT_SYNC_COMMITTEE_FIELD),
+    )
+    .unwrap();
+}
+
+#[test]
+fn follows_recorded_updates_into_the_next_period() {
+    let mut store = recorded_store(recorded_config());
+    assert_eq!(store.period, 0);
+
+    for name in RECORDED_UPDATES {
+        store.apply_update(&recorded_update(name)).unwrap();
+    }
+    // signed by the period 1 committee learned at slot 129, finalizing the first slot of period 1
+    assert_eq!(store.period, 1);
+    assert_eq!(store.finalized_slot, 8192);
+    assert_eq!(store.finalized_block_hash, None);
+    assert!(store.next_sync_committee.is_none());
+
+    // signed by the current committee now, the update hands over the period 2 committee
+    let rotation = recorded_update("next-sync-committee-update");
+    store.apply_update(&rotation).unwrap();
+    assert_eq!(store.next_sync_committee, rotation.next_sync_committee);
+    assert_ne!(
+        store.next_sync_committee,
+        Some(store.current_sync_committee)
+    );
+}
+
+#[test]
+fn rejects_recorded_rotation_without_the_next_committee() {
+    let mut store = recorded_store(recorded_config());
+
+    let err = store
+        .apply_update(&recorded_update("next-sync-committee-update"))
+        .unwrap_err();
+    assert_eq!(err, LightClientError::UnknownCommittee { period: 1 });
+}
+
+#[test]
+fn recorded_signature_binds_the_validators_root_and_header() {
+    let mut config = recorded_config();
+    config.genesis_validators_root = LightClientConfig::mainnet().genesis_validators_root;
+    let err = recorded_store(config)
+        .apply_update(&recorded_update("sync-committee-update"))
+        .unwrap_err();
+    assert_eq!(err, LightClientError::SignatureMismatch);
+
+    let mut update = recorded_update("sync-committee-update");
+    update.attested_header.proposer_index += 1;
+    let err = recorded_store(recorded_config())
+        .apply_update(&update)
+        .unwrap_err();
+    assert_eq!(err, LightClientError::SignatureMismatch);
+}
+
+fn recorded_inputs() -> LightClientInputs {
+    let store = recorded_store(recorded_config());
+    LightClientInputs {
+        config: store.config,
+        bootstrap: LightClientBootstrap {
+            period: store.period,
+            current_sync_committee: store.current_sync_committee,
+        },
+        updates: RECORDED_UPDATES.map(recorded_update).to_vec(),
+        header: LeanHeader::decode(&header_rlp("cancun_mainnet_20000000.hex")).unwrap(),
+    }
+}
+
+#[test]
+fn checks_the_config_against_the_trusted_hash() {
+    let err = LightClientVerifier::verify(&LightClientConfig::mainnet().hash(), recorded_inputs())
+        .unwrap_err();
+    assert_eq!(err, LightClientError::ConfigMismatch);
+
+    // under the devnet's own config every update verifies, the last one proves no payload
+    let err =
+        LightClientVerifier::verify(&recorded_config().hash(), recorded_inputs()).unwrap_err();
+    assert_eq!(err, LightClientError::NotFinalized);
+}
+
+#[test]
+fn config_hash_covers_the_fork_schedule() {
+    let mainnet = LightClientConfig::mainnet();
+    let mut later_fulu = mainnet.clone();
+    later_fulu.forks.last_mut().unwrap().epoch += 1;
+    let mut relabeled = mainnet.clone();
+    relabeled.forks.last_mut().unwrap().layout = BeaconFork::Deneb;
+
+    assert_ne!(mainnet.hash(), later_fulu.hash());
+    assert_ne!(mainnet.hash(), relabeled.hash());
+    assert_ne!(mainnet.hash(), recorded_config().hash());
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{branch, bytes, proof_fixture, state_header};
+use obsidian_lib::multiproof::{MultiProof, MultiProofAccount, MultiProofInputs};
+use obsidian_lib::verifier::{MPTVerifier, VerifierError};
+use serde_json::Value;
+
+/// Every account and slot of the recorded OP Sepolia state, see `accounts.rs`.
+fn inputs() -> (Value, MultiProofInputs) {
+    let fixture = proof_fixture("op_sepolia_26207959");
+    let mut proofs = Vec::new();
+    let mut accounts = Vec::new();
+    for account in fixture["accounts"].as_array().unwrap() {
+        proofs.push(branch(&account["accountProof"]));
+        let mut slots = Vec::new();
+        for slot in account["storageProof"].as_array().unwrap() {
+            proofs.push(branch(&slot["proof"]));
+            slots.push(bytes(&slot["key"]));
+        }
+        accounts.push(MultiProofAccount {
+            address: bytes(&account["address"]),
+            slots,
+        });
+    }
+
+    let inputs = MultiProofInputs {
+        header: state_header(&fixture),
+        accounts,
+        proof: MultiProof::from_proofs(&proofs),
+    };
+    (fixture, inputs)
+}
+
+#[test]
+fn shares_upper_nodes() {
+    let (fixture, inputs) = inputs();
+    let path_nodes: usize = fixture["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|account| {
+            account["accountProof"].as_array().unwrap().len()
+                + account["storageProof"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|slot| slot["proof"].as_array().unwrap().len())
+                    .sum::<usize>()
+        })
+        .sum();
+    // the state root alone is shared by all four account paths
+    assert!(inputs.proof.nodes.len() + 3 <= path_nodes);
+
+    let output = MPTVerifier::verify_multiproof(inputs.clone()).unwrap();
+    assert_eq!(output.block_hash, inputs.header.hash());
+    assert_eq!(output.accounts.len(), 4);
+    let l1_block = &fixture["accounts"][0]["storageProof"];
+    assert_eq!(output.accounts[0].slots.len(), 7);
+    assert_eq!(
+        output.accounts[0].slots[&bytes(&l1_block[2]["key"])],
+        bytes(&l1_block[2]["value"])
+    );
+}
+
+#[test]
+fn rejects_leftover_node() {
+    let (fixture, mut inputs) = inputs();
+    // the absent address's path ends in nodes no proven key walks through
+    let unused = branch(&fixture["absent"]["accountProof"]).pop().unwrap();
+    inputs.proof.nodes.push(unused);
+
+    let result = MPTVerifier::verify_multiproof(inputs);
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::LeftoverNodes { count: 1 }
+    );
+}
+
+#[test]
+fn rejects_duplicate_node() {
+    let (_, mut inputs) = inputs();
+    let root = inputs.proof.nodes[0].clone();
+    inputs.proof.nodes.push(root);
+
+    let result = MPTVerifier::verify_multiproof(inputs);
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::LeftoverNodes { count: 1 }
+    );
+}
+
+#[test]
+fn rejects_missing_node() {
+    let (fixture, mut inputs) = inputs();
+    // the leaf of the last account is on no other path
+    let leaf = branch(&fixture["accounts"][3]["accountProof"])
+        .pop()
+        .unwrap();
+    inputs.proof.nodes.retain(|node| *node != leaf);
+
+    let result = MPTVerifier::verify_multiproof(inputs);
+    assert!(matches!(result, Err(VerifierError::TruncatedProof { .. })));
+}
+
+#[test]
+fn rejects_absent_account() {
+    let (fixture, mut inputs) = inputs();
+    let absent = &fixture["absent"];
+    inputs.proof =
+        MultiProof::from_proofs(&[inputs.proof.nodes.clone(), branch(&absent["accountProof"])]);
+    inputs.accounts.push(MultiProofAccount {
+        address: bytes(&absent["address"]),
+        slots: vec![],
+    });
+
+    let result = MPTVerifier::verify_multiproof(inputs);
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::PathDivergence { depth: 6 }
+    );
+}
+
+#[test]
+fn rejects_slot_wider_than_a_word() {
+    let (_, mut inputs) = inputs();
+    inputs.accounts[0].slots[0].insert(0, 0);
+
+    let result = MPTVerifier::verify_multiproof(inputs);
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::InvalidSlot { length: 33 }
+    );
+}
@@ -0,0 +1,281 @@
+//! OP-stack output roots and L1 block hashes against a recorded OP Sepolia header.
+//!
+//! `op_sepolia_26207959.json` is the recorded OP Sepolia state of `accounts.rs`. Its trie nodes
+//! do not reach the `L2ToL1MessagePasser`, and no output root posted for a block at hand was
+//! found, so the output root tests rebuild the state around the message passer with alloy-trie
+//! and swap the header's state root for the rebuilt one. Output root hashing is checked against
+//! kona-protocol's vector.
+//!
+//! The recorded `L1Block` slots are read as proven. The L1 header they point at, Sepolia 8085312,
+//! was not recorded, so the full L1 state path runs over a rebuilt `L1Block` storing the hash of
+//! the recorded Sepolia 8341157 header.
+
+mod common;
+
+use alloy_primitives::{hex, keccak256, U256};
+use alloy_sol_types::SolValue;
+use alloy_trie::{TrieAccount, KECCAK_EMPTY};
+use common::{branch, header_rlp, proof_fixture, secure_trie, state_header};
+use obsidian_lib::header::LeanHeader;
+use obsidian_lib::op_stack::{
+    L1StateInputs, OpStackError, OpStackVerifier, OutputRootInputs, L1_BLOCK_ADDRESS,
+    L2_TO_L1_MESSAGE_PASSER_ADDRESS,
+};
+use obsidian_lib::verifier::{MultiAccountInputs, VerifierError};
+use obsidian_lib::{pack_l1_state, pack_output_root, L1StateProof, OutputRootProof, ProgramMode};
+use serde_json::Value;
+
+/// Storage root the rebuilt message passer carries.
+const MESSAGE_PASSER_STORAGE: [u8; 32] =
+    hex!("8ed4baae3a927be3dea54996b4d5899f8c01e7594bf50b17dc1e741388ce3d12");
+
+fn recorded_header() -> LeanHeader {
+    state_header(&proof_fixture("op_sepolia_26207959"))
+}
+
+/// The recorded header over a state holding the message passer, with its account proof.
+fn output_inputs() -> OutputRootInputs {
+    let fixture = proof_fixture("op_sepolia_26207959");
+    let message_passer = alloy_rlp::encode(TrieAccount {
+        nonce: 0,
+        balance: U256::ZERO,
+        storage_root: MESSAGE_PASSER_STORAGE.into(),
+        code_hash: KECCAK_EMPTY,
+    });
+    // the recorded L1Block account keeps the message passer from being the only leaf
+    let l1_block = &fixture["accounts"][0];
+    let l1_block_account = alloy_rlp::encode(TrieAccount {
+        nonce: 0,
+        balance: U256::ZERO,
+        storage_root: common::root(&l1_block["storageHash"]).into(),
+        code_hash: common::root(&l1_block["codeHash"]).into(),
+    });
+    let (state_root, proof) = secure_trie(
+        &[
+            (L2_TO_L1_MESSAGE_PASSER_ADDRESS.to_vec(), message_passer),
+            (common::bytes(&l1_block["address"]), l1_block_account),
+        ],
+        &L2_TO_L1_MESSAGE_PASSER_ADDRESS,
+    );
+
+    let mut header = recorded_header();
+    header.state_root = state_root.try_into().unwrap();
+    OutputRootInputs {
+        header,
+        message_passer_proof: proof,
+    }
+}
+
+#[test]
+fn matches_kona_output_root_vector() {
+    let word = |tail: [u8; 2]| {
+        let mut word = [0u8; 32];
+        word[30..].copy_from_slice(&tail);
+        word
+    };
+
+    let root = OpStackVerifier::output_root(
+        &word([0xbe, 0xef]),
+        &word([0xba, 0xbe]),
+        &word([0xc0, 0xde]),
+    );
+    assert_eq!(
+        root,
+        hex!("0c39fb6b07cf6694b13e63e59f7b15255be1c93a4d6d3e0da6c99729647c0d11")
+    );
+}
+
+#[test]
+fn recomputes_output_root_of_the_recorded_header() {
+    let inputs = output_inputs();
+    let header = inputs.header.clone();
+    let expected = keccak256(
+        [
+            [0u8; 32].as_slice(),
+            header.state_root.as_slice(),
+            MESSAGE_PASSER_STORAGE.as_slice(),
+            header.hash().as_slice(),
+        ]
+        .concat(),
+    );
+
+    let out = OpStackVerifier::verify_output_root(inputs, expected.as_slice()).unwrap();
+    assert_eq!(out.block_number, 26207959);
+    assert_eq!(out.block_hash, header.hash().to_vec());
+    assert_eq!(out.message_passer_storage_root, MESSAGE_PASSER_STORAGE);
+}
+
+#[test]
+fn rejects_output_root_of_another_block() {
+    let inputs = output_inputs();
+    let mut other = inputs.header.clone();
+    other.number += 1;
+    let posted = OpStackVerifier::output_root(
+        &other.state_root,
+        &MESSAGE_PASSER_STORAGE,
+        other.hash().as_slice(),
+    );
+
+    assert_eq!(
+        OpStackVerifier::verify_output_root(inputs, &posted).unwrap_err(),
+        OpStackError::OutputRootMismatch
+    );
+}
+
+#[test]
+fn rejects_message_passer_missing_from_the_state() {
+    // the recorded proof reaches the L1Block account, not the message passer
+    let fixture = proof_fixture("op_sepolia_26207959");
+    let inputs = OutputRootInputs {
+        header: recorded_header(),
+        message_passer_proof: branch(&fixture["accounts"][0]["accountProof"]),
+    };
+
+    let err = OpStackVerifier::verify(inputs).unwrap_err();
+    assert!(
+        matches!(err, OpStackError::Verifier(_)),
+        "unexpected {:?}",
+        err
+    );
+}
+
+#[test]
+fn commits_output_root_under_its_mode() {
+    let out = OpStackVerifier::verify(output_inputs()).unwrap();
+    let output_root = out.output_root.clone();
+
+    let (mode, proof) =
+        <(U256, OutputRootProof)>::abi_decode_params(&pack_output_root(out), true).unwrap();
+    assert_eq!(mode, U256::from(ProgramMode::OutputRoot as u8));
+    assert_eq!(proof.output_root.as_slice(), output_root);
+    assert_eq!(proof.block_number, U256::from(26207959));
+}
+
+#[test]
+fn rejects_rebuilt_proof_under_the_recorded_state_root() {
+    let mut inputs = output_inputs();
+    inputs.header = recorded_header();
+
+    assert_eq!(
+        OpStackVerifier::verify(inputs).unwrap_err(),
+        OpStackError::Verifier(VerifierError::HashMismatch { depth: 0 })
+    );
+}
+
+/// The `L1Block` hash slot at OP Sepolia 26207959, pointing at Sepolia 8085312.
+const RECORDED_L1_HASH: [u8; 32] =
+    hex!("e19e7253ba9308da9fcfdcc044397159b91b8b935d94da87af166a724ecf5ca7");
+
+/// Proof of one recorded `L1Block` slot.
+fn l1_block_slot_proof(l1_block: &Value, slot: u8) -> Vec<Vec<u8>> {
+    let slot = l1_block["storageProof"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|proof| common::bytes(&proof["key"])[31] == slot)
+        .unwrap();
+    branch(&slot["proof"])
+}
+
+fn sepolia_header() -> LeanHeader {
+    LeanHeader::decode(&header_rlp("prague_sepolia_8341157.hex")).unwrap()
+}
+
+#[test]
+fn reads_recorded_l1_block_hash() {
+    let fixture = proof_fixture("op_sepolia_26207959");
+    let l1_block = &fixture["accounts"][0];
+    assert_eq!(common::bytes(&l1_block["address"]), L1_BLOCK_ADDRESS);
+
+    let hash = OpStackVerifier::verify_l1_block_hash(
+        &recorded_header(),
+        &branch(&l1_block["accountProof"]),
+        &l1_block_slot_proof(l1_block, 2),
+    )
+    .unwrap();
+    assert_eq!(hash, RECORDED_L1_HASH);
+}
+
+#[test]
+fn rejects_proof_of_another_l1_block_slot() {
+    let fixture = proof_fixture("op_sepolia_26207959");
+    let l1_block = &fixture["accounts"][0];
+
+    // slot 0 packs the L1 number and timestamp, its proof does not reach slot 2
+    let err = OpStackVerifier::verify_l1_block_hash(
+        &recorded_header(),
+        &branch(&l1_block["accountProof"]),
+        &l1_block_slot_proof(l1_block, 0),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, OpStackError::Verifier(_)),
+        "unexpected {:?}",
+        err
+    );
+}
+
+#[test]
+fn rejects_l1_header_not_stored_in_l1_block() {
+    let fixture = proof_fixture("op_sepolia_26207959");
+    let l1_block = &fixture["accounts"][0];
+    let inputs = L1StateInputs {
+        l2_header: recorded_header(),
+        l1_block_account_proof: branch(&l1_block["accountProof"]),
+        l1_block_hash_proof: l1_block_slot_proof(l1_block, 2),
+        l1: MultiAccountInputs {
+            header: sepolia_header(),
+            accounts: Vec::new(),
+        },
+    };
+
+    assert_eq!(
+        OpStackVerifier::verify_l1_state(inputs).unwrap_err(),
+        OpStackError::L1HashMismatch
+    );
+}
+
+#[test]
+fn proves_l1_state_through_l1_block() {
+    let l1_header = sepolia_header();
+    let l1_hash = l1_header.hash();
+
+    let mut slot = [0u8; 32];
+    slot[31] = 2;
+    let (storage_root, hash_proof) = secure_trie(
+        &[
+            (slot.to_vec(), alloy_rlp::encode(l1_hash.as_slice())),
+            (vec![0; 32], alloy_rlp::encode(8341157u64)),
+        ],
+        &slot,
+    );
+    let l1_block = alloy_rlp::encode(TrieAccount {
+        nonce: 0,
+        balance: U256::ZERO,
+        storage_root: storage_root.as_slice().try_into().unwrap(),
+        code_hash: KECCAK_EMPTY,
+    });
+    let (state_root, account_proof) =
+        secure_trie(&[(L1_BLOCK_ADDRESS.to_vec(), l1_block)], &L1_BLOCK_ADDRESS);
+    let mut l2_header = recorded_header();
+    l2_header.state_root = state_root.try_into().unwrap();
+
+    let out = OpStackVerifier::verify_l1_state(L1StateInputs {
+        l2_header: l2_header.clone(),
+        l1_block_account_proof: account_proof,
+        l1_block_hash_proof: hash_proof,
+        l1: MultiAccountInputs {
+            header: l1_header,
+            accounts: Vec::new(),
+        },
+    })
+    .unwrap();
+    assert_eq!(out.l1_block_hash, l1_hash.to_vec());
+    assert_eq!(out.l2_block_hash, l2_header.hash().to_vec());
+
+    let (mode, proof) =
+        <(U256, L1StateProof)>::abi_decode_params(&pack_l1_state(out), true).unwrap();
+    assert_eq!(mode, U256::from(ProgramMode::L1State as u8));
+    assert_eq!(proof.l1_block_number, U256::from(8341157));
+    assert_eq!(proof.l2_block_number, U256::from(26207959));
+}
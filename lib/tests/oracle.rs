@@ -0,0 +1,266 @@
+//! Header fields and aggregates against recorded headers.
+//!
+//! `mainnet_1000001_1000010.json` holds ten consecutive mainnet headers with their hashes, as
+//! ethportal-api records them. Field words and aggregates are checked against the headers as
+//! alloy-consensus decodes them.
+
+mod common;
+
+use alloy_consensus::Header;
+use alloy_primitives::U256;
+use alloy_rlp::{Decodable, Encodable};
+use common::{header_rlp, FIXTURES};
+use obsidian_lib::header::{HeaderError, LeanHeader};
+use obsidian_lib::oracle::{
+    AggregateInputs, Aggregation, FieldsInputs, HeaderField, OracleError, OracleVerifier,
+};
+use serde_json::Value;
+
+const FIELDS: [HeaderField; 11] = [
+    HeaderField::Number,
+    HeaderField::Timestamp,
+    HeaderField::BaseFee,
+    HeaderField::GasLimit,
+    HeaderField::GasUsed,
+    HeaderField::PrevRandao,
+    HeaderField::Beneficiary,
+    HeaderField::StateRoot,
+    HeaderField::BlobGasUsed,
+    HeaderField::ExcessBlobGas,
+    HeaderField::ParentBeaconBlockRoot,
+];
+
+fn uint_word(value: u64) -> [u8; 32] {
+    U256::from(value).to_be_bytes()
+}
+
+/// The recorded run, oldest first, with the hashes ethportal-api records.
+fn recorded_run() -> (Vec<LeanHeader>, Vec<Vec<u8>>) {
+    let raw = std::fs::read_to_string(format!("{}/headers/mainnet_1000001_1000010.json", FIXTURES))
+        .unwrap();
+    let fixture: Value = serde_json::from_str(&raw).unwrap();
+    fixture["headers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                LeanHeader::decode(&common::bytes(&entry["header"])).unwrap(),
+                common::bytes(&entry["hash"]),
+            )
+        })
+        .unzip()
+}
+
+fn aggregate(
+    headers: Vec<LeanHeader>,
+    field: HeaderField,
+    aggregation: Aggregation,
+) -> Result<U256, OracleError> {
+    OracleVerifier::verify_aggregate(AggregateInputs {
+        headers,
+        field,
+        aggregation,
+    })
+    .map(|out| out.value)
+}
+
+#[test]
+fn reads_every_field_of_the_recorded_header() {
+    let raw = header_rlp("cancun_mainnet_20000000.hex");
+    let header = LeanHeader::decode(&raw).unwrap();
+    let expected = Header::decode(&mut raw.as_slice()).unwrap();
+
+    for field in FIELDS {
+        let word = match field {
+            HeaderField::Number => uint_word(expected.number),
+            HeaderField::Timestamp => uint_word(expected.timestamp),
+            HeaderField::BaseFee => uint_word(expected.base_fee_per_gas.unwrap()),
+            HeaderField::GasLimit => uint_word(expected.gas_limit),
+            HeaderField::GasUsed => uint_word(expected.gas_used),
+            HeaderField::PrevRandao => expected.mix_hash.0,
+            HeaderField::Beneficiary => expected.beneficiary.into_word().0,
+            HeaderField::StateRoot => expected.state_root.0,
+            HeaderField::BlobGasUsed => uint_word(expected.blob_gas_used.unwrap()),
+            HeaderField::ExcessBlobGas => uint_word(expected.excess_blob_gas.unwrap()),
+            HeaderField::ParentBeaconBlockRoot => expected.parent_beacon_block_root.unwrap().0,
+        };
+        assert_eq!(
+            OracleVerifier::field_word(&header, field),
+            Ok(word),
+            "{}",
+            field
+        );
+    }
+
+    let out = OracleVerifier::verify_fields(FieldsInputs {
+        header: header.clone(),
+        fields: FIELDS.to_vec(),
+    })
+    .unwrap();
+    assert_eq!(out.block_hash, expected.hash_slow().to_vec());
+    assert_eq!(out.block_number, 20_000_000);
+    assert_eq!(out.values.len(), FIELDS.len());
+}
+
+#[test]
+fn rejects_fields_a_frontier_header_lacks() {
+    let header = LeanHeader::decode(&header_rlp("frontier_mainnet_1000001.hex")).unwrap();
+
+    for field in [
+        HeaderField::BaseFee,
+        HeaderField::BlobGasUsed,
+        HeaderField::ExcessBlobGas,
+        HeaderField::ParentBeaconBlockRoot,
+    ] {
+        assert_eq!(
+            OracleVerifier::field_word(&header, field),
+            Err(OracleError::MissingField {
+                field,
+                number: 1_000_001
+            })
+        );
+    }
+    // the fields every header has still read
+    OracleVerifier::field_word(&header, HeaderField::GasUsed).unwrap();
+
+    let err = OracleVerifier::verify_fields(FieldsInputs {
+        header,
+        fields: vec![HeaderField::Number, HeaderField::BaseFee],
+    })
+    .unwrap_err();
+    assert_eq!(
+        err,
+        OracleError::MissingField {
+            field: HeaderField::BaseFee,
+            number: 1_000_001
+        }
+    );
+}
+
+#[test]
+fn recorded_run_hashes_to_the_recorded_hashes() {
+    let (headers, hashes) = recorded_run();
+    for (header, hash) in headers.iter().zip(&hashes) {
+        assert_eq!(header.hash().to_vec(), *hash);
+    }
+
+    let out = OracleVerifier::verify_aggregate(AggregateInputs {
+        headers,
+        field: HeaderField::GasUsed,
+        aggregation: Aggregation::Sum,
+    })
+    .unwrap();
+    assert_eq!(out.first_hash, hashes[0]);
+    assert_eq!(out.first_number, 1_000_001);
+    assert_eq!(out.last_hash, hashes[9]);
+    assert_eq!(out.last_number, 1_000_010);
+}
+
+#[test]
+fn aggregates_over_the_recorded_run() {
+    let (headers, _) = recorded_run();
+
+    for field in [
+        HeaderField::Number,
+        HeaderField::Timestamp,
+        HeaderField::GasLimit,
+        HeaderField::GasUsed,
+    ] {
+        let values: Vec<u64> = headers
+            .iter()
+            .map(|header| {
+                let mut raw = Vec::new();
+                header.encode(&mut raw);
+                let header = Header::decode(&mut raw.as_slice()).unwrap();
+                match field {
+                    HeaderField::Number => header.number,
+                    HeaderField::Timestamp => header.timestamp,
+                    HeaderField::GasLimit => header.gas_limit,
+                    _ => header.gas_used,
+                }
+            })
+            .collect();
+        let sum: u64 = values.iter().sum();
+
+        for (aggregation, expected) in [
+            (Aggregation::Sum, sum),
+            (Aggregation::Average, sum / 10),
+            (Aggregation::Min, *values.iter().min().unwrap()),
+            (Aggregation::Max, *values.iter().max().unwrap()),
+        ] {
+            assert_eq!(
+                aggregate(headers.clone(), field, aggregation),
+                Ok(U256::from(expected)),
+                "{:?} of {}",
+                aggregation,
+                field
+            );
+        }
+    }
+
+    // the numbers run 1000001 to 1000010
+    assert_eq!(
+        aggregate(headers.clone(), HeaderField::Number, Aggregation::Sum),
+        Ok(U256::from(10_000_055))
+    );
+    assert_eq!(
+        aggregate(headers, HeaderField::Number, Aggregation::Average),
+        Ok(U256::from(1_000_005))
+    );
+}
+
+#[test]
+fn rejects_aggregating_hashes_and_addresses() {
+    let (headers, _) = recorded_run();
+
+    for field in [
+        HeaderField::PrevRandao,
+        HeaderField::Beneficiary,
+        HeaderField::StateRoot,
+        HeaderField::ParentBeaconBlockRoot,
+    ] {
+        assert_eq!(
+            aggregate(headers.clone(), field, Aggregation::Max),
+            Err(OracleError::NotNumeric { field })
+        );
+    }
+}
+
+#[test]
+fn rejects_aggregating_a_field_the_run_lacks() {
+    let (headers, _) = recorded_run();
+    assert_eq!(
+        aggregate(headers, HeaderField::BaseFee, Aggregation::Average),
+        Err(OracleError::MissingField {
+            field: HeaderField::BaseFee,
+            number: 1_000_001
+        })
+    );
+}
+
+#[test]
+fn rejects_a_run_with_a_gap() {
+    let (mut headers, _) = recorded_run();
+    headers.remove(4);
+
+    assert_eq!(
+        aggregate(headers, HeaderField::GasUsed, Aggregation::Sum),
+        Err(OracleError::Header(HeaderError::NonConsecutive {
+            number: 1_000_006
+        }))
+    );
+}
+
+#[test]
+fn rejects_a_run_with_a_tampered_header() {
+    let (mut headers, _) = recorded_run();
+    headers[3].gas_used += 1;
+
+    assert_eq!(
+        aggregate(headers, HeaderField::GasUsed, Aggregation::Sum),
+        Err(OracleError::Header(HeaderError::BrokenLink {
+            number: 1_000_005
+        }))
+    );
+}
@@ -0,0 +1,175 @@
+//! Receipt proofs against recorded receipts.
+//!
+//! `mainnet_14764013.json` holds every receipt of mainnet block 14764013 as ethportal-api records
+//! them, together with the block's receipts root. The block has legacy and EIP-1559 receipts only,
+//! the first one carries the Swap event of a Uniswap V2 pair.
+//!
+//! `op_deposits.json` holds OP deposit receipts: the regolith one is OP mainnet transaction
+//! 0xb7c74afd..., re-encoded from its `eth_getTransactionReceipt` response as op-alloy records
+//! it, the canyon one is op-alloy's post-canyon receipt vector. Neither comes with the rest of its
+//! block, so their tries are built by alloy-trie.
+
+mod common;
+
+use alloy_primitives::hex;
+use common::{list_trie, receipt_fixture};
+use obsidian_lib::receipts::{ReceiptError, ReceiptVerifier, TxType};
+use obsidian_lib::verifier::VerifierError;
+use serde_json::Value;
+
+/// The pair the first receipt's swap went through.
+const PAIR: [u8; 20] = hex!("74c99f3f5331676f6aec2756e1f39b4fc029a83e");
+const USDT: [u8; 20] = hex!("dac17f958d2ee523a2206206994597c13d831ec7");
+/// `Swap(address,uint256,uint256,uint256,uint256,address)`
+const SWAP: [u8; 32] = hex!("d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822");
+/// `Transfer(address,address,uint256)`
+const TRANSFER: [u8; 32] = hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+fn block_receipts() -> (Vec<u8>, Vec<Vec<u8>>) {
+    let fixture = receipt_fixture("mainnet_14764013");
+    (
+        common::bytes(&fixture["receiptsRoot"]),
+        common::branch(&fixture["receipts"]),
+    )
+}
+
+fn deposit(fixture: &Value, name: &str) -> Vec<u8> {
+    common::bytes(&fixture[name]["receipt"])
+}
+
+#[test]
+fn recorded_receipts_rebuild_the_receipts_root() {
+    let (receipts_root, receipts) = block_receipts();
+    let (root, _) = list_trie(&receipts, 0);
+    assert_eq!(root, receipts_root);
+}
+
+#[test]
+fn proves_every_recorded_receipt() {
+    let (receipts_root, receipts) = block_receipts();
+
+    let mut cumulative_gas_used = 0;
+    for index in 0..receipts.len() as u64 {
+        let (_, proof) = list_trie(&receipts, index);
+        let receipt = ReceiptVerifier::verify_receipt(&receipts_root, index, &proof).unwrap();
+
+        let expected = match index {
+            6..=8 => TxType::Legacy,
+            _ => TxType::DynamicFee,
+        };
+        assert_eq!(receipt.tx_type, expected);
+        // transactions 4 and 5 reverted
+        assert_eq!(receipt.status, !matches!(index, 4 | 5));
+        assert!(receipt.cumulative_gas_used > cumulative_gas_used);
+        cumulative_gas_used = receipt.cumulative_gas_used;
+    }
+    // the last receipt's cumulative gas is the block's gas used
+    assert_eq!(cumulative_gas_used, 1314225);
+}
+
+#[test]
+fn proves_legacy_receipt() {
+    let (receipts_root, receipts) = block_receipts();
+    let (_, proof) = list_trie(&receipts, 6);
+
+    let receipt = ReceiptVerifier::verify_receipt(&receipts_root, 6, &proof).unwrap();
+    assert_eq!(receipt.tx_type, TxType::Legacy);
+    assert_eq!(receipt.cumulative_gas_used, 579367);
+    assert!(receipt.logs.is_empty());
+}
+
+#[test]
+fn finds_the_recorded_swap() {
+    let (receipts_root, receipts) = block_receipts();
+    let (_, proof) = list_trie(&receipts, 0);
+    let receipt = ReceiptVerifier::verify_receipt(&receipts_root, 0, &proof).unwrap();
+    assert_eq!(receipt.logs.len(), 7);
+
+    let swaps: Vec<_> = receipt.logs_matching(&PAIR, &SWAP).collect();
+    assert_eq!(swaps.len(), 1);
+    assert_eq!(swaps[0].topics.len(), 3);
+    // four amounts, in and out of each token
+    assert_eq!(swaps[0].data.len(), 128);
+
+    assert_eq!(receipt.logs_matching(&USDT, &TRANSFER).count(), 2);
+    // the pair emitted no transfer and the token no swap
+    assert_eq!(receipt.logs_matching(&PAIR, &TRANSFER).count(), 0);
+    assert_eq!(receipt.logs_matching(&USDT, &SWAP).count(), 0);
+}
+
+#[test]
+fn rejects_receipt_proof_at_another_index() {
+    let (receipts_root, receipts) = block_receipts();
+    let (_, proof) = list_trie(&receipts, 0);
+
+    let result = ReceiptVerifier::verify_receipt(&receipts_root, 1, &proof);
+    assert!(matches!(result, Err(ReceiptError::Verifier(_))));
+}
+
+#[test]
+fn decodes_every_typed_envelope() {
+    // no recorded 2930, 4844 or 7702 receipt is at hand, their payload is the same list as a
+    // 1559 receipt's, so the recorded swap receipt is re-enveloped under each type byte
+    let (_, receipts) = block_receipts();
+    let payload = &receipts[0][1..];
+
+    for (byte, tx_type) in [
+        (0x01, TxType::AccessList),
+        (0x02, TxType::DynamicFee),
+        (0x03, TxType::Blob),
+        (0x04, TxType::SetCode),
+    ] {
+        let mut block = receipts.clone();
+        block[0] = [&[byte], payload].concat();
+        let (root, proof) = list_trie(&block, 0);
+
+        let receipt = ReceiptVerifier::verify_receipt(&root, 0, &proof).unwrap();
+        assert_eq!(receipt.tx_type, tx_type);
+        assert_eq!(receipt.logs_matching(&PAIR, &SWAP).count(), 1);
+    }
+}
+
+#[test]
+fn proves_deposit_receipts() {
+    let fixture = receipt_fixture("op_deposits");
+
+    // regolith appends the deposit nonce, canyon the receipt version as well
+    for (name, cumulative_gas_used) in [("regolith", 0xfa0d), ("canyon", 46913)] {
+        // the deposit is always the first transaction of an op stack block
+        let block = vec![deposit(&fixture, name), block_receipts().1[6].clone()];
+        let (root, proof) = list_trie(&block, 0);
+
+        let receipt = ReceiptVerifier::verify_receipt(&root, 0, &proof).unwrap();
+        assert_eq!(receipt.tx_type, TxType::Deposit);
+        assert!(receipt.status);
+        assert_eq!(receipt.cumulative_gas_used, cumulative_gas_used);
+        assert!(receipt.logs.is_empty());
+    }
+}
+
+#[test]
+fn rejects_deposit_fields_on_other_types() {
+    let fixture = receipt_fixture("op_deposits");
+    let canyon = deposit(&fixture, "canyon");
+
+    let dynamic_fee = [&[0x02], &canyon[1..]].concat();
+    assert_eq!(
+        ReceiptVerifier::decode_receipt(&dynamic_fee).unwrap_err(),
+        ReceiptError::InvalidReceipt { items: 6 }
+    );
+}
+
+#[test]
+fn rejects_tampered_receipt() {
+    let (receipts_root, mut receipts) = block_receipts();
+    // one bit inside the logs
+    let inside = receipts[0].len() - 200;
+    receipts[0][inside] ^= 1;
+    let (_, proof) = list_trie(&receipts, 0);
+
+    let result = ReceiptVerifier::verify_receipt(&receipts_root, 0, &proof);
+    assert_eq!(
+        result.unwrap_err(),
+        ReceiptError::Verifier(VerifierError::HashMismatch { depth: 0 })
+    );
+}
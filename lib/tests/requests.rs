@@ -0,0 +1,191 @@
+//! Execution requests against a recorded Prague header and recorded deposits.
+//!
+//! `prague_sepolia_8341157.hex` is Sepolia block 8341157, re-encoded from the block the
+//! superchain registry records and checked against its hash. The block carries no requests, so
+//! its requests hash is the commitment to an empty list. No header of a block with requests was
+//! at hand: `mainnet_deposits.json` holds the request data of two mainnet deposits, built from
+//! their deposit contract logs as alloy-evm records them, and is checked by decoding alone.
+
+mod common;
+
+use alloy_primitives::hex;
+use common::{header_rlp, request_fixture};
+use obsidian_lib::header::LeanHeader;
+use obsidian_lib::requests::{
+    RequestsError, RequestsInputs, RequestsVerifier, CONSOLIDATION_REQUEST_TYPE,
+    DEPOSIT_REQUEST_TYPE, WITHDRAWAL_REQUEST_TYPE,
+};
+
+fn prague() -> LeanHeader {
+    LeanHeader::decode(&header_rlp("prague_sepolia_8341157.hex")).unwrap()
+}
+
+/// The deposit request of the recorded deposits, type byte first.
+fn deposits() -> Vec<u8> {
+    let data = common::bytes(&request_fixture("mainnet_deposits")["deposits"]);
+    [&[DEPOSIT_REQUEST_TYPE], data.as_slice()].concat()
+}
+
+#[test]
+fn recomputes_the_recorded_requests_hash() {
+    let header = prague();
+    // sha256 of nothing
+    assert_eq!(
+        header.requests_hash,
+        Some(hex!(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        ))
+    );
+
+    let output = RequestsVerifier::verify(RequestsInputs {
+        header,
+        requests: vec![],
+    })
+    .unwrap();
+    assert_eq!(output.block_number, 8341157);
+    assert_eq!(
+        output.block_hash,
+        hex!("0eea59ec81aaf5c8382efd0ae3885003965b9fb26680ec54afefdb51341abbb7")
+    );
+    assert!(output.requests.deposits.is_empty());
+    assert!(output.requests.withdrawals.is_empty());
+    assert!(output.requests.consolidations.is_empty());
+}
+
+#[test]
+fn rejects_requests_the_block_did_not_process() {
+    let result = RequestsVerifier::verify(RequestsInputs {
+        header: prague(),
+        requests: vec![deposits()],
+    });
+    assert_eq!(result.unwrap_err(), RequestsError::HashMismatch);
+}
+
+#[test]
+fn rejects_header_before_prague() {
+    let result = RequestsVerifier::verify(RequestsInputs {
+        header: LeanHeader::decode(&header_rlp("cancun_mainnet_20000000.hex")).unwrap(),
+        requests: vec![],
+    });
+    assert_eq!(result.unwrap_err(), RequestsError::MissingRequestsHash);
+}
+
+#[test]
+fn decodes_recorded_deposits() {
+    let requests = RequestsVerifier::decode(&[deposits()]).unwrap();
+    assert_eq!(requests.deposits.len(), 2);
+
+    let first = &requests.deposits[0];
+    assert_eq!(
+        first.pubkey,
+        hex!("998c8086669bf65e24581cda47d8537966e9f5066fc6ffdcba910a1bfb91eae7a4873fcce166a1c4ea217e6b1afd3962")
+    );
+    // 0x01 credentials withdraw to an execution address
+    assert_eq!(
+        first.withdrawal_credentials,
+        hex!("01000000000000000000000001c340fb72ed14d4eaa71f7633ee9e33b88d4f39")
+    );
+    // 32 ether in gwei
+    assert_eq!(first.amount, 32_000_000_000);
+    assert_eq!(first.signature.len(), 96);
+    assert_eq!(first.index, 1471716);
+
+    let second = &requests.deposits[1];
+    assert_eq!(second.amount, 32_000_000_000);
+    assert_eq!(second.index, 1471715);
+}
+
+/// One request of each type, the withdrawal and consolidation sent from the first deposit's
+/// withdrawal address for its validator.
+fn one_of_each() -> Vec<Vec<u8>> {
+    let deposit = deposits()[..193].to_vec();
+    let source = hex!("01c340fb72ed14d4eaa71f7633ee9e33b88d4f39");
+    let pubkey = &deposit[1..49];
+    let withdrawal = [
+        &[WITHDRAWAL_REQUEST_TYPE],
+        source.as_slice(),
+        pubkey,
+        &[0; 8],
+    ]
+    .concat();
+    let consolidation = [
+        &[CONSOLIDATION_REQUEST_TYPE],
+        source.as_slice(),
+        pubkey,
+        pubkey,
+    ]
+    .concat();
+    vec![deposit, withdrawal, consolidation]
+}
+
+#[test]
+fn decodes_one_request_of_each_type() {
+    let requests = RequestsVerifier::decode(&one_of_each()).unwrap();
+    assert_eq!(requests.deposits.len(), 1);
+    assert_eq!(requests.withdrawals.len(), 1);
+    // a zero amount is a full exit
+    assert_eq!(requests.withdrawals[0].amount, 0);
+    assert_eq!(requests.consolidations.len(), 1);
+    assert_eq!(
+        requests.consolidations[0].source_pubkey,
+        requests.deposits[0].pubkey
+    );
+}
+
+#[test]
+fn rejects_unordered_types() {
+    let mut requests = one_of_each();
+    requests.swap(0, 1);
+    assert_eq!(
+        RequestsVerifier::decode(&requests).unwrap_err(),
+        RequestsError::UnorderedTypes {
+            ty: DEPOSIT_REQUEST_TYPE
+        }
+    );
+
+    // each type once, deposits are concatenated into one request
+    let requests = vec![deposits(), deposits()];
+    assert_eq!(
+        RequestsVerifier::decode(&requests).unwrap_err(),
+        RequestsError::UnorderedTypes {
+            ty: DEPOSIT_REQUEST_TYPE
+        }
+    );
+}
+
+#[test]
+fn rejects_partial_request() {
+    let mut requests = deposits();
+    requests.pop();
+    assert_eq!(
+        RequestsVerifier::decode(&[requests]).unwrap_err(),
+        RequestsError::InvalidLength {
+            ty: DEPOSIT_REQUEST_TYPE,
+            length: 383
+        }
+    );
+
+    let mut requests = one_of_each();
+    requests[2].push(0);
+    assert_eq!(
+        RequestsVerifier::decode(&requests).unwrap_err(),
+        RequestsError::InvalidLength {
+            ty: CONSOLIDATION_REQUEST_TYPE,
+            length: 117
+        }
+    );
+}
+
+#[test]
+fn rejects_empty_request() {
+    // a type without requests is left out, not sent with empty data
+    let requests = vec![deposits(), vec![WITHDRAWAL_REQUEST_TYPE]];
+    assert_eq!(
+        RequestsVerifier::decode(&requests).unwrap_err(),
+        RequestsError::EmptyRequest
+    );
+    assert_eq!(
+        RequestsVerifier::decode(&[vec![]]).unwrap_err(),
+        RequestsError::EmptyRequest
+    );
+}
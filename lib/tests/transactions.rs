@@ -0,0 +1,175 @@
+//! Transaction decoding and proofs against recorded transactions.
+//!
+//! `mainnet_14764013.json` holds every transaction of mainnet block 14764013 as ethportal-api
+//! records them, with the block's transactions root and the hashes etherscan lists for two of them.
+//!
+//! `recorded.json` holds one or more transactions of every envelope type, each with the hash and
+//! sender its chain reports. Raw transactions are taken as published, `eth_getTransactionByHash`
+//! responses are re-encoded, and the recorded hash checks the re-encoding. The 7702 transaction
+//! and one of each legacy and blob transaction come from devnets, no mainnet one was at hand.
+
+mod common;
+
+use alloy_primitives::hex;
+use common::{list_trie, transaction_fixture};
+use obsidian_lib::receipts::TxType;
+use obsidian_lib::transactions::{TransactionError, TransactionVerifier, TxSender};
+use serde_json::Value;
+
+fn block_transactions() -> (Value, Vec<Vec<u8>>) {
+    let fixture = transaction_fixture("mainnet_14764013");
+    let transactions = common::branch(&fixture["transactions"]);
+    (fixture, transactions)
+}
+
+fn tx_type(name: &str) -> TxType {
+    match name.split('_').next().unwrap() {
+        "legacy" => TxType::Legacy,
+        "access" => TxType::AccessList,
+        "dynamic" => TxType::DynamicFee,
+        "blob" => TxType::Blob,
+        "set" => TxType::SetCode,
+        "deposit" => TxType::Deposit,
+        other => panic!("unknown fixture {}", other),
+    }
+}
+
+#[test]
+fn recorded_transactions_rebuild_the_transactions_root() {
+    let (fixture, transactions) = block_transactions();
+    let (root, _) = list_trie(&transactions, 0);
+    assert_eq!(root, common::bytes(&fixture["transactionsRoot"]));
+}
+
+#[test]
+fn proves_every_transaction_of_the_block() {
+    let (fixture, transactions) = block_transactions();
+    let root = common::bytes(&fixture["transactionsRoot"]);
+
+    for index in 0..transactions.len() as u64 {
+        let (_, proof) = list_trie(&transactions, index);
+        let tx = TransactionVerifier::verify_transaction(&root, index, &proof).unwrap();
+
+        let expected = match index {
+            6..=8 => TxType::Legacy,
+            _ => TxType::DynamicFee,
+        };
+        assert_eq!(tx.tx_type, expected);
+        // the legacy ones are eip-155 signed as well
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.sender().unwrap().len(), 20);
+    }
+
+    for (index, hash) in fixture["hashes"].as_object().unwrap() {
+        let index: u64 = index.parse().unwrap();
+        let (_, proof) = list_trie(&transactions, index);
+        let tx = TransactionVerifier::verify_transaction(&root, index, &proof).unwrap();
+        assert_eq!(tx.hash, common::bytes(hash));
+    }
+}
+
+#[test]
+fn matches_recorded_hash_and_sender() {
+    for recorded in transaction_fixture("recorded").as_array().unwrap() {
+        let name = recorded["name"].as_str().unwrap();
+        let tx = TransactionVerifier::decode_transaction(&common::bytes(&recorded["raw"]))
+            .unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+        assert_eq!(tx.tx_type, tx_type(name), "{}", name);
+        assert_eq!(tx.chain_id, recorded["chainId"].as_u64(), "{}", name);
+        if let Some(hash) = recorded.get("hash") {
+            assert_eq!(tx.hash, common::bytes(hash), "{}", name);
+        }
+        if let Some(from) = recorded.get("from") {
+            assert_eq!(tx.sender().unwrap(), common::bytes(from), "{}", name);
+        }
+    }
+}
+
+#[test]
+fn covers_every_envelope_type() {
+    let recorded = transaction_fixture("recorded");
+    for ty in [
+        TxType::Legacy,
+        TxType::AccessList,
+        TxType::DynamicFee,
+        TxType::Blob,
+        TxType::SetCode,
+        TxType::Deposit,
+    ] {
+        assert!(recorded
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tx| tx_type(tx["name"].as_str().unwrap()) == ty));
+    }
+}
+
+fn recorded(name: &str) -> Vec<u8> {
+    let fixture = transaction_fixture("recorded");
+    let tx = fixture
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|tx| tx["name"] == name)
+        .unwrap();
+    common::bytes(&tx["raw"])
+}
+
+/// Position of the legacy `v` byte, right before `r`.
+fn v_position(raw: &[u8]) -> usize {
+    let tx = TransactionVerifier::decode_transaction(raw).unwrap();
+    let TxSender::Signed { signature, .. } = tx.sender else {
+        panic!("unsigned legacy transaction");
+    };
+    let r: [u8; 32] = signature.r.to_be_bytes();
+    let r_item = [&[0xa0], r.as_slice()].concat();
+    raw.windows(33).position(|w| w == r_item).unwrap() - 1
+}
+
+#[test]
+fn eip155_signs_over_the_chain_id() {
+    let raw = recorded("legacy_eip155");
+    let from = TransactionVerifier::decode_transaction(&raw)
+        .unwrap()
+        .sender()
+        .unwrap();
+    assert_eq!(from, hex!("a12e1462d0ced572f396f58b6e2d03894cd7c8a4"));
+
+    // the same signature read as pre-155 hashes without the chain id and recovers someone else
+    let mut unprotected = raw.clone();
+    let v = v_position(&raw);
+    assert_eq!(unprotected[v], 37);
+    unprotected[v] = 27;
+
+    let tx = TransactionVerifier::decode_transaction(&unprotected).unwrap();
+    assert_eq!(tx.chain_id, None);
+    assert_ne!(tx.sender().unwrap(), from);
+}
+
+#[test]
+fn rejects_legacy_v_between_the_schemes() {
+    let mut raw = recorded("legacy_pre_eip155");
+    let v = v_position(&raw);
+    assert_eq!(raw[v], 28);
+    raw[v] = 29;
+
+    assert_eq!(
+        TransactionVerifier::decode_transaction(&raw).unwrap_err(),
+        TransactionError::InvalidSignature
+    );
+}
+
+#[test]
+fn rejects_tampered_transaction_in_the_block() {
+    let (fixture, mut transactions) = block_transactions();
+    let root = common::bytes(&fixture["transactionsRoot"]);
+    let last = transactions[6].len() - 1;
+    transactions[6][last] ^= 1;
+    let (_, proof) = list_trie(&transactions, 6);
+
+    assert!(matches!(
+        TransactionVerifier::verify_transaction(&root, 6, &proof),
+        Err(TransactionError::Verifier(_))
+    ));
+}
@@ -0,0 +1,260 @@
+use alloy_primitives::{keccak256, U256};
+use alloy_rlp::{Encodable, Header};
+use alloy_trie::proof::ProofRetainer;
+use alloy_trie::{HashBuilder, Nibbles};
+use obsidian_lib::decoder::RlpError;
+use obsidian_lib::verifier::{MPTVerifier, VerifierError};
+
+mod common;
+use common::{branch, bytes, proof_fixture, proof_state_root};
+
+const ADDRESS: [u8; 20] = [0x42; 20];
+
+fn list(items: &[&[u8]]) -> Vec<u8> {
+    let payload: Vec<u8> = items.concat();
+    let mut out = Vec::new();
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut out);
+    out.extend(payload);
+    out
+}
+
+fn string(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    bytes.encode(&mut out);
+    out
+}
+
+/// A state trie holding one account, its root is the account's leaf.
+fn single_account(balance: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let account = list(&[
+        &string(&[1]),
+        &string(balance),
+        &string(&[0x56; 32]),
+        &string(&[0xc5; 32]),
+    ]);
+    // even leaf flag followed by the full 64 nibble path
+    let mut path = vec![0x20];
+    path.extend_from_slice(keccak256(ADDRESS).as_slice());
+    let leaf = list(&[&string(&path), &string(&account)]);
+    (keccak256(&leaf).to_vec(), vec![leaf])
+}
+
+#[test]
+fn reads_balance_and_nonce() {
+    let (root, proof) = single_account(&[0x0d, 0xe0, 0xb6, 0xb3]);
+    let state = MPTVerifier::verify_and_get_account_state(
+        &root,
+        ADDRESS.to_vec(),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(state.balance, U256::from(0x0de0b6b3u64));
+    assert_eq!(state.nonce, U256::from(1));
+}
+
+#[test]
+fn rejects_balance_wider_than_a_word() {
+    let (root, proof) = single_account(&[0xff; 33]);
+    let result = MPTVerifier::verify_and_get_account_state(
+        &root,
+        ADDRESS.to_vec(),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::Decode(RlpError::IntegerOverflow { length: 33 })
+    );
+}
+
+#[test]
+fn rejects_balance_with_leading_zero() {
+    let (root, proof) = single_account(&[0x00, 0x01]);
+    let result = MPTVerifier::verify_and_get_account_state(
+        &root,
+        ADDRESS.to_vec(),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::Decode(RlpError::LeadingZeroInteger)
+    );
+}
+
+/// A list trie of one byte values, built by alloy-trie so the root is computed independently.
+/// Leaves this small are embedded in their parent branch instead of hashed.
+fn small_value_trie(entries: u64, target: u64) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let mut keys: Vec<(Nibbles, u64)> = (0..entries)
+        .map(|index| (Nibbles::unpack(MPTVerifier::index_key(index)), index))
+        .collect();
+    keys.sort();
+
+    let target = Nibbles::unpack(MPTVerifier::index_key(target));
+    let mut builder =
+        HashBuilder::default().with_proof_retainer(ProofRetainer::from_iter([target.clone()]));
+    for (key, index) in &keys {
+        builder.add_leaf(key.clone(), &[*index as u8 + 1]);
+    }
+    let root = builder.root();
+    let proof = builder
+        .take_proof_nodes()
+        .matching_nodes_sorted(&target)
+        .into_iter()
+        .map(|(_, node)| node.to_vec())
+        .collect();
+    (root.to_vec(), proof)
+}
+
+#[test]
+fn walks_into_inline_leaf() {
+    let (root, proof) = small_value_trie(20, 5);
+    // the leaf for index 5 sits inside the hashed branch for nibble 0
+    assert!(proof.iter().any(|node| node.len() < 32));
+
+    let value =
+        MPTVerifier::verify_indexed_value(&root, 5, MPTVerifier::decode_proof(&proof).unwrap())
+            .unwrap();
+    assert_eq!(value, &[6]);
+}
+
+#[test]
+fn accepts_inline_leaf_without_its_repeat() {
+    let (root, proof) = small_value_trie(20, 5);
+    // nodes embedded in their parent need not be repeated in the proof
+    let hashed: Vec<Vec<u8>> = proof.into_iter().filter(|node| node.len() >= 32).collect();
+
+    let value =
+        MPTVerifier::verify_indexed_value(&root, 5, MPTVerifier::decode_proof(&hashed).unwrap())
+            .unwrap();
+    assert_eq!(value, &[6]);
+}
+
+#[test]
+fn rejects_tampered_inline_leaf() {
+    let (root, proof) = small_value_trie(20, 5);
+    let mut hashed: Vec<Vec<u8>> = proof.into_iter().filter(|node| node.len() >= 32).collect();
+
+    // the embedded leaf [0x20, 0x06] is covered by its parent's hash
+    let branch = hashed.last_mut().unwrap();
+    let at = branch
+        .windows(3)
+        .position(|leaf| leaf == [0xc2, 0x20, 0x06])
+        .unwrap();
+    branch[at + 2] = 0x07;
+
+    let result =
+        MPTVerifier::verify_indexed_value(&root, 5, MPTVerifier::decode_proof(&hashed).unwrap());
+    assert!(matches!(
+        result,
+        Err(VerifierError::HashMismatch { depth: 1 })
+    ));
+}
+
+#[test]
+fn rejects_proof_under_other_root() {
+    let response = proof_fixture("getproof_7ae1d57b");
+    let proof = branch(&response["accountProof"]);
+    let other = proof_state_root(&proof_fixture("getproof_c36442b4"));
+
+    let result = MPTVerifier::verify_account_state(
+        &other,
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::HashMismatch { depth: 0 }
+    );
+}
+
+#[test]
+fn rejects_nodes_after_the_leaf() {
+    let response = proof_fixture("getproof_7ae1d57b");
+    let mut proof = branch(&response["accountProof"]);
+    proof.push(proof[proof.len() - 1].clone());
+
+    let result = MPTVerifier::verify_account_state(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::LeftoverNodes { count: 1 }
+    );
+}
+
+#[test]
+fn rejects_nodes_after_an_exclusion() {
+    let response = proof_fixture("getproof_7ae1d57b");
+    let slot = &response["storageProof"][0];
+    let mut proof = branch(&slot["proof"]);
+    // an absent key must not hide extra nodes behind the empty slot either
+    proof.push(branch(&response["accountProof"])[0].clone());
+
+    let result = MPTVerifier::verify_slot_exclusion(
+        &bytes(&response["storageHash"]),
+        bytes(&slot["key"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::LeftoverNodes { count: 1 }
+    );
+}
+
+#[test]
+fn rejects_node_not_matching_its_parent() {
+    let response = proof_fixture("getproof_7ae1d57b");
+    let mut proof = branch(&response["accountProof"]);
+    // swap in the same level node of another recorded state
+    proof[3] = branch(&proof_fixture("getproof_c36442b4")["accountProof"])[3].clone();
+
+    let result = MPTVerifier::verify_account_state(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::HashMismatch { depth: 3 }
+    );
+}
+
+#[test]
+fn rejects_truncated_proof() {
+    let response = proof_fixture("getproof_7ae1d57b");
+    let mut proof = branch(&response["accountProof"]);
+    proof.pop();
+
+    let result = MPTVerifier::verify_account_state(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::TruncatedProof { depth: 7 }
+    );
+}
+
+#[test]
+fn rejects_inclusion_when_absent() {
+    let response = proof_fixture("eip1186_example");
+    let proof = branch(&response["accountProof"]);
+    let result = MPTVerifier::verify_and_get_account_state(
+        &proof_state_root(&response),
+        bytes(&response["address"]),
+        MPTVerifier::decode_proof(&proof).unwrap(),
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        VerifierError::PathDivergence { depth: 3 }
+    );
+}
@@ -0,0 +1,143 @@
+//! Withdrawal proofs against recorded withdrawals.
+//!
+//! `mainnet_17585076.json` holds the withdrawals of mainnet block 17585076 from the builder
+//! payload alloy-rpc-types-beacon records. The header fixture is rebuilt from that payload, and
+//! its hash, the block hash the payload names, checks every field including the withdrawals root.
+//! `mainnet_17139055.json` holds the withdrawals of block 17139055 as ethportal-api records them,
+//! with the block's withdrawals root.
+
+mod common;
+
+use alloy_primitives::hex;
+use common::{header_rlp, list_trie, withdrawal_fixture};
+use obsidian_lib::header::LeanHeader;
+use obsidian_lib::verifier::VerifierError;
+use obsidian_lib::withdrawals::{WithdrawalError, WithdrawalInputs, WithdrawalVerifier};
+use serde_json::Value;
+
+fn shanghai() -> (Value, LeanHeader, Vec<Vec<u8>>) {
+    let fixture = withdrawal_fixture("mainnet_17585076");
+    let header = LeanHeader::decode(&header_rlp(fixture["header"].as_str().unwrap())).unwrap();
+    let withdrawals = common::branch(&fixture["withdrawals"]);
+    (fixture, header, withdrawals)
+}
+
+#[test]
+fn recorded_withdrawals_rebuild_the_withdrawals_root() {
+    let (fixture, header, withdrawals) = shanghai();
+    assert_eq!(header.hash().to_vec(), common::bytes(&fixture["blockHash"]));
+    let (root, _) = list_trie(&withdrawals, 0);
+    assert_eq!(root, header.withdrawals_root.unwrap());
+
+    let fixture = withdrawal_fixture("mainnet_17139055");
+    let (root, _) = list_trie(&common::branch(&fixture["withdrawals"]), 0);
+    assert_eq!(root, common::bytes(&fixture["withdrawalsRoot"]));
+}
+
+#[test]
+fn proves_every_withdrawal_against_the_header() {
+    let (fixture, header, withdrawals) = shanghai();
+
+    for position in 0..withdrawals.len() as u64 {
+        let (_, proof) = list_trie(&withdrawals, position);
+        let output = WithdrawalVerifier::verify(WithdrawalInputs {
+            header: header.clone(),
+            position,
+            proof,
+        })
+        .unwrap();
+
+        assert_eq!(output.block_hash, common::bytes(&fixture["blockHash"]));
+        assert_eq!(output.block_number, 17585076);
+        assert_eq!(output.position, position);
+        // withdrawal indices are consecutive across the block
+        assert_eq!(output.withdrawal.index, 8802575 + position);
+    }
+}
+
+#[test]
+fn reads_the_recorded_withdrawal() {
+    let fixture = withdrawal_fixture("mainnet_17139055");
+    let withdrawals = common::branch(&fixture["withdrawals"]);
+    let (_, proof) = list_trie(&withdrawals, 0);
+
+    let withdrawal = WithdrawalVerifier::verify_withdrawal(
+        &common::bytes(&fixture["withdrawalsRoot"]),
+        0,
+        &proof,
+    )
+    .unwrap();
+    assert_eq!(withdrawal.index, 1666861);
+    assert_eq!(withdrawal.validator_index, 487850);
+    assert_eq!(
+        withdrawal.address,
+        hex!("2c885c22321746ab958980a5d060be90cd3fa79b")
+    );
+    assert_eq!(withdrawal.amount, 12341278);
+}
+
+#[test]
+fn rejects_header_before_shanghai() {
+    let (_, _, withdrawals) = shanghai();
+    let (_, proof) = list_trie(&withdrawals, 0);
+    let header = LeanHeader::decode(&header_rlp("frontier_mainnet_1000001.hex")).unwrap();
+
+    let result = WithdrawalVerifier::verify(WithdrawalInputs {
+        header,
+        position: 0,
+        proof,
+    });
+    assert_eq!(result.unwrap_err(), WithdrawalError::MissingWithdrawalsRoot);
+}
+
+#[test]
+fn rejects_proof_under_another_block() {
+    let (_, header, _) = shanghai();
+    let fixture = withdrawal_fixture("mainnet_17139055");
+    let (_, proof) = list_trie(&common::branch(&fixture["withdrawals"]), 0);
+
+    let result = WithdrawalVerifier::verify(WithdrawalInputs {
+        header,
+        position: 0,
+        proof,
+    });
+    assert_eq!(
+        result.unwrap_err(),
+        WithdrawalError::Verifier(VerifierError::HashMismatch { depth: 0 })
+    );
+}
+
+#[test]
+fn rejects_short_address() {
+    let (_, _, mut withdrawals) = shanghai();
+    // drop the last address byte, the list stays short so only its length prefix changes
+    let withdrawal = &withdrawals[0];
+    let address = withdrawal.iter().position(|&b| b == 0x94).unwrap();
+    let mut short = withdrawal[..address + 20].to_vec();
+    short.extend_from_slice(&withdrawal[address + 21..]);
+    short[address] = 0x93;
+    short[0] -= 1;
+    withdrawals[0] = short;
+    let (root, proof) = list_trie(&withdrawals, 0);
+
+    let result = WithdrawalVerifier::verify_withdrawal(&root, 0, &proof);
+    assert_eq!(
+        result.unwrap_err(),
+        WithdrawalError::InvalidAddress { length: 19 }
+    );
+}
+
+#[test]
+fn rejects_withdrawal_without_amount() {
+    let (_, _, withdrawals) = shanghai();
+    // index, validator index and address, the amount cut off
+    let withdrawal = &withdrawals[0];
+    let address = withdrawal.iter().position(|&b| b == 0x94).unwrap();
+    let mut cut = withdrawal[..address + 21].to_vec();
+    cut[0] = 0xc0 + (cut.len() - 1) as u8;
+
+    assert_eq!(
+        WithdrawalVerifier::decode_withdrawal(&cut).unwrap_err(),
+        WithdrawalError::InvalidWithdrawal { items: 3 }
+    );
+}
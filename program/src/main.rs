@@ -1,25 +1,163 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
+use std::collections::BTreeMap;
+
 use obsidian_lib::{
-    pack_order,
+    bloom::{AbsenceInputs, BloomVerifier},
+    header::{HeaderFields, HeaderVerifier},
+    history::HistoryVerifier,
+    multiproof::MultiProofInputs,
+    op_stack::{L1StateInputs, OpStackVerifier, OutputRootInputs},
+    oracle::{AggregateInputs, FieldsInputs, OracleVerifier},
+    pack_account_state, pack_anchored_order, pack_block_aggregate, pack_block_fields,
+    pack_l1_state, pack_log_absence, pack_multi_account, pack_order, pack_output_root,
+    pack_raw_order, pack_withdrawal,
     states::uni_v2,
     swapper::uni_v2_swapper,
-    verifier::{MPTVerifier, VerifierOutput},
-    ObsidianInput,
+    verifier::{AccountStateInputs, MPTVerifier, VerifierOutput},
+    withdrawals::{WithdrawalInputs, WithdrawalVerifier},
+    AnchorLink, AnchoredOrderInput, ObsidianInput, ProgramInput, RawOrderInput,
 };
 
 pub fn main() {
-    let input: ObsidianInput = sp1_zkvm::io::read::<ObsidianInput>();
-    let block_number = input.block_verifier_inputs.header.number;
+    match sp1_zkvm::io::read::<ProgramInput>() {
+        ProgramInput::Order(input) => prove_order(input),
+        ProgramInput::AnchoredOrder(input) => prove_anchored_order(input),
+        ProgramInput::AccountState(input) => prove_account_state(input),
+        ProgramInput::Withdrawal(input) => prove_withdrawal(input),
+        ProgramInput::RawOrder(input) => prove_raw_order(input),
+        ProgramInput::BlockFields(input) => prove_block_fields(input),
+        ProgramInput::BlockAggregate(input) => prove_block_aggregate(input),
+        ProgramInput::LogAbsence(input) => prove_log_absence(input),
+        ProgramInput::Multiproof(input) => prove_multiproof(input),
+        ProgramInput::OutputRoot(input) => prove_output_root(input),
+        ProgramInput::L1State(input) => prove_l1_state(input),
+    }
+}
 
-    let out: VerifierOutput = MPTVerifier::verify_slot(input.block_verifier_inputs).unwrap();
+/// Runs the swap on the proven pool reserves.
+fn swap_on_slots(
+    slots: &BTreeMap<Vec<u8>, Vec<u8>>,
+    swap_payload: uni_v2_swapper::SwapInput,
+) -> uni_v2_swapper::SwapOutput {
+    let reserves = slots
+        .get(uni_v2::RESERVES_SLOT.as_slice())
+        .expect("reserves slot not proven");
     let reserves_state: uni_v2::UniV2ReservesState =
-        uni_v2::UniV2ReservesState::try_from(out.slot_data.clone()).unwrap();
+        uni_v2::UniV2ReservesState::try_from(reserves.clone()).unwrap();
+
+    uni_v2_swapper::swap(reserves_state, swap_payload)
+}
+
+/// Verifies the pool reserves at the order's block and runs the swap on them.
+fn swap_at_block(input: ObsidianInput) -> (uni_v2_swapper::SwapOutput, u64, Vec<u8>) {
+    let block_number = input.block_verifier_inputs.header.number;
+
+    let out: VerifierOutput = MPTVerifier::verify_slot(input.block_verifier_inputs)
+        .unwrap_or_else(|e| panic!("state verification failed: {}", e));
+    let swap_out = swap_on_slots(&out.slots, input.swap_payload);
+
+    (swap_out, block_number, out.block_hash)
+}
 
-    let swap_out = uni_v2_swapper::swap(reserves_state, input.swap_payload);
+fn prove_order(input: ObsidianInput) {
+    let (swap_out, block_number, block_hash) = swap_at_block(input);
 
-    let order = pack_order(swap_out, block_number, out.block_hash);
+    let order = pack_order(swap_out, block_number, block_hash);
 
     sp1_zkvm::io::commit_slice(&order);
 }
+
+fn prove_raw_order(input: RawOrderInput) {
+    // hashes the header bytes directly and reads only the fields the order needs
+    let header = HeaderFields::decode(&input.header_rlp)
+        .unwrap_or_else(|e| panic!("header decoding failed: {}", e));
+
+    let slots = MPTVerifier::verify_account_slots(&header.state_root, input.address, input.proofs)
+        .unwrap_or_else(|e| panic!("state verification failed: {}", e));
+    let swap_out = swap_on_slots(&slots, input.swap_payload);
+
+    let order = pack_raw_order(swap_out, header.number, header.hash.to_vec());
+
+    sp1_zkvm::io::commit_slice(&order);
+}
+
+fn prove_anchored_order(input: AnchoredOrderInput) {
+    let target = &input.order.block_verifier_inputs.header;
+    let ancestry = match input.link {
+        AnchorLink::Headers(descendants) => {
+            // the chain starts at the order's block and ends at the anchor settlement checks
+            let mut chain = Vec::with_capacity(descendants.len() + 1);
+            chain.push(target.clone());
+            chain.extend(descendants);
+            HeaderVerifier::verify_ancestry(&chain)
+                .unwrap_or_else(|e| panic!("header chain verification failed: {}", e))
+        }
+        AnchorLink::History(proof) => HistoryVerifier::verify(target, *proof)
+            .unwrap_or_else(|e| panic!("history verification failed: {}", e)),
+    };
+
+    let (swap_out, block_number, block_hash) = swap_at_block(input.order);
+
+    let order = pack_anchored_order(swap_out, block_number, block_hash, &ancestry);
+
+    sp1_zkvm::io::commit_slice(&order);
+}
+
+fn prove_account_state(input: AccountStateInputs) {
+    let out = MPTVerifier::verify_account(input)
+        .unwrap_or_else(|e| panic!("account verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_account_state(out));
+}
+
+fn prove_withdrawal(input: WithdrawalInputs) {
+    let out = WithdrawalVerifier::verify(input)
+        .unwrap_or_else(|e| panic!("withdrawal verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_withdrawal(out));
+}
+
+fn prove_block_fields(input: FieldsInputs) {
+    let out = OracleVerifier::verify_fields(input)
+        .unwrap_or_else(|e| panic!("header field verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_block_fields(out));
+}
+
+fn prove_block_aggregate(input: AggregateInputs) {
+    let out = OracleVerifier::verify_aggregate(input)
+        .unwrap_or_else(|e| panic!("header aggregate verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_block_aggregate(out));
+}
+
+fn prove_log_absence(input: AbsenceInputs) {
+    let out = BloomVerifier::verify_absence(input)
+        .unwrap_or_else(|e| panic!("log absence verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_log_absence(out));
+}
+
+fn prove_multiproof(input: MultiProofInputs) {
+    let block_number = input.header.number;
+    let out = MPTVerifier::verify_multiproof(input)
+        .unwrap_or_else(|e| panic!("multiproof verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_multi_account(out, block_number));
+}
+
+fn prove_output_root(input: OutputRootInputs) {
+    let out = OpStackVerifier::verify(input)
+        .unwrap_or_else(|e| panic!("output root verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_output_root(out));
+}
+
+fn prove_l1_state(input: L1StateInputs) {
+    let out = OpStackVerifier::verify_l1_state(input)
+        .unwrap_or_else(|e| panic!("l1 state verification failed: {}", e));
+
+    sp1_zkvm::io::commit_slice(&pack_l1_state(out));
+}
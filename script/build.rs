@@ -6,5 +6,13 @@ fn main() {
         output_directory: Some(String::from("../.artifacts")),
         ..Default::default()
     };
-    build_program_with_args("../program", args)
+    build_program_with_args("../program", args);
+
+    // the decode bench is its own ELF so the settled program never carries it
+    let bench_args: BuildArgs = BuildArgs {
+        elf_name: Some(String::from("decode-bench")),
+        output_directory: Some(String::from("../.artifacts")),
+        ..Default::default()
+    };
+    build_program_with_args("../bench-program", bench_args)
 }
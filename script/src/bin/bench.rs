@@ -0,0 +1,103 @@
+//! Compares zkVM cycles spent decoding proof nodes through the owned and borrowed decoders.
+//!
+//! Fetches the uniswap pool proof from a Base rpc and decodes its account path cut at several
+//! depths, then the full account path plus the reserves storage path.
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin bench -- --rpc-url <base rpc>
+//! ```
+//! The rpc url may also be set with `RPC_URL`.
+
+use std::str::FromStr;
+
+use alloy::{
+    primitives::{address, b256},
+    providers::{Provider, ProviderBuilder},
+    rpc,
+    transports::http::reqwest,
+};
+use clap::Parser;
+use obsidian_lib::{DecodeBenchInput, DecodePath};
+use sp1_sdk::{include_elf, EnvProver, ProverClient, SP1Stdin};
+
+/// The decode bench program, built separately from the obsidian program.
+pub const DECODE_BENCH_ELF: &[u8] = include_elf!("decode-bench");
+
+const DEPTHS: [usize; 4] = [4, 6, 8, 10];
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct BenchArgs {
+    /// Base rpc serving `eth_getProof`
+    #[clap(long, env = "RPC_URL")]
+    rpc_url: String,
+}
+
+fn cycles(client: &EnvProver, path: DecodePath, proofs: &[Vec<Vec<u8>>]) -> u64 {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&DecodeBenchInput {
+        path,
+        proofs: proofs.to_vec(),
+    });
+    let (_, report) = client.execute(DECODE_BENCH_ELF, &stdin).run().unwrap();
+    report.total_instruction_count()
+}
+
+fn report(client: &EnvProver, label: &str, proofs: &[Vec<Vec<u8>>]) {
+    let owned = cycles(client, DecodePath::Owned, proofs);
+    let borrowed = cycles(client, DecodePath::Borrowed, proofs);
+    println!(
+        "{:<24} owned {:>10} borrowed {:>10} saved {:>6.2}%",
+        label,
+        owned,
+        borrowed,
+        100.0 * (owned as f64 - borrowed as f64) / owned as f64
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+
+    let args = BenchArgs::parse();
+    let pool_address = address!("0x88a43bbdf9d098eec7bceda4e2494615dfd9bb9c");
+    let reserves_slot = b256!("0000000000000000000000000000000000000000000000000000000000000008");
+
+    let provider = ProviderBuilder::new().on_http(reqwest::Url::from_str(&args.rpc_url).unwrap());
+    let proof: rpc::types::EIP1186AccountProofResponse = provider
+        .get_proof(pool_address, vec![reserves_slot])
+        .await
+        .unwrap();
+
+    let account_proof: Vec<Vec<u8>> = proof.account_proof.iter().map(|n| n.to_vec()).collect();
+    let storage_proof: Vec<Vec<u8>> = proof.storage_proof[0]
+        .proof
+        .iter()
+        .map(|n| n.to_vec())
+        .collect();
+    println!(
+        "account proof depth {}, storage proof depth {}",
+        account_proof.len(),
+        storage_proof.len()
+    );
+
+    let client = ProverClient::from_env();
+
+    // both paths pay the same stdin read, the difference is decoding alone
+    report(&client, "empty", &[]);
+    for depth in DEPTHS {
+        if depth > account_proof.len() {
+            break;
+        }
+        report(
+            &client,
+            &format!("account depth {}", depth),
+            &[account_proof[..depth].to_vec()],
+        );
+    }
+    report(
+        &client,
+        "account + storage",
+        &[account_proof, storage_proof],
+    );
+}
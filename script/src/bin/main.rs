@@ -30,8 +30,8 @@ use obsidian_lib::{
     header::LeanHeader,
     states::uni_v2,
     swapper::uni_v2_swapper::{self, SwapInput},
-    verifier::{Node, Proofs, VerifierInputs},
-    ObsidianInput,
+    verifier::{Node, Proofs, StorageProof, VerifierInputs},
+    ObsidianInput, ProgramInput,
 };
 use sp1_sdk::{include_elf, HashableKey, ProverClient, SP1Stdin};
 
@@ -101,19 +101,21 @@ async fn main() {
         account_collector.push(acc.to_vec());
     }
 
-    let slots = proof.storage_proof.clone();
-    let mut storage_collector: Vec<Vec<u8>> = Vec::new();
-    for acc in slots.first().unwrap().clone().proof {
-        storage_collector.push(acc.to_vec());
-    }
+    let storage_proofs: Vec<StorageProof> = uniswap_storage_slot
+        .iter()
+        .zip(proof.storage_proof.iter())
+        .map(|(slot, storage)| StorageProof {
+            slot: slot.to_vec(),
+            proof: storage.proof.iter().map(|node| node.to_vec()).collect(),
+        })
+        .collect();
 
     let inputs = VerifierInputs {
         header: LeanHeader::from(latest.header.inner),
         address: pool_address.to_vec(),
-        storage_slot: uniswap_storage_slot.first().unwrap().to_vec(),
         proofs: Proofs {
             account_proof: account_collector,
-            storage_proof: storage_collector,
+            storage_proofs,
         },
     };
 
@@ -132,8 +134,9 @@ async fn main() {
     if args.local {
         let out = obsidian_lib::verifier::MPTVerifier::verify_slot(inputs).unwrap();
         println!("{:0x?}", out);
+        let reserves = out.slots.get(uni_v2::RESERVES_SLOT.as_slice()).unwrap();
         let reserves_state: uni_v2::UniV2ReservesState =
-            uni_v2::UniV2ReservesState::try_from(out.slot_data.clone()).unwrap();
+            uni_v2::UniV2ReservesState::try_from(reserves.clone()).unwrap();
 
         let swap_out = uni_v2_swapper::swap(reserves_state, swap_payload);
         println!("swap out {:0x?}", swap_out);
@@ -148,7 +151,7 @@ async fn main() {
         // setup vms
         let client = ProverClient::from_env();
         let mut stdin = SP1Stdin::new();
-        stdin.write(&vm_input);
+        stdin.write(&ProgramInput::Order(vm_input));
 
         if args.execute {
             // Execute the program
@@ -5,7 +5,7 @@ use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::Path;
 
 use alloy::{hex, primitives::Keccak256};
-use obsidian_lib::ObsidianInput;
+use obsidian_lib::ProgramInput;
 use sp1_sdk::{include_elf, EnvProver, HashableKey, ProverClient, SP1ProvingKey, SP1Stdin};
 
 const OBSIDIAN_ELF: &[u8] = include_elf!("obsidian-program");
@@ -58,7 +58,7 @@ fn handle_client(client: &EnvProver, pk: &SP1ProvingKey, mut stream: UnixStream)
         return;
     }
 
-    let request: ObsidianInput = match bincode::deserialize(&buffer) {
+    let request: ProgramInput = match bincode::deserialize(&buffer) {
         Ok(req) => req,
         Err(e) => {
             log::error!("failed to parse request: {}", e);
@@ -99,7 +99,7 @@ fn send_response(stream: &mut UnixStream, response: &NewOrderResponse) {
 fn generate_proof(
     client: &EnvProver,
     pk: &SP1ProvingKey,
-    vm_input: ObsidianInput,
+    vm_input: ProgramInput,
 ) -> NewOrderResponse {
     let mut stdin = SP1Stdin::new();
     stdin.write(&vm_input);
@@ -1,7 +1,9 @@
+pub mod multiproof;
 pub mod order_processor;
 pub mod prover;
 pub mod router;
 pub mod server;
+pub mod state_processor;
 
 pub struct Config {
     host: String,
@@ -0,0 +1,48 @@
+use alloy::rpc::types::EIP1186AccountProofResponse;
+use obsidian_lib::{
+    header::LeanHeader,
+    multiproof::{MultiProof, MultiProofAccount, MultiProofInputs},
+};
+
+/// Converts several `eth_getProof` responses taken at the same block into one multiproof,
+/// storing every trie node once.
+pub fn build_multiproof_inputs(
+    header: LeanHeader,
+    responses: &[EIP1186AccountProofResponse],
+) -> MultiProofInputs {
+    let mut proofs: Vec<Vec<Vec<u8>>> = Vec::new();
+    let mut accounts: Vec<MultiProofAccount> = Vec::with_capacity(responses.len());
+
+    for response in responses {
+        proofs.push(
+            response
+                .account_proof
+                .iter()
+                .map(|node| node.to_vec())
+                .collect(),
+        );
+
+        let mut slots: Vec<Vec<u8>> = Vec::with_capacity(response.storage_proof.len());
+        for storage in &response.storage_proof {
+            slots.push(storage.key.as_b256().to_vec());
+            proofs.push(storage.proof.iter().map(|node| node.to_vec()).collect());
+        }
+
+        accounts.push(MultiProofAccount {
+            address: response.address.to_vec(),
+            slots,
+        });
+    }
+
+    log::debug!(
+        "built multiproof for {} accounts from {} proofs",
+        accounts.len(),
+        proofs.len()
+    );
+
+    MultiProofInputs {
+        header,
+        accounts,
+        proof: MultiProof::from_proofs(&proofs),
+    }
+}
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use alloy::{
     hex,
-    primitives::{b256, Address},
+    primitives::{b256, Address, B256},
     providers::ProviderBuilder,
     rpc,
     transports::http::reqwest,
@@ -12,11 +12,13 @@ use alloy_provider::Provider;
 use obsidian_lib::{
     header::LeanHeader,
     swapper::uni_v2_swapper::SwapInput,
-    verifier::{Proofs, VerifierInputs},
-    ObsidianInput,
+    verifier::{MPTVerifier, Proofs, StorageProof, VerifierInputs},
+    ObsidianInput, ProgramInput,
 };
 
+use crate::multiproof::build_multiproof_inputs;
 use crate::order_processor::{NewOrderRequest, NewOrderResponse};
+use crate::state_processor::StateRequest;
 
 pub const RPC_URL: &str = "https://base-rpc.publicnode.com";
 
@@ -38,9 +40,6 @@ impl Prover {
     }
 
     pub async fn prove(&self, request: NewOrderRequest) -> Result<NewOrderResponse, String> {
-        use tokio::io::{AsyncReadExt, AsyncWriteExt};
-        use tokio::net::UnixStream;
-
         log::info!("preparing proof inputs for request");
 
         // prepare uniswap storage slot
@@ -110,112 +109,193 @@ impl Prover {
             account_collector.push(acc.to_vec());
         }
 
-        // collect storage proofs
-        let slots = proof.storage_proof.clone();
-        if let Some(first_slot) = slots.first() {
-            let mut storage_collector: Vec<Vec<u8>> = Vec::new();
-            for acc in first_slot.clone().proof {
-                storage_collector.push(acc.to_vec());
-            }
+        // collect storage proofs, eth_getProof answers in the order slots were requested
+        if proof.storage_proof.len() != uniswap_storage_slot.len() {
+            return Err(format!(
+                "expected {} storage proofs found {}",
+                uniswap_storage_slot.len(),
+                proof.storage_proof.len()
+            ));
+        }
+        let storage_proofs: Vec<StorageProof> = uniswap_storage_slot
+            .iter()
+            .zip(proof.storage_proof.iter())
+            .map(|(slot, storage)| StorageProof {
+                slot: slot.to_vec(),
+                proof: storage.proof.iter().map(|node| node.to_vec()).collect(),
+            })
+            .collect();
+
+        // parse token addresses
+        let sell_token = match Address::from_str(&request.sell_token) {
+            Ok(addr) => addr,
+            Err(e) => return Err(format!("invalid sell token address: {}", e)),
+        };
 
-            // parse token addresses
-            let sell_token = match Address::from_str(&request.sell_token) {
-                Ok(addr) => addr,
-                Err(e) => return Err(format!("invalid sell token address: {}", e)),
-            };
+        let buy_token = match Address::from_str(&request.buy_token) {
+            Ok(addr) => addr,
+            Err(e) => return Err(format!("invalid buy token address: {}", e)),
+        };
 
-            let buy_token = match Address::from_str(&request.buy_token) {
-                Ok(addr) => addr,
-                Err(e) => return Err(format!("invalid buy token address: {}", e)),
-            };
+        let seller = match Address::from_str(&request.address) {
+            Ok(addr) => addr,
+            Err(e) => return Err(format!("invalid seller address: {}", e)),
+        };
 
-            let seller = match Address::from_str(&request.address) {
-                Ok(addr) => addr,
-                Err(e) => return Err(format!("invalid seller address: {}", e)),
-            };
+        // parse sell amount
+        let sell_amount = match U256::from_str_radix(&request.amount, 10) {
+            Ok(amount) => amount,
+            Err(e) => return Err(format!("invalid sell amount: {}", e)),
+        };
 
-            // parse sell amount
-            let sell_amount = match U256::from_str_radix(&request.amount, 10) {
-                Ok(amount) => amount,
-                Err(e) => return Err(format!("invalid sell amount: {}", e)),
-            };
+        // prepare verifier inputs
+        let inputs = VerifierInputs {
+            header: LeanHeader::from(latest.header.inner),
+            address: pool_address.to_vec(),
+            proofs: Proofs {
+                account_proof: account_collector,
+                storage_proofs,
+            },
+        };
 
-            // prepare verifier inputs
-            let inputs = VerifierInputs {
-                header: LeanHeader::from(latest.header.inner),
-                address: pool_address.to_vec(),
-                storage_slot: uniswap_storage_slot.first().unwrap().to_vec(),
-                proofs: Proofs {
-                    account_proof: account_collector,
-                    storage_proof: storage_collector,
-                },
-            };
+        // reject proofs the rpc got wrong before spending time proving them
+        if let Err(e) = MPTVerifier::verify_slot(inputs.clone()) {
+            return Err(format!("rpc proof failed verification: {}", e));
+        }
 
-            // prepare swap payload
-            let swap_payload = SwapInput {
-                sell_amount: sell_amount.to_be_bytes_vec(),
-                sell_token0: false,
-                sell_token: sell_token.to_vec(),
-                buy_token: buy_token.to_vec(),
-                seller: seller.to_vec(),
-            };
+        // prepare swap payload
+        let swap_payload = SwapInput {
+            sell_amount: sell_amount.to_be_bytes_vec(),
+            sell_token0: false,
+            sell_token: sell_token.to_vec(),
+            buy_token: buy_token.to_vec(),
+            seller: seller.to_vec(),
+        };
 
-            // combine inputs
-            let vm_input = ObsidianInput {
-                swap_payload,
-                block_verifier_inputs: inputs,
-            };
+        // combine inputs
+        let vm_input = ObsidianInput {
+            swap_payload,
+            block_verifier_inputs: inputs,
+        };
 
-            // connect to prover service
-            log::info!("connecting to prover service at {}", self.socket_path);
-            let mut stream = match UnixStream::connect(&self.socket_path).await {
-                Ok(stream) => stream,
-                Err(e) => return Err(format!("failed to connect to prover: {}", e)),
-            };
+        let mut prover_response = self.request_proof(&ProgramInput::Order(vm_input)).await?;
 
-            // send request
-            let request_bytes = match bincode::serialize(&vm_input) {
-                Ok(bytes) => bytes,
-                Err(e) => return Err(format!("failed to serialize request: {}", e)),
-            };
+        // add block number to response
+        prover_response.block = block_number;
 
-            if let Err(e) = stream.write_all(&request_bytes).await {
-                return Err(format!("failed to send request: {}", e));
-            }
+        Ok(prover_response)
+    }
 
-            // shutdown write to signal end of request
-            if let Err(e) = stream.shutdown().await {
-                return Err(format!("failed to shutdown write: {}", e));
-            }
+    /// Proves every account and slot at `block` in one multiproof.
+    pub async fn prove_state(&self, request: StateRequest) -> Result<NewOrderResponse, String> {
+        let provider = ProviderBuilder::new().on_http(match reqwest::Url::from_str(RPC_URL) {
+            Ok(url) => url,
+            Err(e) => return Err(format!("invalid RPC URL: {}", e)),
+        });
 
-            log::info!("request sent, waiting for proof generation");
+        let block = match provider
+            .get_block_by_number(rpc::types::BlockNumberOrTag::Number(request.block))
+            .await
+        {
+            Ok(Some(block)) => block,
+            Ok(None) => return Err(format!("block {} not found", request.block)),
+            Err(e) => return Err(format!("failed to fetch block: {}", e)),
+        };
 
-            // read response
-            let mut buffer = Vec::new();
-            if let Err(e) = stream.read_to_end(&mut buffer).await {
-                return Err(format!("failed to read response: {}", e));
+        let mut responses = Vec::with_capacity(request.accounts.len());
+        for account in &request.accounts {
+            let address = match Address::from_str(&account.address) {
+                Ok(addr) => addr,
+                Err(e) => return Err(format!("invalid account address: {}", e)),
+            };
+            let mut slots = Vec::with_capacity(account.slots.len());
+            for slot in &account.slots {
+                match B256::from_str(slot) {
+                    Ok(slot) => slots.push(slot),
+                    Err(e) => return Err(format!("invalid storage slot: {}", e)),
+                }
             }
 
-            // deserialize response
-            let mut prover_response: NewOrderResponse = match bincode::deserialize(&buffer) {
-                Ok(resp) => resp,
-                Err(e) => return Err(format!("failed to deserialize response: {}", e)),
-            };
+            let params = serde_json::json!([
+                format!("{:#x}", address),
+                slots
+                    .iter()
+                    .map(|slot| format!("{:#x}", slot))
+                    .collect::<Vec<String>>(),
+                format!("0x{:x}", request.block)
+            ]);
+            let proof: rpc::types::EIP1186AccountProofResponse =
+                match provider.raw_request("eth_getProof".into(), params).await {
+                    Ok(proof) => proof,
+                    Err(e) => return Err(format!("failed to get proof: {}", e)),
+                };
+            responses.push(proof);
+        }
 
-            // check for error in response
-            if let Some(error) = &prover_response.error {
-                log::error!("prover service returned error: {}", error);
-                return Err(format!("prover service error: {}", error));
-            }
+        let inputs = build_multiproof_inputs(LeanHeader::from(block.header.inner), &responses);
 
-            log::info!("successfully received proof");
+        // reject proofs the rpc got wrong before spending time proving them
+        if let Err(e) = MPTVerifier::verify_multiproof(inputs.clone()) {
+            return Err(format!("rpc proof failed verification: {}", e));
+        }
 
-            // add block number to response
-            prover_response.block = block_number;
+        let mut prover_response = self
+            .request_proof(&ProgramInput::Multiproof(inputs))
+            .await?;
+        prover_response.block = request.block;
 
-            Ok(prover_response)
-        } else {
-            Err("no storage slots found in proof".to_string())
+        Ok(prover_response)
+    }
+
+    /// Sends a program input to the prover service and waits for its proof.
+    async fn request_proof(&self, input: &ProgramInput) -> Result<NewOrderResponse, String> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::UnixStream;
+
+        // connect to prover service
+        log::info!("connecting to prover service at {}", self.socket_path);
+        let mut stream = match UnixStream::connect(&self.socket_path).await {
+            Ok(stream) => stream,
+            Err(e) => return Err(format!("failed to connect to prover: {}", e)),
+        };
+
+        // send request
+        let request_bytes = match bincode::serialize(input) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(format!("failed to serialize request: {}", e)),
+        };
+
+        if let Err(e) = stream.write_all(&request_bytes).await {
+            return Err(format!("failed to send request: {}", e));
+        }
+
+        // shutdown write to signal end of request
+        if let Err(e) = stream.shutdown().await {
+            return Err(format!("failed to shutdown write: {}", e));
+        }
+
+        log::info!("request sent, waiting for proof generation");
+
+        // read response
+        let mut buffer = Vec::new();
+        if let Err(e) = stream.read_to_end(&mut buffer).await {
+            return Err(format!("failed to read response: {}", e));
+        }
+
+        // deserialize response
+        let prover_response: NewOrderResponse = match bincode::deserialize(&buffer) {
+            Ok(resp) => resp,
+            Err(e) => return Err(format!("failed to deserialize response: {}", e)),
+        };
+
+        // check for error in response
+        if let Some(error) = &prover_response.error {
+            log::error!("prover service returned error: {}", error);
+            return Err(format!("prover service error: {}", error));
         }
+
+        log::info!("successfully received proof");
+
+        Ok(prover_response)
     }
 }
@@ -1,7 +1,11 @@
 use actix_web::web;
 
-use crate::order_processor;
+use crate::{order_processor, state_processor};
 
 pub fn router(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/v1").route("/order", web::post().to(order_processor::process_order)));
+    cfg.service(
+        web::scope("/v1")
+            .route("/order", web::post().to(order_processor::process_order))
+            .route("/state", web::post().to(state_processor::process_state)),
+    );
 }
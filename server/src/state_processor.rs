@@ -0,0 +1,47 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::order_processor::NewOrderResponse;
+use crate::prover::Prover;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateAccount {
+    pub address: String,
+    pub slots: Vec<String>,
+}
+
+/// Accounts and storage slots to prove at one block of the chain behind
+/// [`RPC_URL`](crate::prover::RPC_URL).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateRequest {
+    pub block: u64,
+    pub accounts: Vec<StateAccount>,
+}
+
+pub async fn process_state(request: web::Json<StateRequest>) -> impl Responder {
+    log::info!(
+        "received state request for {} accounts at block {}",
+        request.accounts.len(),
+        request.block
+    );
+    log::debug!("state request details: {:?}", request);
+
+    let prover = Prover::new();
+
+    match prover.prove_state(request.clone()).await {
+        Ok(proved_state) => {
+            log::info!("successfully proved state at block {}", proved_state.block);
+            HttpResponse::Ok().json(proved_state)
+        }
+        Err(error) => {
+            log::error!("state proving failed: {}", error);
+            let error_response = NewOrderResponse {
+                block: 0,
+                proof: String::new(),
+                public_values: String::new(),
+                error: Some(error),
+            };
+            HttpResponse::InternalServerError().json(error_response)
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use alloy::rpc::types::EIP1186AccountProofResponse;
+use alloy_primitives::hex;
+use obsidian_lib::{header::LeanHeader, verifier::MPTVerifier};
+use serde_json::Value;
+use server::multiproof::build_multiproof_inputs;
+
+/// eth_getProof responses recorded at OP Sepolia block 26207959, shared with the lib tests.
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../lib/tests/fixtures/proofs/op_sepolia_26207959.json"
+);
+
+#[test]
+fn builds_verifiable_multiproof_from_responses() {
+    let fixture: Value = serde_json::from_str(&std::fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let header =
+        LeanHeader::decode(&hex::decode(fixture["header"].as_str().unwrap()).unwrap()).unwrap();
+    let responses: Vec<EIP1186AccountProofResponse> =
+        serde_json::from_value(fixture["accounts"].clone()).unwrap();
+
+    let inputs = build_multiproof_inputs(header, &responses);
+    let output = MPTVerifier::verify_multiproof(inputs.clone()).unwrap();
+
+    assert_eq!(inputs.accounts.len(), responses.len());
+    for (account, response) in output.accounts.iter().zip(&responses) {
+        assert_eq!(account.address, response.address.to_vec());
+        assert_eq!(account.slots.len(), response.storage_proof.len());
+        for storage in &response.storage_proof {
+            let value = &account.slots[&storage.key.as_b256().to_vec()];
+            assert_eq!(alloy_primitives::U256::from_be_slice(value), storage.value);
+        }
+    }
+}